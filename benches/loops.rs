@@ -0,0 +1,27 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use cry_script::engine::{Backend, Engine};
+
+const SCRIPTS: [&str; 3] = ["counter", "nested_blocks", "function_locals"];
+
+fn loops(c: &mut Criterion) {
+    for (name, backend) in [
+        ("tree", Backend::TreeWalker),
+        ("bytecode", Backend::Bytecode),
+    ] {
+        let mut engine = Engine::new();
+        engine.set_backend(backend);
+        for script in SCRIPTS {
+            let path = format!(
+                "{}/benches/scripts/{}.cry",
+                env!("CARGO_MANIFEST_DIR"),
+                script
+            );
+            c.bench_function(&format!("{}/{}", name, script), |b| {
+                b.iter(|| engine.run_file(&path).unwrap())
+            });
+        }
+    }
+}
+
+criterion_group!(benches, loops);
+criterion_main!(benches);
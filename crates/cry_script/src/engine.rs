@@ -0,0 +1,401 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    env,
+    fs::File,
+    io::Read,
+    path::PathBuf,
+    rc::Rc,
+    time::Duration,
+};
+
+use crate::{
+    exceptions::{
+        interpreter_exceptions::UnableToOpenFile, lexer_exceptions::UnterminatedString, Exception,
+    },
+    interpreter::{context::Context, runtime::Runtime},
+    lexer::Lexer,
+    lint,
+    lints::{Lint, LintLevel},
+    parser::{data::Value, Parser},
+    resolver::RootScope,
+    run_with_data,
+    variables::Variables,
+    FileData, Flow, Position, Returnable, STD_DIR,
+};
+
+/// Limits a host can put on a single run, `None` means unlimited
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// Maximum amount of instructions and loop iterations a script may execute
+    pub max_steps: Option<u64>,
+    /// Maximum wall-clock time a script may run for
+    pub time_limit: Option<Duration>,
+    /// Maximum bytes the script's variables may take up, including the strings
+    /// and class instances they hold
+    pub max_memory: Option<usize>,
+}
+
+/// Which files the standard library and `use` statements may touch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileSystemAccess {
+    /// Anything the host process itself can access
+    Full,
+    /// Only files inside this directory, relative paths are resolved against it
+    Restricted(PathBuf),
+    /// No file access at all
+    Denied,
+}
+
+/// What a script is allowed to do outside of the interpreter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    pub file_system: FileSystemAccess,
+    /// Whether `read()` may block on the host's stdin
+    pub stdin: bool,
+    /// Whether `exit()` terminates the host process, when disabled
+    /// it only stops the script and [`Engine::run_file`] returns an
+    /// [`ExceptionKind::Exit`](crate::exceptions::ExceptionKind::Exit)
+    pub process_exit: bool,
+    /// Whether scripts start out with the host process' environment variables,
+    /// ignored when the host passes its own to [`Engine::set_env`]
+    pub environment: bool,
+}
+
+impl Capabilities {
+    /// No file system, no stdin, no environment variables and `exit()` only stops the script
+    pub fn sandboxed() -> Self {
+        Self {
+            file_system: FileSystemAccess::Denied,
+            stdin: false,
+            process_exit: false,
+            environment: false,
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            file_system: FileSystemAccess::Full,
+            stdin: true,
+            process_exit: true,
+            environment: true,
+        }
+    }
+}
+
+/// How scripts are executed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Walks the instruction tree directly
+    #[default]
+    TreeWalker,
+    /// Compiles every file and function to bytecode first and runs it on a stack machine
+    Bytecode,
+}
+
+/// What the garbage collector did during a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// How many times the collector ran
+    pub collections: u64,
+    /// Variables freed over all collections
+    pub freed_variables: u64,
+    /// Bytes freed over all collections
+    pub freed_bytes: u64,
+    /// Variables still allocated when the run finished
+    pub live_variables: u64,
+    /// Bytes still allocated when the run finished
+    pub live_bytes: u64,
+}
+
+/// Entry point for hosts embedding CryScript
+#[derive(Debug, Clone)]
+pub struct Engine {
+    pub(crate) limits: Limits,
+    pub(crate) capabilities: Capabilities,
+    pub(crate) backend: Backend,
+    pub(crate) optimize: bool,
+    pub(crate) args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+    pub(crate) lint_levels: HashMap<Lint, LintLevel>,
+    pub(crate) seed: Option<u64>,
+    gc_stats: Cell<GcStats>,
+    warnings: RefCell<Vec<Exception>>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self {
+            limits: Limits::default(),
+            capabilities: Capabilities::default(),
+            backend: Backend::default(),
+            optimize: true,
+            args: vec![],
+            env: None,
+            lint_levels: HashMap::new(),
+            seed: None,
+            gc_stats: Cell::default(),
+            warnings: RefCell::default(),
+        }
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits
+    }
+
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.backend = backend
+    }
+
+    pub fn optimize(&self) -> bool {
+        self.optimize
+    }
+
+    /// Turns constant folding and dead code elimination on or off, on by default.
+    /// Turning it off keeps every instruction around which can help while debugging
+    pub fn set_optimize(&mut self, optimize: bool) {
+        self.optimize = optimize
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Arguments scripts can read with `args()`, `arg(index)` and `arg_count()`
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args
+    }
+
+    pub fn env(&self) -> Option<&HashMap<String, String>> {
+        self.env.as_ref()
+    }
+
+    /// Environment variables scripts see instead of the host process' own.
+    /// `set_env` in a script only changes its own copy, never the host's
+    pub fn set_env(&mut self, env: HashMap<String, String>) {
+        self.env = Some(env)
+    }
+
+    pub fn lint_level(&self, lint: Lint) -> LintLevel {
+        self.lint_levels.get(&lint).copied().unwrap_or_default()
+    }
+
+    /// Changes what happens when `lint` finds something, every lint warns by default.
+    /// The standard library is never linted
+    pub fn set_lint_level(&mut self, lint: Lint, level: LintLevel) {
+        self.lint_levels.insert(lint, level);
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Seeds every random number generator of a run, so scripts using `std_rand`
+    /// get the same numbers every time
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed)
+    }
+
+    /// Warnings the linter reported for the files of the last run or check
+    pub fn warnings(&self) -> Vec<Exception> {
+        self.warnings.borrow().clone()
+    }
+
+    /// Garbage collector statistics of the last [`Engine::run_file`]
+    pub fn gc_stats(&self) -> GcStats {
+        self.gc_stats.get()
+    }
+
+    /// Runs the file at `root_file_path` with the standard library loaded.
+    ///
+    /// Errors are handed back to the host instead of terminating the process,
+    /// use [`Exception::kind`] to tell script errors apart from exceeded limits
+    pub fn run_file(&self, root_file_path: &str) -> Result<Returnable, Exception> {
+        let file_data = read_file(root_file_path)?;
+        self.run(file_data.clone(), |context| {
+            run_with_data(file_data, context, RootScope::Owned)
+        })
+    }
+
+    /// Runs `source` like the contents of a file, with the standard library loaded
+    pub fn eval(&self, source: &str) -> Result<Returnable, Exception> {
+        let file_data = Rc::new(FileData::from_source(source, "eval".to_string()));
+        self.run(file_data.clone(), |context| {
+            run_with_data(file_data, context, RootScope::Owned)
+        })
+    }
+
+    /// Lexes, parses and lints the file at `file_path` without running it
+    pub fn check_file(&self, file_path: &str) -> Result<(), Exception> {
+        let file_data = read_file(file_path)?;
+        let instructions = Parser::parse(file_data.clone(), Lexer::lex(file_data.clone())?)?;
+        let context = self.root_context(file_data.clone());
+        let result = lint(&instructions, &file_data, &context, RootScope::Owned);
+        self.warnings
+            .replace(std::mem::take(&mut context.runtime.borrow_mut().warnings));
+        result
+    }
+
+    /// Sets up a fresh runtime with the standard library and runs `run` in its root context
+    fn run(
+        &self,
+        file_data: Rc<FileData>,
+        run: impl FnOnce(&Context) -> Result<Flow, Exception>,
+    ) -> Result<Returnable, Exception> {
+        let context = self.root_context(file_data);
+        let variables = context.variables.clone();
+        let result = load_std(&context)
+            .and_then(|_| run(&context))
+            .map(|v| v.into_returnable().into_original());
+        self.warnings
+            .replace(std::mem::take(&mut context.runtime.borrow_mut().warnings));
+        // Class instances and functions keep their contexts alive through
+        // the variables they're stored in, clearing them breaks those cycles
+        drop(context);
+        self.gc_stats.set(variables.borrow().stats());
+        Variables::clear(&variables);
+        result
+    }
+
+    fn root_context(&self, file_data: Rc<FileData>) -> Context {
+        let runtime = Runtime::new(self);
+        Context::new_root(
+            file_data,
+            Variables::new(self.limits.max_memory),
+            Rc::new(RefCell::new(runtime)),
+        )
+    }
+
+    /// The environment variables a run starts out with
+    pub(crate) fn script_env(&self) -> HashMap<String, String> {
+        match &self.env {
+            Some(v) => v.clone(),
+            // Variables that aren't valid unicode can't be represented as a string
+            None if self.capabilities.environment => env::vars_os()
+                .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+                .collect(),
+            None => HashMap::new(),
+        }
+    }
+
+    /// Starts a [`Session`] with the standard library loaded
+    pub fn session(&self) -> Result<Session, Exception> {
+        let context = self.root_context(Rc::new(FileData::new(String::new(), "repl".to_string())));
+        let mut session = Session {
+            context,
+            std_names: vec![],
+        };
+        load_std(&session.context)?;
+        session.std_names = session.context.variable_names();
+        Ok(session)
+    }
+}
+
+/// A root context that stays alive between runs, declarations made by one
+/// [`Session::eval`] can be used by the next
+pub struct Session {
+    context: Context,
+    /// Declared by the standard library, left out of [`Session::variables`]
+    std_names: Vec<String>,
+}
+
+impl Session {
+    /// Runs `source` in the session's context, limits apply to each run separately
+    pub fn eval(&mut self, source: &str) -> Result<Returnable, Exception> {
+        let file_data = Rc::new(FileData::from_source(source, "repl".to_string()));
+        self.context.runtime.borrow_mut().budget.reset();
+        run_with_data(file_data, &self.context, RootScope::Shared)
+            .map(|v| v.into_returnable().into_original())
+    }
+
+    /// Runs the file at `file_path` in the session's context, the engine's file system
+    /// capability applies like it does to files a script uses
+    pub fn load(&mut self, file_path: &str) -> Result<Returnable, Exception> {
+        let position = Position::new(0);
+        let path = self.context.runtime.borrow().resolve_path(
+            file_path,
+            (&position, &position, &self.context.file_data()),
+        )?;
+        let file_data = read_file(&path.to_string_lossy())?;
+        self.context.runtime.borrow_mut().budget.reset();
+        run_with_data(file_data, &self.context, RootScope::Shared)
+            .map(|v| v.into_returnable().into_original())
+    }
+
+    /// Whether `source` leaves a curly bracket or a string open, so more lines belong to it
+    pub fn is_incomplete(&self, source: &str) -> bool {
+        let file_data = Rc::new(FileData::from_source(source, "repl".to_string()));
+        match Lexer::open_indentation(file_data) {
+            Ok(v) => v > 0,
+            Err(e) => e.diagnostic().code == Some(UnterminatedString::CODE),
+        }
+    }
+
+    /// Variables declared in the session sorted by name, without the standard library
+    pub fn variables(&self) -> Vec<(String, Value)> {
+        let mut variables: Vec<_> = self
+            .context
+            .variable_values()
+            .into_iter()
+            .filter(|(k, _)| !self.std_names.contains(k))
+            .collect();
+        variables.sort_by(|a, b| a.0.cmp(&b.0));
+        variables
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        Variables::clear(&self.context.variables)
+    }
+}
+
+/// Runs `std.cry` in `context` and resets the budget so it doesn't count against the script
+fn load_std(context: &Context) -> Result<Flow, Exception> {
+    let result = Context::import_data(
+        context,
+        Rc::new(FileData::new(
+            STD_DIR
+                .get_file("std.cry")
+                .unwrap()
+                .contents_utf8()
+                .unwrap()
+                .to_string(),
+            "std".to_string(),
+        )),
+    );
+    context.runtime.borrow_mut().budget.reset();
+    result
+}
+
+fn read_file(file_path: &str) -> Result<Rc<FileData>, Exception> {
+    let mut data = String::new();
+    match File::open(file_path).and_then(|mut v| v.read_to_string(&mut data)) {
+        Ok(_) => Ok(Rc::new(FileData::from_source(&data, file_path.to_string()))),
+        Err(_) => Err(UnableToOpenFile::call(file_path)),
+    }
+}
@@ -1,14 +1,18 @@
+pub mod diagnostic;
 pub mod interpreter_exceptions;
 pub mod lexer_exceptions;
+pub mod lint_exceptions;
 pub mod parser_exceptions;
 
 use std::rc::Rc;
 
 use colored::{Color, Colorize};
-use utils::StringUtils;
+use utils::edit_distance;
 
 use crate::{FileData, Position};
 
+use self::diagnostic::{Diagnostic, RelatedSpan, Severity, Span};
+
 macro_rules! crash {
     () => {
         // panic!()
@@ -31,6 +35,19 @@ pub const EXCEPTION: ErrorColourScheme = ErrorColourScheme {
     note_colour: ORANGE,
     message: "error",
     message_colour: Color::BrightRed,
+    severity: Severity::Error,
+};
+
+pub const WARNING: ErrorColourScheme = ErrorColourScheme {
+    arrow_to_message: Color::BrightYellow,
+    line_number: ORANGE,
+    separator: Color::BrightYellow,
+    arrow_to_error: Color::BrightYellow,
+    equal: Color::BrightYellow,
+    note_colour: ORANGE,
+    message: "warning",
+    message_colour: Color::BrightYellow,
+    severity: Severity::Warning,
 };
 
 pub struct ErrorColourScheme {
@@ -42,24 +59,158 @@ pub struct ErrorColourScheme {
     message: &'static str,
     message_colour: Color,
     note_colour: Color,
+    severity: Severity,
+}
+
+/// What kind of failure an [`Exception`] represents
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExceptionKind {
+    /// A regular script error, scripts can recover from it with `do { } catch { }`
+    Error,
+    /// The script executed more instructions than its step budget allows
+    StepLimitExceeded,
+    /// The script ran past the deadline set by the host
+    TimeLimitExceeded,
+    /// The script called `exit` while the host doesn't allow it to end the process
+    Exit(i32),
+    /// The script's variables need more memory than the host allows
+    OutOfMemory,
 }
 
-#[derive(Clone)]
-pub struct Exception {
+impl ExceptionKind {
+    /// Whether a `do { } catch { }` block in the script is allowed to swallow the exception,
+    /// budget violations always have to reach the host
+    pub fn is_catchable(&self) -> bool {
+        matches!(self, ExceptionKind::Error | ExceptionKind::OutOfMemory)
+    }
+}
+
+/// Boxed so `Result<_, Exception>` stays small, every `?` in the interpreter's recursive
+/// functions takes up stack space for one
+#[derive(Clone, Debug)]
+pub struct Exception(Box<ExceptionData>);
+
+#[derive(Clone, Debug)]
+struct ExceptionData {
     string: String,
+    kind: ExceptionKind,
+    diagnostic: Diagnostic,
+    /// Every diagnostic when several were reported at once, see [`Exception::diagnostics`]
+    diagnostics: Vec<Exception>,
 }
 
 impl Exception {
     pub fn new(string: String) -> Self {
-        Self { string }
+        Self(Box::new(ExceptionData {
+            kind: ExceptionKind::Error,
+            diagnostic: Diagnostic::new(Severity::Error, string.clone()),
+            string,
+            diagnostics: vec![],
+        }))
+    }
+
+    /// Reports every diagnostic of a file as one exception, sorted by position
+    pub(crate) fn from_diagnostics(mut diagnostics: Vec<Exception>) -> Self {
+        diagnostics.sort_by_key(|v| v.0.diagnostic.span.as_ref().map(|v| v.start));
+        if diagnostics.len() == 1 {
+            return diagnostics.pop().unwrap();
+        }
+        let mut string: String = diagnostics.iter().map(Exception::message).collect();
+        string.push_str(
+            format!(
+                "\n{}: {}\n",
+                EXCEPTION.message.bold().color(EXCEPTION.message_colour),
+                format!("found {} errors", diagnostics.len()).bold()
+            )
+            .as_str(),
+        );
+        let mut exception = Exception::new(string);
+        exception.0.diagnostic = Diagnostic::new(
+            Severity::Error,
+            format!("found {} errors", diagnostics.len()),
+        );
+        exception.0.diagnostics = diagnostics;
+        exception
+    }
+
+    /// The separate diagnostics this exception reports, just itself unless several
+    /// errors were collected while parsing a file
+    pub fn diagnostics(&self) -> &[Exception] {
+        if self.0.diagnostics.is_empty() {
+            std::slice::from_ref(self)
+        } else {
+            &self.0.diagnostics
+        }
+    }
+
+    pub(crate) fn with_kind(mut self, kind: ExceptionKind) -> Self {
+        self.0.kind = kind;
+        self
+    }
+
+    pub(crate) fn with_code(mut self, code: &'static str) -> Self {
+        self.0.diagnostic.code = Some(code);
+        self
+    }
+
+    /// Points the diagnostic to another place it's about
+    pub(crate) fn with_related(
+        mut self,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+        message: &str,
+    ) -> Self {
+        self.0.diagnostic.related.push(RelatedSpan {
+            span: Span::new(start, end, file_data),
+            message: message.to_string(),
+        });
+        self
+    }
+
+    /// What the exception is made of, without colours or formatting
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self.0.diagnostic
+    }
+
+    pub(crate) fn diagnostic_mut(&mut self) -> &mut Diagnostic {
+        &mut self.0.diagnostic
+    }
+
+    pub fn kind(&self) -> &ExceptionKind {
+        &self.0.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.0.string
     }
 
     pub fn run(&self) -> ! {
-        println!("{}", self.string);
+        if let ExceptionKind::Exit(code) = self.0.kind {
+            std::process::exit(code)
+        }
+        println!("{}", self.0.string);
         crash!()
     }
 }
 
+/// The candidate `identifier` is most likely a typo of, if any is close enough
+pub(crate) fn closest_match(identifier: &str, candidates: &[String]) -> Option<String> {
+    let max_distance = (identifier.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .map(|v| (edit_distance(identifier, v), v))
+        .filter(|(distance, _)| (1..=max_distance).contains(distance))
+        .min()
+        .map(|(_, v)| v.clone())
+}
+
+/// Appends a "did you mean" line to `note` when there's a suggestion
+pub(crate) fn with_suggestion(note: String, suggestion: Option<String>) -> String {
+    match suggestion {
+        Some(v) => format!("{}\ndid you mean » {} «?", note, v),
+        None => note,
+    }
+}
+
 pub(crate) struct PositionException;
 
 impl PositionException {
@@ -72,20 +223,9 @@ impl PositionException {
         note: &str,
         colour_scheme: &ErrorColourScheme,
     ) -> Exception {
-        let lines: Vec<&str> = file_data.data.lines().collect();
-
-        let start_line = file_data.data.line_at(start.value).unwrap_or_else(|| {
-            panic!(
-                "filename: {}, exception: {}, start: {}, end: {}, note: {}",
-                file_data.path, exception_name, start.value, end.value, note
-            )
-        });
-        let end_line = file_data.data.line_at(end.value).unwrap_or_else(|| {
-            panic!(
-                "filename: {}, exception: {}, start: {}, end: {}, note: {}",
-                file_data.path, exception_name, start.value, end.value, note
-            )
-        });
+        let source_map = &file_data.source_map;
+        let start_line = source_map.line_at(start.value);
+        let end_line = source_map.line_at(end.value);
 
         let biggest_line_number_size = end_line.to_string().len();
         let smallest_line_number_size = start_line.to_string().len();
@@ -113,21 +253,19 @@ impl PositionException {
                 " ".repeat(smallest_line_number_size),
                 " -->".color(colour_scheme.arrow_to_message),
                 file_data.path,
-                file_data.data.line_at(teleport_position.value).unwrap() + 1,
-                teleport_position.value
-                    - file_data
-                        .data
-                        .start_of_line(file_data.data.line_at(teleport_position.value).unwrap())
-                        .unwrap()
-                    + 1,
+                source_map.location(teleport_position.value).0 + 1,
+                source_map.location(teleport_position.value).1 + 1,
             )
             .as_str(),
         );
 
         message.push_str(format!("{}\n", empty_line_number_display,).as_str());
         if file_data.path != "std" {
-            for (line_number, _) in lines.iter().enumerate().take(end_line + 1).skip(start_line) {
-                let current_line: String = lines[line_number].to_string();
+            for line_number in start_line..=end_line {
+                // Sources end in a null character for the lexer, it shouldn't be printed
+                let current_line = source_map
+                    .line(&file_data.data, line_number)
+                    .trim_end_matches('\0');
                 let line_number_display = format!(
                     "{}{} {}",
                     " ".repeat(
@@ -152,17 +290,13 @@ impl PositionException {
                         if line_number == start_line {
                             format!(
                                 "{}{}",
-                                " ".repeat(
-                                    (start.value as i32
-                                        - file_data.data.start_of_line(line_number).unwrap() as i32)
-                                        .max(0) as usize
-                                ),
+                                " ".repeat(start.value - source_map.start_of_line_or(line_number)),
                                 "^".repeat(
-                                    file_data
-                                        .data
+                                    source_map
                                         .start_of_line_or(line_number + 1)
                                         .min(end.value + 1)
-                                        - start.value
+                                        .saturating_sub(start.value)
+                                        .max(1)
                                 )
                                 .color(colour_scheme.arrow_to_error)
                             )
@@ -170,13 +304,12 @@ impl PositionException {
                             format!(
                                 "{}",
                                 "^".repeat(
-                                    end.value - file_data.data.start_of_line(line_number).unwrap()
-                                        + 1
+                                    end.value - source_map.start_of_line_or(line_number) + 1
                                 )
                                 .color(colour_scheme.arrow_to_error)
                             )
                         } else {
-                            "^".repeat(current_line.len())
+                            "^".repeat(current_line.chars().count())
                                 .color(colour_scheme.arrow_to_error)
                                 .to_string()
                         }
@@ -185,10 +318,18 @@ impl PositionException {
                 )
             }
         }
+        let diagnostic = Diagnostic {
+            severity: colour_scheme.severity,
+            code: None,
+            message: exception_name.to_string(),
+            note: note.to_string(),
+            span: Some(Span::new(start, end, file_data)),
+            related: vec![],
+        };
         let mut note = note.to_string();
         if note.contains('\n') {
             let mut first = false;
-            for line in note.clone().split('\n') {
+            for line in std::mem::take(&mut note).split('\n') {
                 if !first {
                     first = true;
                     note.push_str(line.trim());
@@ -219,6 +360,8 @@ impl PositionException {
                 .as_str(),
             );
         }
-        Exception::new(message)
+        let mut exception = Exception::new(message);
+        exception.0.diagnostic = diagnostic;
+        exception
     }
 }
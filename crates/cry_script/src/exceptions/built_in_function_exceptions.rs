@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use crate::{interpreter::function::TypeHint, parser::data::DataType, FileData, Position};
+use crate::{interpreter::function::TypeHint, parser::data::Value, FileData, Position};
 
 use super::{Exception, PositionException, EXCEPTION};
 
@@ -11,7 +11,7 @@ impl CantRunInContext {
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
-        data_type: &DataType,
+        data_type: &Value,
     ) -> Exception {
         PositionException::call(
             start,
@@ -0,0 +1,142 @@
+//! The parts an [`Exception`](super::Exception) is made of, for hosts that need errors as data
+//! instead of coloured text.
+
+use std::fmt::{Display, Write};
+
+use crate::{FileData, Position};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A line and column in a source file, both start at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    fn new(index: usize, file_data: &FileData) -> Self {
+        let (line, column) = file_data.source_map.location(index);
+        Self {
+            line: line + 1,
+            column: column + 1,
+        }
+    }
+}
+
+/// A range of a source file, `end` is the last character it covers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub file: String,
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    pub(crate) fn new(start: &Position, end: &Position, file_data: &FileData) -> Self {
+        Self {
+            file: file_data.path.clone(),
+            start: Location::new(start.value, file_data),
+            end: Location::new(end.value, file_data),
+        }
+    }
+}
+
+/// Another place a diagnostic refers to, like the declaration a variable shadows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedSpan {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stays the same between versions, it's the `CODE` of the exception struct
+    /// or the code of the lint that reported it
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub note: String,
+    /// `None` for diagnostics that aren't about a place in a file, like a file that can't be opened
+    pub span: Option<Span>,
+    pub related: Vec<RelatedSpan>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(severity: Severity, message: String) -> Self {
+        Self {
+            severity,
+            code: None,
+            message,
+            note: String::new(),
+            span: None,
+            related: vec![],
+        }
+    }
+
+    /// The diagnostic as a single line JSON object
+    pub fn to_json(&self) -> String {
+        let mut json = format!(
+            "{{\"severity\":{},\"code\":{},\"message\":{},\"note\":{},\"span\":{},\"related\":[",
+            json_string(&self.severity.to_string()),
+            self.code.map_or("null".to_string(), json_string),
+            json_string(&self.message),
+            json_string(&self.note),
+            self.span.as_ref().map_or("null".to_string(), span_json),
+        );
+        for (i, related) in self.related.iter().enumerate() {
+            if i > 0 {
+                json.push(',')
+            }
+            json.push_str(&format!(
+                "{{\"message\":{},\"span\":{}}}",
+                json_string(&related.message),
+                span_json(&related.span)
+            ))
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+fn span_json(span: &Span) -> String {
+    format!(
+        "{{\"file\":{},\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}}",
+        json_string(&span.file),
+        span.start.line,
+        span.start.column,
+        span.end.line,
+        span.end.column
+    )
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
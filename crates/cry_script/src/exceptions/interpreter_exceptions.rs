@@ -1,17 +1,21 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Duration};
 
-use crate::{interpreter::type_hint::TypeHint, parser::data::DataType, FileData, Position};
+use colored::Colorize;
 
-use super::{Exception, PositionException, EXCEPTION};
+use crate::{interpreter::type_hint::TypeHint, parser::data::Value, FileData, Position};
+
+use super::{with_suggestion, Exception, ExceptionKind, PositionException, EXCEPTION};
 
 pub struct InvalidBinaryOperation;
 
 impl InvalidBinaryOperation {
+    pub const CODE: &str = "E0201";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
-        (num1, num2): (&DataType, &DataType),
+        (num1, num2): (&Value, &Value),
         operation: &str,
     ) -> Exception {
         PositionException::call(
@@ -29,17 +33,20 @@ impl InvalidBinaryOperation {
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct TypeConversion;
 
 impl TypeConversion {
+    pub const CODE: &str = "E0202";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
-        current_type: &DataType,
+        current_type: &Value,
         convert_type: &str,
     ) -> Exception {
         PositionException::call(
@@ -56,12 +63,15 @@ impl TypeConversion {
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct IntegerDivisionByZero;
 
 impl IntegerDivisionByZero {
+    pub const CODE: &str = "E0203";
+
     pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
@@ -72,12 +82,15 @@ impl IntegerDivisionByZero {
             "dividing an integer with 0 will result in an unknown value",
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct InvalidFilePath;
 
 impl InvalidFilePath {
+    pub const CODE: &str = "E0204";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
@@ -93,17 +106,21 @@ impl InvalidFilePath {
             format!("unable to locate a file at \"{}\"", file_path,).as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct AccessUndeclaredVariable;
 
 impl AccessUndeclaredVariable {
+    pub const CODE: &str = "E0205";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
         identifier: &String,
+        suggestion: Option<String>,
     ) -> Exception {
         PositionException::call(
             start,
@@ -111,24 +128,31 @@ impl AccessUndeclaredVariable {
             start,
             file_data,
             "access undeclared variable",
-            format!(
-                "can't access {} since it does not exist in the current scope",
-                identifier
+            with_suggestion(
+                format!(
+                    "can't access {} since it does not exist in the current scope",
+                    identifier
+                ),
+                suggestion,
             )
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct AccessUndeclaredClass;
 
 impl AccessUndeclaredClass {
+    pub const CODE: &str = "E0206";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
         identifier: &String,
+        suggestion: Option<String>,
     ) -> Exception {
         PositionException::call(
             start,
@@ -136,24 +160,31 @@ impl AccessUndeclaredClass {
             start,
             file_data,
             "access undeclared class",
-            format!(
-                "can't access {} since it does not exist in the current scope",
-                identifier
+            with_suggestion(
+                format!(
+                    "can't access {} since it does not exist in the current scope",
+                    identifier
+                ),
+                suggestion,
             )
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct AccessUndeclaredFunction;
 
 impl AccessUndeclaredFunction {
+    pub const CODE: &str = "E0207";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
         identifier: &String,
+        suggestion: Option<String>,
     ) -> Exception {
         PositionException::call(
             start,
@@ -161,24 +192,31 @@ impl AccessUndeclaredFunction {
             start,
             file_data,
             "access undeclared function",
-            format!(
-                "can't call {} since it does not exist in the current scope",
-                identifier
+            with_suggestion(
+                format!(
+                    "can't call {} since it does not exist in the current scope",
+                    identifier
+                ),
+                suggestion,
             )
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct UpdateUndeclaredVariable;
 
 impl UpdateUndeclaredVariable {
+    pub const CODE: &str = "E0208";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
         identifier: &String,
+        suggestion: Option<String>,
     ) -> Exception {
         PositionException::call(
             start,
@@ -186,19 +224,25 @@ impl UpdateUndeclaredVariable {
             start,
             file_data,
             "update undeclared variable",
-            format!(
-                "can't update {} since it does not exist in the current scope",
-                identifier
+            with_suggestion(
+                format!(
+                    "can't update {} since it does not exist in the current scope",
+                    identifier
+                ),
+                suggestion,
             )
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct ReturnFromRoot;
 
 impl ReturnFromRoot {
+    pub const CODE: &str = "E0209";
+
     pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
@@ -209,12 +253,15 @@ impl ReturnFromRoot {
             "can't return from a root context, try exit() to quit the application",
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct InvalidAmountOfArguments;
 
 impl InvalidAmountOfArguments {
+    pub const CODE: &str = "E0210";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
@@ -236,19 +283,22 @@ impl InvalidAmountOfArguments {
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct InvalidArgumentType;
 
 impl InvalidArgumentType {
+    pub const CODE: &str = "E0211";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
         argument_name: &String,
         type_hint: &TypeHint,
-        provided_argument_type: &DataType,
+        provided_argument_type: &Value,
     ) -> Exception {
         PositionException::call(
             start,
@@ -265,19 +315,22 @@ impl InvalidArgumentType {
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct InvalidType;
 
 impl InvalidType {
+    pub const CODE: &str = "E0212";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
         identifier: &String,
         type_hint: &TypeHint,
-        provided_argument_type: &DataType,
+        provided_argument_type: &Value,
     ) -> Exception {
         PositionException::call(
             start,
@@ -294,12 +347,15 @@ impl InvalidType {
             .as_str(),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct FailedToReadInput;
 
 impl FailedToReadInput {
+    pub const CODE: &str = "E0213";
+
     pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
@@ -310,12 +366,15 @@ impl FailedToReadInput {
             "this is likely a os related issue",
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct VariableIsNotAFunction;
 
 impl VariableIsNotAFunction {
+    pub const CODE: &str = "E0214";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
@@ -331,12 +390,15 @@ impl VariableIsNotAFunction {
             &format!("variable {} exists but it is not a function", identifier),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct VariableIsFinal;
 
 impl VariableIsFinal {
+    pub const CODE: &str = "E0215";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
@@ -355,17 +417,20 @@ impl VariableIsFinal {
             ),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
     }
 }
 
 pub struct CantRunInContext;
 
 impl CantRunInContext {
+    pub const CODE: &str = "E0216";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
-        data_type: &DataType,
+        data_type: &Value,
     ) -> Exception {
         PositionException::call(
             start,
@@ -379,5 +444,249 @@ impl CantRunInContext {
             ),
             &EXCEPTION,
         )
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct StepLimitExceeded;
+
+impl StepLimitExceeded {
+    pub const CODE: &str = "E0217";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        max_steps: u64,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "step limit exceeded",
+            &format!(
+                "the script executed more than {} instructions and was stopped",
+                max_steps
+            ),
+            &EXCEPTION,
+        )
+        .with_kind(ExceptionKind::StepLimitExceeded)
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct TimeLimitExceeded;
+
+impl TimeLimitExceeded {
+    pub const CODE: &str = "E0218";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        time_limit: &Duration,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "time limit exceeded",
+            &format!(
+                "the script ran for longer than {} milliseconds and was stopped",
+                time_limit.as_millis()
+            ),
+            &EXCEPTION,
+        )
+        .with_kind(ExceptionKind::TimeLimitExceeded)
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct OutOfMemory;
+
+impl OutOfMemory {
+    pub const CODE: &str = "E0219";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        max_memory: usize,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "out of memory",
+            &format!(
+                "the script's variables would take up more than {} bytes",
+                max_memory
+            ),
+            &EXCEPTION,
+        )
+        .with_kind(ExceptionKind::OutOfMemory)
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct PermissionDenied;
+
+impl PermissionDenied {
+    pub const CODE: &str = "E0220";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        reason: &str,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "permission denied",
+            reason,
+            &EXCEPTION,
+        )
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct ScriptExit;
+
+impl ScriptExit {
+    pub const CODE: &str = "E0221";
+
+    pub(crate) fn call(code: i32) -> Exception {
+        Exception::new(String::new())
+            .with_kind(ExceptionKind::Exit(code))
+            .with_code(Self::CODE)
+    }
+}
+
+pub struct UnableToOpenFile;
+
+impl UnableToOpenFile {
+    pub const CODE: &str = "E0222";
+
+    pub(crate) fn call(file_path: &str) -> Exception {
+        let mut exception = Exception::new(
+            format!("Err: Unable to open {}", file_path)
+                .red()
+                .bold()
+                .to_string(),
+        )
+        .with_code(Self::CODE);
+        exception.diagnostic_mut().message = "unable to open file".to_string();
+        exception.diagnostic_mut().note = format!("can't read {}", file_path);
+        exception
+    }
+}
+
+pub struct InvalidNumber;
+
+impl InvalidNumber {
+    pub const CODE: &str = "E0223";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        text: &str,
+        expected: &str,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "invalid number",
+            &format!("» {} « isn't a valid {}", text, expected),
+            &EXCEPTION,
+        )
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct InvalidNumericArgument;
+
+impl InvalidNumericArgument {
+    pub const CODE: &str = "E0224";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        expected: &str,
+        provided: &Value,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "invalid numeric argument",
+            &format!(
+                "expected {} but the provided value was of type {}",
+                expected,
+                provided.data_type()
+            ),
+            &EXCEPTION,
+        )
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct FileSystemError;
+
+impl FileSystemError {
+    pub const CODE: &str = "E0225";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        action: &str,
+        file_path: &str,
+        error: &std::io::Error,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "file system error",
+            &format!("unable to {} \"{}\": {}", action, file_path, error),
+            &EXCEPTION,
+        )
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct IntegerOverflow;
+
+impl IntegerOverflow {
+    pub const CODE: &str = "E0226";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        expression: &str,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "integer overflow",
+            &format!("the result of {} doesn't fit in an integer", expression),
+            &EXCEPTION,
+        )
+        .with_code(Self::CODE)
     }
 }
@@ -2,12 +2,14 @@ use std::rc::Rc;
 
 use crate::{FileData, Position};
 
-use super::{PositionException, EXCEPTION};
+use super::{Exception, PositionException, EXCEPTION};
 
 pub struct UnterminatedIndentation;
 
 impl UnterminatedIndentation {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0001";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -17,14 +19,16 @@ impl UnterminatedIndentation {
             "consider adding a curly bracket",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct UnmatchedDedentToken;
 
 impl UnmatchedDedentToken {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0002";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -34,14 +38,16 @@ impl UnmatchedDedentToken {
             "this curly bracket has no matching bracket",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct UnterminatedString;
 
 impl UnterminatedString {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0003";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -51,14 +57,16 @@ impl UnterminatedString {
             "consider adding a quotation mark",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct InvalidAmountOfDots;
 
 impl InvalidAmountOfDots {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0004";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -68,14 +76,16 @@ impl InvalidAmountOfDots {
             "expected either 0 or 1 dot(s) while parsing the number",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct UnknownToken;
 
 impl UnknownToken {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0005";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -85,14 +95,16 @@ impl UnknownToken {
             "check the docs for valid token(s)",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct InvalidAnnotation;
 
 impl InvalidAnnotation {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0006";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -102,6 +114,49 @@ impl InvalidAnnotation {
             "check the docs for valid annotations",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct InvalidUnicodeEscape;
+
+impl InvalidUnicodeEscape {
+    pub const CODE: &str = "E0007";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "invalid unicode escape",
+            "write it as \\u{...} with 1 to 6 hex digits of a valid code point",
+            &EXCEPTION,
+        )
+        .with_code(Self::CODE)
+    }
+}
+
+pub struct InvalidInterpolation;
+
+impl InvalidInterpolation {
+    pub const CODE: &str = "E0008";
+
+    pub(crate) fn call(
+        start: &Position,
+        end: &Position,
+        file_data: &Rc<FileData>,
+        note: &str,
+    ) -> Exception {
+        PositionException::call(
+            start,
+            end,
+            start,
+            file_data,
+            "invalid interpolation",
+            note,
+            &EXCEPTION,
+        )
+        .with_code(Self::CODE)
     }
 }
@@ -0,0 +1,30 @@
+use crate::lints::{LintLevel, Warning};
+
+use super::{Exception, PositionException, EXCEPTION, WARNING};
+
+pub struct LintWarning;
+
+impl LintWarning {
+    /// Reports `warning` as an error when its lint is denied
+    pub(crate) fn call(warning: &Warning, level: LintLevel) -> Exception {
+        let exception = PositionException::call(
+            &warning.start,
+            &warning.end,
+            &warning.start,
+            &warning.file_data,
+            &warning.message,
+            format!("{}\n{} is set to {}", warning.note, warning.lint, level).as_str(),
+            match level {
+                LintLevel::Deny => &EXCEPTION,
+                _ => &WARNING,
+            },
+        )
+        .with_code(warning.lint.code());
+        match &warning.related {
+            Some((start, end, message)) => {
+                exception.with_related((start, end, &warning.file_data), message)
+            }
+            None => exception,
+        }
+    }
+}
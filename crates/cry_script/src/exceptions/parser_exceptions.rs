@@ -2,16 +2,18 @@ use std::rc::Rc;
 
 use crate::{lexer::token::TokenType, FileData, Position};
 
-use super::{PositionException, EXCEPTION};
+use super::{Exception, PositionException, EXCEPTION};
 
 pub struct UnexpectedToken;
 
 impl UnexpectedToken {
+    pub const CODE: &str = "E0101";
+
     pub(crate) fn call(
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
         expected: &str,
         found: &str,
-    ) -> ! {
+    ) -> Exception {
         PositionException::call(
             start,
             end,
@@ -21,14 +23,16 @@ impl UnexpectedToken {
             format!("expected » {} « found » {} «", expected, found).as_str(),
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct UnterminatedString;
 
 impl UnterminatedString {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0102";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -38,14 +42,16 @@ impl UnterminatedString {
             "consider adding a quotation mark",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct UnterminatedParenthesis;
 
 impl UnterminatedParenthesis {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0103";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -55,19 +61,21 @@ impl UnterminatedParenthesis {
             "consider adding a matching parenthesis",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct NotATypeHint;
 
 impl NotATypeHint {
+    pub const CODE: &str = "E0104";
+
     pub(crate) fn call(
         start: &Position,
         end: &Position,
         file_data: &Rc<FileData>,
         current: &TokenType,
-    ) -> ! {
+    ) -> Exception {
         PositionException::call(
             start,
             end,
@@ -77,14 +85,16 @@ impl NotATypeHint {
             format!("token \"{}\" is not a valid type hint", current).as_str(),
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
 
 pub struct InvalidInstructionInClass;
 
 impl InvalidInstructionInClass {
-    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> ! {
+    pub const CODE: &str = "E0105";
+
+    pub(crate) fn call(start: &Position, end: &Position, file_data: &Rc<FileData>) -> Exception {
         PositionException::call(
             start,
             end,
@@ -94,6 +104,6 @@ impl InvalidInstructionInClass {
             "class declarations don't accept this instruction, consider putting it inside a function",
             &EXCEPTION,
         )
-        .run()
+        .with_code(Self::CODE)
     }
 }
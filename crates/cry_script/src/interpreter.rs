@@ -2,14 +2,17 @@ use std::{cell::RefCell, collections::HashMap, fmt::Display, rc::Rc};
 
 use crate::{
     exceptions::{parser_exceptions::InvalidInstructionInClass, Exception},
-    parser::data::{Data, DataType},
-    variables::Variable,
+    parser::data::Value,
+    variables::{
+        gc::{Trace, Tracer},
+        Variable,
+    },
     AsString, FileData, Position,
 };
 
 use self::{
     context::Context,
-    function::Function,
+    function::{Function, FunctionBody},
     instructions::{Instruction, InstructionType},
     type_hint::{Type, TypeHint},
 };
@@ -18,6 +21,7 @@ mod built_in_functions;
 pub mod context;
 pub mod function;
 pub mod instructions;
+pub(crate) mod runtime;
 pub mod type_hint;
 
 #[derive(Debug, Clone)]
@@ -46,7 +50,7 @@ impl<T: AsString> Display for Table<T> {
     }
 }
 
-pub type DataRef = Rc<RefCell<Data>>;
+pub type DataRef = Rc<RefCell<Value>>;
 
 #[derive(Debug, Clone)]
 pub(crate) struct Class {
@@ -62,9 +66,9 @@ impl Class {
         identifier: String,
         (start, end, file_data): (Position, Position, Rc<FileData>),
         body: Vec<Instruction>,
-        parent: *mut Context,
+        parent: &Context,
     ) -> Result<Self, Exception> {
-        let mut context = Context::new(parent, unsafe { &*parent }.file_data.clone());
+        let context = Context::new(parent, parent.file_data());
         let mut variables = vec![];
         for i in body.into_iter() {
             match i.instruction_type {
@@ -73,14 +77,11 @@ impl Class {
                     data,
                     type_hint,
                     is_final,
+                    ..
                 } => variables.push((
                     identifier.clone(),
                     Variable::new(
-                        match data.visit(&mut context)? {
-                            crate::Returnable::Return(v) => v,
-                            crate::Returnable::Evaluate(v) => v,
-                            crate::Returnable::Break(v) => v,
-                        },
+                        data.visit(&context)?.unwrap(),
                         type_hint,
                         is_final,
                         identifier.clone(),
@@ -94,19 +95,14 @@ impl Class {
                     variables.push((
                         identifier.clone(),
                         Variable::new(
-                            Data::new(
-                                body.file_data.clone(),
+                            Value::Function(Rc::new(Function::new(
+                                &arguments,
+                                FunctionBody::new(*body.clone(), &context),
                                 body.start.clone(),
                                 body.end.clone(),
-                                DataType::Function(Box::new(Function::new(
-                                    &arguments,
-                                    *body.clone(),
-                                    body.start.clone(),
-                                    body.end.clone(),
-                                    identifier.clone(),
-                                    &mut context,
-                                ))),
-                            ),
+                                identifier.clone(),
+                                &context,
+                            )?)),
                             Type::new(
                                 TypeHint::None,
                                 body.start.clone(),
@@ -118,7 +114,13 @@ impl Class {
                         ),
                     ));
                 }
-                _ => InvalidInstructionInClass::call(&i.start, &i.end, &i.file_data),
+                _ => {
+                    return Err(InvalidInstructionInClass::call(
+                        &i.start,
+                        &i.end,
+                        &i.file_data,
+                    ))
+                }
             }
         }
         Ok(Self {
@@ -135,9 +137,14 @@ impl Class {
 pub struct ClassVariable {
     pub(crate) class_name: String,
     pub(crate) context: Context,
-    pub(crate) start: Position,
-    pub(crate) end: Position,
-    pub(crate) file_data: Rc<FileData>,
+}
+
+impl Trace for Class {
+    fn trace(&self, tracer: &mut Tracer) {
+        for (_, variable) in self.variables.iter() {
+            variable.data.trace(tracer)
+        }
+    }
 }
 
 impl Display for Class {
@@ -156,34 +163,21 @@ impl Display for Class {
 impl ClassVariable {
     fn new(
         class: &Class,
-        parent: *mut Context,
-        args: Vec<Data>,
-        (start, end, file_data): (Position, Position, Rc<FileData>),
+        parent: &Context,
+        args: Vec<Value>,
+        position: (&Position, &Position, &Rc<FileData>),
     ) -> Result<Self, Exception> {
-        let mut context = Context::new(parent, unsafe { &*parent }.file_data.clone());
+        let context = Context::new(parent, parent.file_data());
         for variable in class.variables.iter() {
-            context.declare_variable(variable.0.clone(), variable.1.clone())?;
+            context.declare_variable(variable.0.clone(), variable.1.clone(), position)?;
         }
-        if context
-            .variables_defined_in_this_scope
-            .contains_key("constructor")
-        {
-            Context::call_fn_no_std(
-                &mut context,
-                &"constructor".to_string(),
-                args,
-                (&start, &end, &file_data),
-            )?;
-            context
-                .variables_defined_in_this_scope
-                .remove(&"constructor".to_string());
+        if context.defines("constructor") {
+            Context::call_fn_no_std(&context, &"constructor".to_string(), args, position)?;
+            context.forget_variable("constructor");
         }
         Ok(Self {
             class_name: class.identifier.clone(),
             context,
-            start,
-            end,
-            file_data,
         })
     }
 }
@@ -195,11 +189,15 @@ impl Display for ClassVariable {
             "{} variables: {} classes: {}",
             self.class_name,
             self.context
-                .variables_defined_in_this_scope
+                .variable_names()
                 .iter()
-                .map(|x| format!("{} ", x.0))
+                .map(|x| format!("{} ", x))
                 .collect::<String>(),
-            self.context.classes
+            self.context
+                .class_names()
+                .iter()
+                .map(|x| format!("{} ", x))
+                .collect::<String>()
         )
     }
 }
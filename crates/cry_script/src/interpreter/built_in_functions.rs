@@ -1,42 +1,158 @@
 use std::{
-    fs::{File, OpenOptions},
-    io::{stdin, stdout, Read, Write},
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, stdin, stdout, Write},
+    path::Path,
     rc::Rc,
+    time::UNIX_EPOCH,
 };
 
-use rand::Rng;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
 use crate::{
+    engine::FileSystemAccess,
     exceptions::{
-        interpreter_exceptions::{FailedToReadInput, InvalidFilePath},
+        interpreter_exceptions::{
+            FailedToReadInput, FileSystemError, IntegerOverflow, InvalidAmountOfArguments,
+            InvalidFilePath, InvalidNumber, InvalidNumericArgument, PermissionDenied, ScriptExit,
+        },
         Exception,
     },
-    parser::data::{Data, DataType},
+    parser::data::Value,
     FileData, Position,
 };
 
-use super::context::Context;
+use super::{context::Context, runtime::Runtime};
 
 pub struct BuiltInFunctions;
 impl BuiltInFunctions {
+    /// Functions handled by [`BuiltInFunctions::run`] instead of a script function and how
+    /// many arguments they take
+    pub(crate) const FUNCTIONS: [(&'static str, usize); 77] = [
+        ("std_clone", 1),
+        ("std_out", 1),
+        ("std_in", 0),
+        ("std_rand_new", 0),
+        ("std_rand_seed", 2),
+        ("std_rand_int", 1),
+        ("std_rand_float", 1),
+        ("std_rand_normal", 3),
+        ("std_rand_exponential", 2),
+        ("std_rand_permutation", 2),
+        ("std_contents_of_file", 1),
+        ("std_write_to_file", 2),
+        ("std_create_file", 1),
+        ("std_clear_file", 1),
+        ("std_append_to_file", 2),
+        ("std_file_exists", 1),
+        ("std_remove", 1),
+        ("std_rename", 2),
+        ("std_copy", 2),
+        ("std_list_dir", 1),
+        ("std_create_dir_all", 1),
+        ("std_file_size", 1),
+        ("std_modified", 1),
+        ("std_is_dir", 1),
+        ("std_line_count", 1),
+        ("std_line", 2),
+        ("std_path_join", 2),
+        ("std_path_extension", 1),
+        ("std_path_parent", 1),
+        ("std_path_file_name", 1),
+        ("std_sqrt", 1),
+        ("std_exit", 1),
+        ("std_arg_count", 0),
+        ("std_arg", 1),
+        ("std_env", 1),
+        ("std_set_env", 2),
+        ("std_cwd", 0),
+        ("std_length", 1),
+        ("std_char_at", 2),
+        ("std_trim", 1),
+        ("std_replace", 3),
+        ("std_starts_with", 2),
+        ("std_ends_with", 2),
+        ("std_find", 2),
+        ("std_to_upper", 1),
+        ("std_to_lower", 1),
+        ("std_substring", 3),
+        ("std_split_count", 2),
+        ("std_split_part", 3),
+        ("std_split_new", 2),
+        ("std_split_length", 1),
+        ("std_split_get", 2),
+        ("std_pad_left", 3),
+        ("std_pad_right", 3),
+        ("std_parse_int", 1),
+        ("std_parse_float", 1),
+        ("std_sin", 1),
+        ("std_cos", 1),
+        ("std_tan", 1),
+        ("std_asin", 1),
+        ("std_acos", 1),
+        ("std_atan", 1),
+        ("std_atan2", 2),
+        ("std_ln", 1),
+        ("std_log10", 1),
+        ("std_log2", 1),
+        ("std_exp", 1),
+        ("std_floor", 1),
+        ("std_ceil", 1),
+        ("std_round", 1),
+        ("std_pow", 2),
+        ("std_hypot", 2),
+        ("std_gcd", 2),
+        ("std_lcm", 2),
+        ("std_is_nan", 1),
+        ("std_infinity", 0),
+        ("std_nan", 0),
+    ];
+
+    /// Whether `identifier` is handled by [`BuiltInFunctions::run`] instead of a script function
+    pub(crate) fn defines(identifier: &str) -> bool {
+        BuiltInFunctions::FUNCTIONS
+            .iter()
+            .any(|v| v.0 == identifier)
+    }
+
     pub(crate) fn run(
-        context: *mut Context,
+        context: &Context,
         identifier: &String,
-        args: Vec<Data>,
+        args: Vec<Value>,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
-    ) -> Result<Data, Exception> {
+    ) -> Result<Value, Exception> {
+        // Scripts can call these directly, not only through the std wrappers
+        if let Some((_, count)) = BuiltInFunctions::FUNCTIONS
+            .iter()
+            .find(|v| v.0 == identifier)
+        {
+            if args.len() != *count {
+                return Err(InvalidAmountOfArguments::call(
+                    start,
+                    end,
+                    file_data,
+                    identifier,
+                    *count,
+                    args.len(),
+                ));
+            }
+        }
+        let runtime = &context.runtime;
         match identifier.as_str() {
-            "std_clone" => Ok(Data::new(
-                file_data.clone(),
-                start.clone(),
-                end.clone(),
-                args[0].data_type.original(),
-            )),
+            "std_clone" => Ok(args[0].original().into_owned()),
             "std_out" => {
-                print!("{}", args[0].data_type);
-                Ok(Data::null_zero(file_data.clone()))
+                print!("{}", args[0]);
+                Ok(Value::Null)
             }
             "std_in" => {
+                if !runtime.borrow().capabilities.stdin {
+                    return Err(PermissionDenied::call(
+                        start,
+                        end,
+                        file_data,
+                        "can't read input since the host doesn't allow access to stdin",
+                    ));
+                }
                 let mut s = String::new();
                 stdout().flush().unwrap();
                 match stdin().read_line(&mut s) {
@@ -49,140 +165,522 @@ impl BuiltInFunctions {
                 if let Some('\r') = s.chars().next_back() {
                     s.pop();
                 }
-                Ok(Data::new(
-                    file_data.clone(),
-                    start.clone(),
-                    end.clone(),
-                    DataType::String(s),
-                ))
+                Ok(Value::from(s))
+            }
+            "std_rand_new" => {
+                let generators = &mut runtime.borrow_mut().generators;
+                let generator = StdRng::seed_from_u64(generators[0].gen());
+                generators.push(generator);
+                Ok(Value::Integer(generators.len() as i32 - 1))
             }
-            "std_rand_int" => Ok(Data::new(
-                file_data.clone(),
-                start.clone(),
-                end.clone(),
-                DataType::Integer(rand::thread_rng().gen()),
+            "std_rand_seed" => {
+                let seed = integer(&args[1], (start, end, file_data))?;
+                *generator(&mut runtime.borrow_mut(), &args[0]) =
+                    StdRng::seed_from_u64(seed as i64 as u64);
+                Ok(Value::Null)
+            }
+            "std_rand_int" => Ok(Value::Integer(
+                generator(&mut runtime.borrow_mut(), &args[0]).gen(),
             )),
-            "std_rand_float" => Ok(Data::new(
-                file_data.clone(),
-                start.clone(),
-                end.clone(),
-                DataType::Float(rand::thread_rng().gen()),
+            "std_rand_float" => Ok(Value::Float(
+                generator(&mut runtime.borrow_mut(), &args[0]).gen(),
             )),
+            "std_rand_normal" => {
+                let mean = float(&args[1], (start, end, file_data))?;
+                let standard_deviation = float(&args[2], (start, end, file_data))?;
+                let mut runtime = runtime.borrow_mut();
+                let generator = generator(&mut runtime, &args[0]);
+                // Box-Muller transform, 1 - x keeps the logarithm away from 0
+                let (u1, u2): (f32, f32) = (1.0 - generator.gen::<f32>(), generator.gen());
+                let z = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+                Ok(Value::Float(mean + standard_deviation * z))
+            }
+            "std_rand_exponential" => {
+                let rate = float(&args[1], (start, end, file_data))?;
+                let u = 1.0 - generator(&mut runtime.borrow_mut(), &args[0]).gen::<f32>();
+                Ok(Value::Float(-u.ln() / rate))
+            }
+            // There are no lists, so the order is returned as comma separated indices
+            "std_rand_permutation" => {
+                let length = index(&args[1]).unwrap_or(0);
+                let mut order: Vec<usize> = (0..length).collect();
+                order.shuffle(generator(&mut runtime.borrow_mut(), &args[0]));
+                Ok(Value::from(
+                    order
+                        .iter()
+                        .map(usize::to_string)
+                        .collect::<Vec<_>>()
+                        .join(","),
+                ))
+            }
             "std_contents_of_file" => {
-                let mut s = String::new();
-                match match File::open(args[0].data_type.to_string()) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        return Err(InvalidFilePath::call(
-                            start,
-                            end,
-                            file_data,
-                            &args[0].data_type.to_string(),
-                        ))
-                    }
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                match fs::read_to_string(path) {
+                    Ok(v) => Ok(Value::from(v)),
+                    Err(e) => Err(io_error(e, "read", &args[0], (start, end, file_data))),
                 }
-                .read_to_string(&mut s)
-                {
-                    Ok(_) => {}
-                    Err(_) => {
-                        return Err(InvalidFilePath::call(
-                            start,
-                            end,
-                            file_data,
-                            &args[0].data_type.to_string(),
-                        ))
-                    }
-                };
-                Ok(Data::new(
-                    file_data.clone(),
-                    start.clone(),
-                    end.clone(),
-                    DataType::String(s),
-                ))
             }
             "std_write_to_file" => {
-                let mut file = match OpenOptions::new()
-                    .write(true)
-                    .open(args[0].data_type.to_string())
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                match fs::write(path, args[1].to_string()) {
+                    Ok(_) => Ok(Value::Null),
+                    Err(e) => Err(io_error(e, "write to", &args[0], (start, end, file_data))),
+                }
+            }
+            "std_append_to_file" => {
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                match OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(path)
+                    .and_then(|mut v| v.write_all(args[1].to_string().as_bytes()))
                 {
-                    Ok(v) => v,
-                    Err(_) => {
-                        return Err(InvalidFilePath::call(
-                            start,
-                            end,
-                            file_data,
-                            &args[0].data_type.to_string(),
-                        ))
-                    }
-                };
-                // file.set_len(1).unwrap();
-                file.write_all(args[1].data_type.to_string().as_bytes())
-                    .unwrap();
-                Ok(Data::new(
-                    file_data.clone(),
-                    start.clone(),
-                    end.clone(),
-                    DataType::Null,
-                ))
+                    Ok(_) => Ok(Value::Null),
+                    Err(e) => Err(io_error(e, "append to", &args[0], (start, end, file_data))),
+                }
             }
             "std_create_file" => {
-                match File::create(args[0].data_type.to_string()) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        return Err(InvalidFilePath::call(
-                            start,
-                            end,
-                            file_data,
-                            &args[0].data_type.to_string(),
-                        ))
-                    }
-                };
-                Ok(Data::new(
-                    file_data.clone(),
-                    start.clone(),
-                    end.clone(),
-                    DataType::Null,
-                ))
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                match File::create(path) {
+                    Ok(_) => Ok(Value::Null),
+                    Err(e) => Err(io_error(e, "create", &args[0], (start, end, file_data))),
+                }
             }
             "std_clear_file" => {
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
                 match OpenOptions::new()
                     .write(true)
-                    .open(args[0].data_type.to_string())
+                    .open(path)
+                    .and_then(|v| v.set_len(0))
                 {
+                    Ok(_) => Ok(Value::Null),
+                    Err(e) => Err(io_error(e, "clear", &args[0], (start, end, file_data))),
+                }
+            }
+            "std_file_exists" => Ok(runtime
+                .borrow()
+                .resolve_path(&args[0].to_string(), (start, end, file_data))?
+                .exists()
+                .into()),
+            // Directories are only removed when they are empty
+            "std_remove" => {
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                let result = if path.is_dir() {
+                    fs::remove_dir(path)
+                } else {
+                    fs::remove_file(path)
+                };
+                match result {
+                    Ok(_) => Ok(Value::Null),
+                    Err(e) => Err(io_error(e, "remove", &args[0], (start, end, file_data))),
+                }
+            }
+            "std_rename" | "std_copy" => {
+                let runtime = runtime.borrow();
+                let from = runtime.resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                let to = runtime.resolve_path(&args[1].to_string(), (start, end, file_data))?;
+                let (result, action) = if identifier == "std_rename" {
+                    (fs::rename(from, to), "rename")
+                } else {
+                    (fs::copy(from, to).map(|_| ()), "copy")
+                };
+                match result {
+                    Ok(_) => Ok(Value::Null),
+                    Err(e) => Err(io_error(e, action, &args[0], (start, end, file_data))),
+                }
+            }
+            // There are no lists, so the sorted names are returned separated by \0
+            // which can't be part of a file name
+            "std_list_dir" => {
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                let names = fs::read_dir(path).and_then(|entries| {
+                    entries
+                        .map(|v| v.map(|v| v.file_name().to_string_lossy().into_owned()))
+                        .collect::<Result<Vec<_>, _>>()
+                });
+                match names {
+                    Ok(mut v) => {
+                        v.sort();
+                        Ok(Value::from(v.join("\0")))
+                    }
+                    Err(e) => Err(io_error(e, "list", &args[0], (start, end, file_data))),
+                }
+            }
+            "std_create_dir_all" => {
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                match fs::create_dir_all(path) {
+                    Ok(_) => Ok(Value::Null),
+                    Err(e) => Err(io_error(e, "create", &args[0], (start, end, file_data))),
+                }
+            }
+            "std_file_size" | "std_modified" | "std_is_dir" => {
+                let path = runtime
+                    .borrow()
+                    .resolve_path(&args[0].to_string(), (start, end, file_data))?;
+                let metadata = match fs::metadata(path) {
                     Ok(v) => v,
-                    Err(_) => {
-                        return Err(InvalidFilePath::call(
-                            start,
-                            end,
-                            file_data,
-                            &args[0].data_type.to_string(),
-                        ))
+                    Err(e) => {
+                        return Err(io_error(e, "inspect", &args[0], (start, end, file_data)))
                     }
+                };
+                match identifier.as_str() {
+                    "std_file_size" => Ok(large_integer(metadata.len())),
+                    "std_modified" => match metadata.modified() {
+                        Ok(v) => Ok(large_integer(
+                            v.duration_since(UNIX_EPOCH).map_or(0, |v| v.as_secs()),
+                        )),
+                        Err(e) => Err(io_error(e, "inspect", &args[0], (start, end, file_data))),
+                    },
+                    _ => Ok(metadata.is_dir().into()),
+                }
+            }
+            "std_line_count" => Ok(Value::Integer(args[0].to_string().lines().count() as i32)),
+            "std_line" => {
+                let line = index(&args[1])
+                    .and_then(|v| args[0].to_string().lines().nth(v).map(String::from));
+                Ok(line.map_or(Value::Null, Value::from))
+            }
+            "std_path_join" => Ok(Value::from(
+                Path::new(&args[0].to_string())
+                    .join(args[1].to_string())
+                    .to_string_lossy()
+                    .into_owned(),
+            )),
+            "std_path_extension" | "std_path_parent" | "std_path_file_name" => {
+                let text = args[0].to_string();
+                let path = Path::new(&text);
+                let part = match identifier.as_str() {
+                    "std_path_extension" => path.extension(),
+                    "std_path_parent" => path.parent().map(Path::as_os_str),
+                    _ => path.file_name(),
+                };
+                Ok(part.map_or(Value::Null, |v| {
+                    Value::from(v.to_string_lossy().into_owned())
+                }))
+            }
+            "std_sqrt" | "std_sin" | "std_cos" | "std_tan" | "std_asin" | "std_acos"
+            | "std_atan" | "std_ln" | "std_log10" | "std_log2" | "std_exp" => {
+                let n = float(&args[0], (start, end, file_data))?;
+                Ok(Value::Float(match identifier.as_str() {
+                    "std_sqrt" => n.sqrt(),
+                    "std_sin" => n.sin(),
+                    "std_cos" => n.cos(),
+                    "std_tan" => n.tan(),
+                    "std_asin" => n.asin(),
+                    "std_acos" => n.acos(),
+                    "std_atan" => n.atan(),
+                    "std_ln" => n.ln(),
+                    "std_log10" => n.log10(),
+                    "std_log2" => n.log2(),
+                    _ => n.exp(),
+                }))
+            }
+            "std_atan2" | "std_hypot" => {
+                let (a, b) = (
+                    float(&args[0], (start, end, file_data))?,
+                    float(&args[1], (start, end, file_data))?,
+                );
+                Ok(Value::Float(if identifier == "std_atan2" {
+                    a.atan2(b)
+                } else {
+                    a.hypot(b)
+                }))
+            }
+            // Integers are already whole numbers, so they stay integers
+            "std_floor" | "std_ceil" | "std_round" => match args[0].original().as_ref() {
+                Value::Integer(v) => Ok(Value::Integer(*v)),
+                Value::Float(v) => Ok(Value::Float(match identifier.as_str() {
+                    "std_floor" => v.floor(),
+                    "std_ceil" => v.ceil(),
+                    _ => v.round(),
+                })),
+                v => Err(InvalidNumericArgument::call(
+                    start, end, file_data, "a number", v,
+                )),
+            },
+            "std_pow" => match (args[0].original().as_ref(), args[1].original().as_ref()) {
+                (Value::Integer(base), Value::Integer(exponent)) => Ok(u32::try_from(*exponent)
+                    .ok()
+                    .and_then(|v| base.checked_pow(v))
+                    .map_or(
+                        Value::Float((*base as f32).powf(*exponent as f32)),
+                        Value::Integer,
+                    )),
+                _ => Ok(Value::Float(
+                    float(&args[0], (start, end, file_data))?
+                        .powf(float(&args[1], (start, end, file_data))?),
+                )),
+            },
+            "std_gcd" | "std_lcm" => {
+                let (a, b) = (
+                    integer(&args[0], (start, end, file_data))?,
+                    integer(&args[1], (start, end, file_data))?,
+                );
+                let (x, y) = (a.unsigned_abs() as u64, b.unsigned_abs() as u64);
+                let gcd = gcd(x, y);
+                let result = match identifier.as_str() {
+                    "std_gcd" => gcd,
+                    _ if gcd == 0 => 0,
+                    _ => x / gcd * y,
+                };
+                match i32::try_from(result) {
+                    Ok(v) => Ok(Value::Integer(v)),
+                    Err(_) => Err(IntegerOverflow::call(
+                        start,
+                        end,
+                        file_data,
+                        &format!("{}({}, {})", &identifier["std_".len()..], a, b),
+                    )),
+                }
+            }
+            "std_is_nan" => Ok(float(&args[0], (start, end, file_data))?.is_nan().into()),
+            "std_infinity" => Ok(Value::Float(f32::INFINITY)),
+            "std_nan" => Ok(Value::Float(f32::NAN)),
+            "std_exit" => {
+                let code = match args[0].original().as_ref() {
+                    Value::Float(v) => *v as i32,
+                    v => integer(v, (start, end, file_data))?,
+                };
+                if runtime.borrow().capabilities.process_exit {
+                    std::process::exit(code)
                 }
-                .set_len(0)
-                .unwrap();
-                Ok(Data::new(
-                    file_data.clone(),
-                    start.clone(),
-                    end.clone(),
-                    DataType::Null,
+                Err(ScriptExit::call(code))
+            }
+            "std_arg_count" => Ok(Value::Integer(runtime.borrow().args.len() as i32)),
+            "std_arg" => {
+                let runtime = runtime.borrow();
+                let arg = match args[0].original().as_ref() {
+                    Value::Integer(v) => usize::try_from(*v).ok().and_then(|v| runtime.args.get(v)),
+                    _ => None,
+                };
+                Ok(arg.map_or(Value::Null, |v| Value::String(Rc::new(v.clone()))))
+            }
+            "std_length" => Ok(Value::Integer(args[0].to_string().chars().count() as i32)),
+            "std_char_at" => {
+                let chr = index(&args[1]).and_then(|v| args[0].to_string().chars().nth(v));
+                Ok(chr.map_or(Value::Null, |v| Value::String(Rc::new(v.to_string()))))
+            }
+            "std_trim" => Ok(Value::from(args[0].to_string().trim().to_string())),
+            "std_replace" => Ok(Value::from(
+                args[0]
+                    .to_string()
+                    .replace(&args[1].to_string(), &args[2].to_string()),
+            )),
+            "std_starts_with" => Ok(args[0].to_string().starts_with(&args[1].to_string()).into()),
+            "std_ends_with" => Ok(args[0].to_string().ends_with(&args[1].to_string()).into()),
+            "std_find" => {
+                let text = args[0].to_string();
+                Ok(text.find(&args[1].to_string()).map_or(Value::Null, |v| {
+                    Value::Integer(text[..v].chars().count() as i32)
+                }))
+            }
+            "std_to_upper" => Ok(Value::from(args[0].to_string().to_uppercase())),
+            "std_to_lower" => Ok(Value::from(args[0].to_string().to_lowercase())),
+            "std_substring" => {
+                let text = args[0].to_string();
+                let length = text.chars().count();
+                let start = index(&args[1]).unwrap_or(0).min(length);
+                let end = match args[2].original().as_ref() {
+                    Value::Null => length,
+                    v => index(v).unwrap_or(0).clamp(start, length),
+                };
+                Ok(Value::from(
+                    text.chars()
+                        .skip(start)
+                        .take(end - start)
+                        .collect::<String>(),
                 ))
             }
-            "std_sqrt" => match args[0].data_type.original() {
-                DataType::Float(n1) => Ok(Data::new(
-                    file_data.clone(),
-                    start.clone(),
-                    end.clone(),
-                    DataType::Float(n1.sqrt()),
+            "std_split_count" => Ok(Value::Integer(
+                split(&args[0].to_string(), &args[1].to_string()).len() as i32,
+            )),
+            "std_split_part" => {
+                let text = args[0].to_string();
+                let part = index(&args[2])
+                    .and_then(|v| split(&text, &args[1].to_string()).get(v).cloned());
+                Ok(part.map_or(Value::Null, Value::from))
+            }
+            "std_split_new" => {
+                let parts = split(&args[0].to_string(), &args[1].to_string());
+                let splits = &mut runtime.borrow_mut().splits;
+                splits.push(parts.into_iter().map(Rc::new).collect());
+                Ok(Value::Integer(splits.len() as i32 - 1))
+            }
+            "std_split_length" => Ok(Value::Integer(
+                parts(&runtime.borrow(), &args[0]).map_or(0, |v| v.len()) as i32,
+            )),
+            "std_split_get" => Ok(parts(&runtime.borrow(), &args[0])
+                .zip(index(&args[1]))
+                .and_then(|(parts, i)| parts.get(i).cloned())
+                .map_or(Value::Null, Value::String)),
+            "std_pad_left" | "std_pad_right" => {
+                let text = args[0].to_string();
+                let width = index(&args[1]).unwrap_or(0);
+                let padding: String = args[2]
+                    .to_string()
+                    .chars()
+                    .cycle()
+                    .take(width.saturating_sub(text.chars().count()))
+                    .collect();
+                Ok(Value::from(if identifier == "std_pad_left" {
+                    padding + &text
+                } else {
+                    text + &padding
+                }))
+            }
+            "std_parse_int" => match args[0].to_string().trim().parse() {
+                Ok(v) => Ok(Value::Integer(v)),
+                Err(_) => Err(InvalidNumber::call(
+                    start,
+                    end,
+                    file_data,
+                    &args[0].to_string(),
+                    "integer",
+                )),
+            },
+            "std_parse_float" => match args[0].to_string().trim().parse() {
+                Ok(v) => Ok(Value::Float(v)),
+                Err(_) => Err(InvalidNumber::call(
+                    start,
+                    end,
+                    file_data,
+                    &args[0].to_string(),
+                    "float",
                 )),
-                _ => panic!(),
             },
-            "std_exit" => std::process::exit(match args[0].original().data_type {
-                DataType::Integer(v) => v,
-                DataType::Float(v) => v as i32,
-                _ => panic!(),
-            }),
+            "std_env" => Ok(runtime
+                .borrow()
+                .env
+                .get(&args[0].to_string())
+                .map_or(Value::Null, |v| Value::String(Rc::new(v.clone())))),
+            "std_set_env" => {
+                let env = &mut runtime.borrow_mut().env;
+                match args[1].original().as_ref() {
+                    Value::Null => env.remove(&args[0].to_string()),
+                    v => env.insert(args[0].to_string(), v.to_string()),
+                };
+                Ok(Value::Null)
+            }
+            "std_cwd" => {
+                let cwd = match &runtime.borrow().capabilities.file_system {
+                    FileSystemAccess::Full => env::current_dir().ok(),
+                    // Relative paths are resolved against the root so that's where the script is
+                    FileSystemAccess::Restricted(root) => Some(root.clone()),
+                    FileSystemAccess::Denied => {
+                        return Err(PermissionDenied::call(
+                            start,
+                            end,
+                            file_data,
+                            "can't read the working directory since the host doesn't allow file system access",
+                        ))
+                    }
+                };
+                Ok(cwd.map_or(Value::Null, |v| Value::from(v.display().to_string())))
+            }
             _ => Context::call_fn_no_std(context, identifier, args, (start, end, file_data)),
         }
     }
 }
+
+/// The value as an index into something, `None` for negative numbers and other types
+fn index(value: &Value) -> Option<usize> {
+    match value.original().as_ref() {
+        Value::Integer(v) => usize::try_from(*v).ok(),
+        _ => None,
+    }
+}
+
+/// The parts of `text` between every `separator`, an empty separator splits it into characters
+fn split(text: &str, separator: &str) -> Vec<String> {
+    if separator.is_empty() {
+        text.chars().map(String::from).collect()
+    } else {
+        text.split(separator).map(String::from).collect()
+    }
+}
+
+/// The value as a float, integers are converted
+fn float(
+    value: &Value,
+    (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+) -> Result<f32, Exception> {
+    match value.original().as_ref() {
+        Value::Integer(v) => Ok(*v as f32),
+        Value::Float(v) => Ok(*v),
+        v => Err(InvalidNumericArgument::call(
+            start, end, file_data, "a number", v,
+        )),
+    }
+}
+
+fn integer(
+    value: &Value,
+    (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+) -> Result<i32, Exception> {
+    match value.original().as_ref() {
+        Value::Integer(v) => Ok(*v),
+        v => Err(InvalidNumericArgument::call(
+            start,
+            end,
+            file_data,
+            "an integer",
+            v,
+        )),
+    }
+}
+
+/// An integer if it fits, file sizes and times can be larger than the integers of scripts
+fn large_integer(value: u64) -> Value {
+    match i32::try_from(value) {
+        Ok(v) => Value::Integer(v),
+        Err(_) => Value::Float(value as f32),
+    }
+}
+
+/// The exception for a failed file system operation on the path in `path`
+fn io_error(
+    error: io::Error,
+    action: &str,
+    path: &Value,
+    (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+) -> Exception {
+    match error.kind() {
+        io::ErrorKind::NotFound => InvalidFilePath::call(start, end, file_data, &path.to_string()),
+        _ => FileSystemError::call(start, end, file_data, action, &path.to_string(), &error),
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The parts of a `Split` instance, `None` if the id doesn't belong to any
+fn parts<'a>(runtime: &'a Runtime, id: &Value) -> Option<&'a Vec<Rc<String>>> {
+    runtime.splits.get(index(id)?)
+}
+
+/// The generator of a `Random` instance, the first one if the id doesn't belong to any
+fn generator<'a>(runtime: &'a mut Runtime, id: &Value) -> &'a mut StdRng {
+    let id = index(id).filter(|v| *v < runtime.generators.len());
+    &mut runtime.generators[id.unwrap_or(0)]
+}
@@ -1,168 +1,362 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc};
 
 use slotmap::DefaultKey;
 
 use crate::{
     exceptions::{
+        closest_match,
         interpreter_exceptions::{
             AccessUndeclaredClass, AccessUndeclaredFunction, AccessUndeclaredVariable,
-            UpdateUndeclaredVariable, VariableIsFinal, VariableIsNotAFunction,
+            CantRunInContext, InvalidFilePath, UpdateUndeclaredVariable, VariableIsFinal,
+            VariableIsNotAFunction,
         },
         Exception,
     },
-    parser::data::{Data, DataType},
+    parser::data::Value,
+    resolver::{RootScope, Slot},
     run_from_file, run_with_data,
-    variables::{VariableReference, Variables},
-    AsString, FileData, Position, Returnable, STD_DIR, STD_FILES,
+    variables::{
+        gc::{Trace, Tracer},
+        VariableReference, Variables,
+    },
+    AsString, FileData, Flow, Position, STD_DIR, STD_FILES,
 };
 
 use super::{
     built_in_functions::BuiltInFunctions,
     function::Function,
+    runtime::Runtime,
     type_hint::{Type, TypeHint},
     Class, ClassVariable, Table, Variable,
 };
 
-#[derive(Debug, Clone)]
-pub struct Context {
-    pub parent: Option<*mut Context>,
-    pub(crate) variables_defined_in_this_scope: HashMap<String, DefaultKey>,
-    pub(crate) classes: Table<Rc<Class>>,
-    pub(crate) file_data: Rc<FileData>,
+/// The storage behind a [`Context`], every handle to the same scope shares it
+pub(crate) struct Scope {
+    parent: Option<Context>,
+    variables_defined_in_this_scope: HashMap<String, DefaultKey>,
+    classes: Table<Rc<Class>>,
+    file_data: Rc<FileData>,
     imported_files: Vec<String>,
-    variables: *mut Variables,
-    persistent_storage: Vec<VariableReference>,
+    /// Variables the resolver assigned a slot to, see [`crate::resolver`]
+    slots: Vec<Option<DefaultKey>>,
+}
+
+impl Scope {
+    /// Drops everything the scope holds, used by the garbage collector
+    /// to break cycles between scopes that are no longer reachable
+    pub(crate) fn release(&mut self) {
+        self.parent = None;
+        self.variables_defined_in_this_scope.clear();
+        self.slots.clear();
+        self.classes.map.clear();
+    }
+}
+
+impl Trace for Scope {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(v) = &self.parent {
+            v.trace(tracer)
+        }
+        for key in self.variables_defined_in_this_scope.values() {
+            tracer.declared_variable(*key)
+        }
+        for key in self.slots.iter().flatten() {
+            tracer.declared_variable(*key)
+        }
+        for class in self.classes.map.values() {
+            tracer.class(class)
+        }
+    }
+}
+
+/// A shared handle to a scope, cloning it doesn't copy the scope
+#[derive(Clone)]
+pub struct Context {
+    scope: Rc<RefCell<Scope>>,
+    pub(crate) variables: Rc<RefCell<Variables>>,
+    pub(crate) runtime: Rc<RefCell<Runtime>>,
 }
 
 impl Context {
-    pub(crate) fn new(parent: *mut Context, file_data: Rc<FileData>) -> Context {
-        Context {
-            parent: Some(parent),
+    pub(crate) fn new(parent: &Context, file_data: Rc<FileData>) -> Context {
+        let scope = Rc::new(RefCell::new(Scope {
+            parent: Some(parent.clone()),
             variables_defined_in_this_scope: HashMap::with_capacity(0),
             classes: Table::new(),
             file_data,
             imported_files: vec![],
-            variables: unsafe { &*parent }.variables,
-            persistent_storage: vec![],
+            slots: vec![],
+        }));
+        parent.variables.borrow_mut().track_scope(&scope);
+        Context {
+            scope,
+            variables: parent.variables.clone(),
+            runtime: parent.runtime.clone(),
         }
     }
 
-    pub(crate) fn new_root(file_data: Rc<FileData>, variables: *mut Variables) -> Context {
-        Context {
+    pub(crate) fn new_root(
+        file_data: Rc<FileData>,
+        variables: Rc<RefCell<Variables>>,
+        runtime: Rc<RefCell<Runtime>>,
+    ) -> Context {
+        let scope = Rc::new(RefCell::new(Scope {
             parent: None,
             variables_defined_in_this_scope: HashMap::with_capacity(0),
             classes: Table::new(),
             file_data,
             imported_files: vec![],
+            slots: vec![],
+        }));
+        variables.borrow_mut().track_scope(&scope);
+        Context {
+            scope,
             variables,
-            persistent_storage: vec![],
+            runtime,
         }
     }
 
-    pub(crate) fn declare_function(&mut self, function: Function) {
+    pub(crate) fn parent(&self) -> Option<Context> {
+        self.scope.borrow().parent.clone()
+    }
+
+    /// Whether `ancestor` is this context or one of its parents
+    pub(crate) fn descends_from(&self, ancestor: &Context) -> bool {
+        let mut context = Some(self.clone());
+        while let Some(v) = context {
+            if Rc::ptr_eq(&v.scope, &ancestor.scope) {
+                return true;
+            }
+            context = v.parent();
+        }
+        false
+    }
+
+    /// Swaps out the parent scope, returning the previous one
+    pub(crate) fn set_parent(&self, parent: Option<Context>) -> Option<Context> {
+        std::mem::replace(&mut self.scope.borrow_mut().parent, parent)
+    }
+
+    pub(crate) fn file_data(&self) -> Rc<FileData> {
+        self.scope.borrow().file_data.clone()
+    }
+
+    pub(crate) fn defines(&self, identifier: &str) -> bool {
+        self.scope
+            .borrow()
+            .variables_defined_in_this_scope
+            .contains_key(identifier)
+    }
+
+    pub(crate) fn forget_variable(&self, identifier: &str) {
+        self.scope
+            .borrow_mut()
+            .variables_defined_in_this_scope
+            .remove(identifier);
+    }
+
+    pub(crate) fn variable_names(&self) -> Vec<String> {
+        self.scope
+            .borrow()
+            .variables_defined_in_this_scope
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Variables declared directly in this scope with their current values
+    pub(crate) fn variable_values(&self) -> Vec<(String, Value)> {
+        self.scope
+            .borrow()
+            .variables_defined_in_this_scope
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.clone(),
+                    Variables::access_variable(&self.variables, *v).data(),
+                )
+            })
+            .collect()
+    }
+
+    pub(crate) fn class_names(&self) -> Vec<String> {
+        self.scope.borrow().classes.map.keys().cloned().collect()
+    }
+
+    fn slot_key(&self, slot: Slot) -> Option<DefaultKey> {
+        let scope = self.scope.borrow();
+        match slot.depth {
+            0 => scope.slots.get(slot.index).copied().flatten(),
+            depth => scope.parent.as_ref()?.slot_key(Slot {
+                depth: depth - 1,
+                ..slot
+            }),
+        }
+    }
+
+    fn variable_key(&self, identifier: &str) -> Option<DefaultKey> {
+        self.scope
+            .borrow()
+            .variables_defined_in_this_scope
+            .get(identifier)
+            .copied()
+    }
+
+    pub(crate) fn declare_function(&self, function: Function) -> Result<(), Exception> {
+        let file_data = self.file_data();
+        let (start, end) = (function.start.clone(), function.end.clone());
         let type_hint = Type::new(
             TypeHint::None,
-            function.start.clone(),
-            function.end.clone(),
-            self.file_data.clone(),
+            start.clone(),
+            end.clone(),
+            file_data.clone(),
         );
         let identifier = function.identifier.clone();
-        match self.declare_variable(
+        self.declare_variable(
             identifier.clone(),
             Variable::new(
-                Data::new(
-                    self.file_data.clone(),
-                    function.start.clone(),
-                    function.end.clone(),
-                    DataType::Function(Box::new(function)),
-                ),
+                Value::Function(Rc::new(function)),
                 type_hint,
                 true,
                 identifier,
             ),
-        ) {
-            Ok(_) => {}
-            Err(v) => v.run(),
-        };
+            (&start, &end, &file_data),
+        )?;
+        Ok(())
     }
 
-    pub(crate) fn declare_class(&mut self, class: Class) {
-        self.classes
+    pub(crate) fn declare_class(&self, class: Class) {
+        self.scope
+            .borrow_mut()
+            .classes
             .map
             .insert(class.identifier.clone(), Rc::new(class));
     }
 
     pub(crate) fn call_function(
-        context: *mut Context,
+        context: &Context,
         identifier: &String,
-        args: Vec<Data>,
+        args: Vec<Value>,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
-    ) -> Result<Data, Exception> {
+    ) -> Result<Value, Exception> {
         BuiltInFunctions::run(context, identifier, args, (start, end, file_data))
     }
 
     pub(crate) fn call_fn_no_std(
-        context: *mut Context,
+        context: &Context,
         identifier: &String,
-        args: Vec<Data>,
+        args: Vec<Value>,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
-    ) -> Result<Data, Exception> {
-        match unsafe { &*context }
-            .variables_defined_in_this_scope
-            .get(identifier)
-        {
-            Some(v) => {
-                let var_ref = unsafe { &mut *(*context).variables }.access_variable(*v);
-                match &(*var_ref).data.data_type {
-                    DataType::Function(v) => v,
-                    _ => {
-                        return Err(VariableIsNotAFunction::call(
-                            start, end, file_data, identifier,
-                        ))
-                    }
-                }
-                .call(context, file_data.clone(), args)
+    ) -> Result<Value, Exception> {
+        let (scope, key) = match context.find_variable(identifier) {
+            Some(v) => v,
+            None => {
+                return Err(AccessUndeclaredFunction::call(
+                    start,
+                    end,
+                    file_data,
+                    identifier,
+                    context.suggest_function(identifier),
+                ))
+            }
+        };
+        let function = match Variables::access_variable(&scope.variables, key).data() {
+            Value::Function(v) => v,
+            _ => {
+                return Err(VariableIsNotAFunction::call(
+                    start, end, file_data, identifier,
+                ))
+            }
+        };
+        function.call(&scope, args, (start, end, file_data))
+    }
+
+    /// The variable `identifier` refers to and the context it is declared in
+    fn find_variable(&self, identifier: &str) -> Option<(Context, DefaultKey)> {
+        match self.variable_key(identifier) {
+            Some(v) => Some((self.clone(), v)),
+            None => self.parent()?.find_variable(identifier),
+        }
+    }
+
+    /// Every name a variable can be accessed by from this scope
+    fn visible_variables(&self) -> Vec<String> {
+        let mut names = self.variable_names();
+        if let Some(parent) = self.parent() {
+            names.extend(parent.visible_variables())
+        }
+        names
+    }
+
+    fn visible_classes(&self) -> Vec<String> {
+        let mut names = self.class_names();
+        if let Some(parent) = self.parent() {
+            names.extend(parent.visible_classes())
+        }
+        names
+    }
+
+    fn suggest_variable(&self, identifier: &str) -> Option<String> {
+        closest_match(identifier, &self.visible_variables())
+    }
+
+    fn suggest_function(&self, identifier: &str) -> Option<String> {
+        let mut names = self.visible_variables();
+        names.extend(BuiltInFunctions::FUNCTIONS.iter().map(|v| v.0.to_string()));
+        closest_match(identifier, &names)
+    }
+
+    fn suggest_class(&self, identifier: &str) -> Option<String> {
+        closest_match(identifier, &self.visible_classes())
+    }
+
+    /// Calls the function like [`Context::call_function`], but hands a script function back to
+    /// the `Function::call` that returns its result so it can run without nesting
+    pub(crate) fn tail_call(
+        context: &Context,
+        identifier: &String,
+        args: Vec<Value>,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Flow, Exception> {
+        if !BuiltInFunctions::defines(identifier) {
+            if let Some((scope, function)) = context.lookup_function(identifier) {
+                return Ok(Flow::TailCall(scope, function, args));
             }
-            None => match unsafe { &mut *context }.parent {
-                Some(v) => Context::call_function(v, identifier, args, (start, end, file_data)),
-                None => {
-                    let mut current = context;
-                    loop {
-                        println!(
-                            "{}",
-                            unsafe { &*current }
-                                .variables_defined_in_this_scope
-                                .iter()
-                                .map(|x| format!("{} ", x.0))
-                                .collect::<String>()
-                        );
-                        current = match unsafe { &*current }.parent {
-                            Some(v) => v,
-                            None => break,
-                        };
-                    }
-                    Err(AccessUndeclaredFunction::call(
-                        start, end, file_data, identifier,
-                    ))
-                }
+        }
+        Ok(Flow::Return(Context::call_function(
+            context,
+            identifier,
+            args,
+            (start, end, file_data),
+        )?))
+    }
+
+    /// The function `identifier` refers to and the context it is declared in
+    fn lookup_function(&self, identifier: &str) -> Option<(Context, Rc<Function>)> {
+        match self.variable_key(identifier) {
+            Some(v) => match Variables::access_variable(&self.variables, v).data() {
+                Value::Function(v) => Some((self.clone(), v)),
+                _ => None,
             },
+            None => self.parent()?.lookup_function(identifier),
         }
     }
 
     pub(crate) fn call_override_class_fn(
-        context: *mut Context,
+        context: &Context,
         identifier: &String,
-        args: Vec<Data>,
+        args: Vec<Value>,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
-    ) -> Result<Data, Exception> {
-        if unsafe { &*context }
-            .variables_defined_in_this_scope
-            .contains_key(identifier)
-        {
+    ) -> Result<Value, Exception> {
+        if context.defines(identifier) {
             Context::call_fn_no_std(context, identifier, args, (start, end, file_data))
         } else {
             Err(AccessUndeclaredFunction::call(
-                start, end, file_data, identifier,
+                start,
+                end,
+                file_data,
+                identifier,
+                closest_match(identifier, &context.variable_names()),
             ))
         }
     }
@@ -171,13 +365,10 @@ impl Context {
         &self,
         identifier: &String,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
-    ) -> Result<&Data, Exception> {
-        Ok(&unsafe {
-            &*self
-                .access_variable(identifier, (start, end, file_data))?
-                .reference
-        }
-        .data)
+    ) -> Result<Value, Exception> {
+        Ok(self
+            .access_variable(identifier, (start, end, file_data))?
+            .data())
     }
 
     pub(crate) fn access_variable(
@@ -185,17 +376,28 @@ impl Context {
         identifier: &String,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
     ) -> Result<VariableReference, Exception> {
-        match self
-            .variables_defined_in_this_scope
-            .get(&identifier.clone())
-        {
-            Some(v) => Ok(unsafe { &mut *self.variables }.access_variable(*v)),
-            None => match &self.parent {
-                Some(v) => unsafe { &**v }.access_variable(identifier, (start, end, file_data)),
-                None => Err(AccessUndeclaredVariable::call(
-                    start, end, file_data, identifier,
-                )),
-            },
+        match self.find_variable(identifier) {
+            Some((_, v)) => Ok(Variables::access_variable(&self.variables, v)),
+            None => Err(AccessUndeclaredVariable::call(
+                start,
+                end,
+                file_data,
+                identifier,
+                self.suggest_variable(identifier),
+            )),
+        }
+    }
+
+    /// Like [`Context::access_variable`] but skips the lookup by name if the slot is filled
+    pub(crate) fn access_variable_in_slot(
+        &self,
+        identifier: &String,
+        slot: Option<Slot>,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<VariableReference, Exception> {
+        match slot.and_then(|v| self.slot_key(v)) {
+            Some(v) => Ok(Variables::access_variable(&self.variables, v)),
+            None => self.access_variable(identifier, (start, end, file_data)),
         }
     }
 
@@ -204,118 +406,198 @@ impl Context {
         identifier: &String,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
     ) -> Result<Rc<Class>, Exception> {
-        match self.classes.map.get(&identifier.clone()) {
-            Some(v) => Ok(v.clone()),
-            None => match &self.parent {
-                Some(v) => unsafe { &**v }.access_class(identifier, (start, end, file_data)),
-                None => Err(AccessUndeclaredClass::call(
-                    start, end, file_data, identifier,
-                )),
-            },
+        match self.find_class(identifier) {
+            Some(v) => Ok(v),
+            None => Err(AccessUndeclaredClass::call(
+                start,
+                end,
+                file_data,
+                identifier,
+                self.suggest_class(identifier),
+            )),
+        }
+    }
+
+    fn find_class(&self, identifier: &str) -> Option<Rc<Class>> {
+        let class = self.scope.borrow().classes.map.get(identifier).cloned();
+        match class {
+            Some(v) => Some(v),
+            None => self.parent()?.find_class(identifier),
         }
     }
 
     pub(crate) fn new_class(
-        parent: *mut Context,
+        parent: &Context,
         identifier: &String,
-        args: Vec<Data>,
+        args: Vec<Value>,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
-    ) -> Result<Data, Exception> {
-        let class = unsafe { &mut *parent }.access_class(identifier, (start, end, file_data))?;
-        let class_var = ClassVariable::new(
-            &*class,
-            parent,
-            args,
-            (start.clone(), end.clone(), file_data.clone()),
-        )?;
+    ) -> Result<Value, Exception> {
+        let class = parent.access_class(identifier, (start, end, file_data))?;
+        let class_var = ClassVariable::new(&class, parent, args, (start, end, file_data))?;
 
-        Ok(Data::new(
-            file_data.clone(),
-            start.clone(),
-            end.clone(),
-            DataType::Class(Box::new(class_var)),
-        ))
+        Ok(Value::Class(Rc::new(class_var)))
     }
 
     pub(crate) fn update_variable(
-        context: *mut Context,
+        context: &Context,
         identifier: &String,
-        data: Data,
+        data: Value,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
     ) -> Result<(), Exception> {
-        let ctx = unsafe { &mut *context };
-        match ctx.variables_defined_in_this_scope.get(identifier) {
-            Some(_) => {
-                let var = &mut ctx.access_variable(identifier, (start, end, file_data))?;
-                if var.is_final {
-                    return Err(VariableIsFinal::call(start, end, file_data, identifier));
-                }
-                var.data.original().data_type.is_of_type(
-                    &var.type_hint,
-                    identifier,
-                    (start, end, file_data),
-                )?;
-                *var.data.original_mut() = data
-            }
-            None => match ctx.parent {
-                Some(v) => Context::update_variable(v, identifier, data, (start, end, file_data))?,
-                None => {
-                    return Err(UpdateUndeclaredVariable::call(
-                        start, end, file_data, identifier,
-                    ))
-                }
-            },
+        match context.find_variable(identifier) {
+            Some((scope, v)) => scope.update_key(v, identifier, data, (start, end, file_data)),
+            None => Err(UpdateUndeclaredVariable::call(
+                start,
+                end,
+                file_data,
+                identifier,
+                context.suggest_variable(identifier),
+            )),
         }
-        Ok(())
+    }
+
+    /// Like [`Context::update_variable`] but skips the lookup by name if the slot is filled
+    pub(crate) fn update_variable_in_slot(
+        context: &Context,
+        identifier: &String,
+        slot: Option<Slot>,
+        data: Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<(), Exception> {
+        match slot.and_then(|v| context.slot_key(v)) {
+            Some(v) => context.update_key(v, identifier, data, (start, end, file_data)),
+            None => Context::update_variable(context, identifier, data, (start, end, file_data)),
+        }
+    }
+
+    fn update_key(
+        &self,
+        key: DefaultKey,
+        identifier: &String,
+        data: Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<(), Exception> {
+        let var = Variables::access_variable(&self.variables, key);
+        var.with_variable(|variable| {
+            if variable.is_final {
+                return Err(VariableIsFinal::call(start, end, file_data, identifier));
+            }
+            variable.data.original().is_of_type(
+                &variable.type_hint,
+                identifier,
+                (start, end, file_data),
+            )
+        })?;
+        var.set_original(data)
+            .map_err(|v| v.at((start, end, file_data)))
     }
 
     pub(crate) fn assign_variable(
-        &mut self,
+        &self,
         identifier: String,
-        data: Data,
+        data: Value,
         type_hint: Type,
         is_final: bool,
+        slot: Option<usize>,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
     ) -> Result<(), Exception> {
         data.original()
-            .data_type
             .is_of_type(&type_hint, &identifier, (start, end, file_data))?;
         if let TypeHint::Class(i) = &type_hint.type_value {
             if !self.has_class(i) {
-                return Err(AccessUndeclaredClass::call(start, end, file_data, i));
+                return Err(AccessUndeclaredClass::call(
+                    start,
+                    end,
+                    file_data,
+                    i,
+                    self.suggest_class(i),
+                ));
             }
         }
-        self.declare_variable(
+        let key = self.declare_variable(
             identifier.clone(),
             Variable::new(data, type_hint, is_final, identifier),
-        )
+            (start, end, file_data),
+        )?;
+        if let Some(index) = slot {
+            let slots = &mut self.scope.borrow_mut().slots;
+            if slots.len() <= index {
+                slots.resize(index + 1, None)
+            }
+            slots[index] = Some(key)
+        }
+        Ok(())
     }
 
     pub(crate) fn declare_variable(
-        &mut self,
+        &self,
         identifier: String,
         variable: Variable,
-    ) -> Result<(), Exception> {
-        self.variables_defined_in_this_scope.insert(
-            identifier.clone(),
-            unsafe { &mut *self.variables }.declare_variable(variable),
-        );
-        self.persistent_storage.push(
-            unsafe { &mut *self.variables }.access_variable(
-                *self
-                    .variables_defined_in_this_scope
-                    .get(&identifier)
-                    .unwrap(),
-            ),
-        );
-        Ok(())
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<DefaultKey, Exception> {
+        let key = self
+            .variables
+            .borrow_mut()
+            .declare_variable(variable)
+            .map_err(|v| v.at(position))?;
+        self.scope
+            .borrow_mut()
+            .variables_defined_in_this_scope
+            .insert(identifier, key);
+        Ok(key)
+    }
+
+    /// Runs a `use` statement, files outside of the standard library go through the runtime's
+    /// file system capability first
+    pub(crate) fn use_file(
+        context: &Context,
+        file_path: &String,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Flow, Exception> {
+        let file_path = if STD_FILES.contains(&file_path.as_str()) {
+            file_path.clone()
+        } else {
+            let path = context
+                .runtime
+                .borrow()
+                .resolve_path(file_path, (start, end, file_data))?;
+            if !path.exists() {
+                return Err(InvalidFilePath::call(start, end, file_data, file_path));
+            }
+            path.to_string_lossy().to_string()
+        };
+        Context::import_file(context, &file_path, (start, end, file_data))
+    }
+
+    /// Runs `run` in the scope of the class instance `target`, with `context` as its parent
+    /// for the duration of the call
+    pub(crate) fn run_in_context_of(
+        context: &Context,
+        target: &Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+        run: impl FnOnce(&Context) -> Result<Flow, Exception>,
+    ) -> Result<Flow, Exception> {
+        let reference = target.original();
+        match reference.as_ref() {
+            // Already inside the class, like when a method call is an argument of another
+            // method of the same instance, making it a child of this context would make it
+            // its own parent
+            Value::Class(v) if context.descends_from(&v.context) => run(context),
+            Value::Class(v) => {
+                let parent = v.context.set_parent(Some(context.clone()));
+                let return_value = run(&v.context);
+                v.context.set_parent(parent);
+                return_value
+            }
+            _ => Err(CantRunInContext::call(start, end, file_data, &reference)),
+        }
     }
 
     pub(crate) fn import_file(
-        context: *mut Context,
+        context: &Context,
         file_path: &str,
-        file_data: Rc<FileData>,
-    ) -> Result<Returnable, Exception> {
+        location: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Flow, Exception> {
         if STD_FILES.contains(&file_path) {
             Context::import_data(
                 context,
@@ -329,64 +611,94 @@ impl Context {
                     file_path.to_string(),
                 )),
             )
-        } else if unsafe { &*context }
+        } else if context
+            .scope
+            .borrow()
             .imported_files
             .contains(&file_path.to_string())
         {
-            Ok(Returnable::Evaluate(Data::null_zero(file_data)))
+            Ok(Flow::Evaluate(Value::Null))
         } else {
-            unsafe { &mut *context }
+            context
+                .scope
+                .borrow_mut()
                 .imported_files
                 .push(file_path.to_string());
-            run_from_file(file_path, context)
+            run_from_file(file_path, context, RootScope::Shared, location)
         }
     }
 
     pub(crate) fn import_data(
-        context: *mut Context,
+        context: &Context,
         file_data: Rc<FileData>,
-    ) -> Result<Returnable, Exception> {
-        if unsafe { &*context }
+    ) -> Result<Flow, Exception> {
+        if context
+            .scope
+            .borrow()
             .imported_files
             .contains(&file_data.path.to_string())
         {
-            Ok(Returnable::Evaluate(Data::null_zero(file_data)))
+            Ok(Flow::Evaluate(Value::Null))
         } else {
-            unsafe { &mut *context }
+            context
+                .scope
+                .borrow_mut()
                 .imported_files
                 .push(file_data.path.to_string());
-            run_with_data(file_data, context)
+            run_with_data(file_data, context, RootScope::Shared)
         }
     }
 
     pub(crate) fn has_file(&self, file_path: &str) -> bool {
-        if self.imported_files.contains(&file_path.to_string()) {
+        if self
+            .scope
+            .borrow()
+            .imported_files
+            .contains(&file_path.to_string())
+        {
             true
-        } else if self.parent.is_some() {
-            unsafe { &*self.parent.unwrap() }.has_file(file_path)
         } else {
-            false
+            match self.parent() {
+                Some(v) => v.has_file(file_path),
+                None => false,
+            }
         }
     }
 
     pub(crate) fn has_class(&self, identifier: &String) -> bool {
-        if self.classes.map.contains_key(identifier) {
+        if self.scope.borrow().classes.map.contains_key(identifier) {
             true
-        } else if self.parent.is_some() {
-            unsafe { &*self.parent.unwrap() }.has_class(identifier)
         } else {
-            false
+            match self.parent() {
+                Some(v) => v.has_class(identifier),
+                None => false,
+            }
         }
     }
 
     pub(crate) fn depth(&self) -> usize {
-        match self.parent.as_ref() {
-            Some(v) => unsafe { &**v }.depth() + 1,
+        match self.parent() {
+            Some(v) => v.depth() + 1,
             None => 0,
         }
     }
 }
 
+impl Trace for Context {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.scope(&self.scope)
+    }
+}
+
+impl Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Context")
+            .field("variables", &self.variable_names())
+            .field("classes", &self.class_names())
+            .finish()
+    }
+}
+
 impl AsString for Rc<RefCell<Variable>> {
     fn as_string(&self) -> String {
         format!("{}", self.borrow())
@@ -1,12 +1,15 @@
 use std::rc::Rc;
 
 use crate::{
+    engine::Backend,
     exceptions::{
         interpreter_exceptions::{InvalidAmountOfArguments, InvalidArgumentType},
         Exception,
     },
-    parser::data::Data,
-    FileData, Position,
+    parser::data::Value,
+    variables::gc::{Trace, Tracer},
+    vm::{self, chunk::Chunk, compiler::Compiler},
+    FileData, Flow, Position,
 };
 
 use super::{
@@ -16,9 +19,28 @@ use super::{
 };
 
 #[derive(Debug, Clone)]
-pub(crate) struct Function {
+pub(crate) enum FunctionBody {
+    Tree(Instruction),
+    Bytecode(Rc<Chunk>),
+}
+
+impl FunctionBody {
+    /// Compiles `body` if the run uses the bytecode backend
+    pub(crate) fn new(body: Instruction, context: &Context) -> Self {
+        let backend = context.runtime.borrow().backend;
+        match backend {
+            Backend::TreeWalker => FunctionBody::Tree(body),
+            Backend::Bytecode => {
+                FunctionBody::Bytecode(Rc::new(Compiler::compile_expression(&body)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
     pub(crate) arguments: Vec<Argument>,
-    pub(crate) body: Instruction,
+    pub(crate) body: FunctionBody,
     pub(crate) start: Position,
     pub(crate) end: Position,
     pub(crate) identifier: String,
@@ -27,44 +49,66 @@ pub(crate) struct Function {
 impl Function {
     pub(crate) fn new(
         arguments: &[(String, Type, Option<Instruction>)],
-        body: Instruction,
+        body: FunctionBody,
         start: Position,
         end: Position,
         identifier: String,
-        context: *mut Context,
-    ) -> Self {
+        context: &Context,
+    ) -> Result<Self, Exception> {
         let v: Vec<Argument> = arguments
             .iter()
             .map(|(i, th, dv)| {
-                Argument::new(
+                Ok(Argument::new(
                     i.clone(),
                     th.clone(),
                     match dv {
-                        Some(v) => match v.visit(context) {
-                            Ok(v) => Some(v.unwrap()),
-                            Err(v) => v.run(),
-                        },
+                        Some(v) => Some(v.visit(context)?.unwrap()),
                         None => None,
                     },
-                )
+                ))
             })
-            .collect();
-        Self {
+            .collect::<Result<_, Exception>>()?;
+        Ok(Self {
             arguments: v,
             body,
             start,
             end,
             identifier,
-        }
+        })
     }
 
+    /// Calls the function in a new child of `context`, `position` is the call site
     pub(crate) fn call(
+        self: &Rc<Self>,
+        context: &Context,
+        args: Vec<Value>,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        let (mut function, mut context, mut args) = (self.clone(), context.clone(), args);
+        // Tail calls come back here instead of calling again, so the stack stays flat
+        loop {
+            let func_context = function.bind(&context, &args, (start, end, file_data))?;
+            let returnable = match &function.body {
+                FunctionBody::Tree(v) => v.visit(&func_context)?,
+                FunctionBody::Bytecode(v) => vm::run(v, &func_context)?,
+            };
+            match returnable {
+                Flow::TailCall(scope, next, next_args) => {
+                    (function, context, args) = (next, scope, next_args)
+                }
+                v => return Ok(v.unwrap()),
+            }
+        }
+    }
+
+    /// Creates the context the body runs in with the arguments assigned
+    fn bind(
         &self,
-        context: *mut Context,
-        file_data: Rc<FileData>,
-        args: Vec<Data>,
-    ) -> Result<Data, Exception> {
-        let mut func_context = Context::new(context, file_data.clone());
+        context: &Context,
+        args: &[Value],
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Context, Exception> {
+        let func_context = Context::new(context, file_data.clone());
 
         for (i, arg) in self.arguments.iter().enumerate() {
             match &arg.default_value {
@@ -73,14 +117,15 @@ impl Function {
                     v.clone(),
                     arg.type_hint.clone(),
                     true,
-                    (&self.start, &self.end, &file_data),
+                    Some(i),
+                    (&self.start, &self.end, file_data),
                 )?,
                 None => {
                     if args.len() <= i {
                         return Err(InvalidAmountOfArguments::call(
                             &self.start,
                             &self.end,
-                            &file_data.clone(),
+                            file_data,
                             &self.identifier,
                             self.arguments.len(),
                             args.len(),
@@ -91,32 +136,32 @@ impl Function {
             if args.len() <= i {
                 continue;
             }
-            match (&arg.type_hint.type_value, &args[i].original().data_type) {
-                (TypeHint::Integer, crate::parser::data::DataType::Integer(_))
-                | (TypeHint::String, crate::parser::data::DataType::String(_))
-                | (TypeHint::Float, crate::parser::data::DataType::Float(_))
+            match (&arg.type_hint.type_value, args[i].original().as_ref()) {
+                (TypeHint::Integer, Value::Integer(_))
+                | (TypeHint::String, Value::String(_))
+                | (TypeHint::Float, Value::Float(_))
                 | (TypeHint::None, _)
-                | (_, crate::parser::data::DataType::Null) => {}
-                (TypeHint::Class(v), crate::parser::data::DataType::Class(class)) => {
+                | (_, Value::Null) => {}
+                (TypeHint::Class(v), Value::Class(class)) => {
                     if &class.class_name != v {
                         return Err(InvalidArgumentType::call(
-                            &args[i].start,
-                            &args[i].end,
-                            &args[i].file_data,
+                            start,
+                            end,
+                            file_data,
                             &arg.identifier,
                             &arg.type_hint.type_value,
-                            &args[i].data_type,
+                            &args[i],
                         ));
                     }
                 }
                 _ => {
                     return Err(InvalidArgumentType::call(
-                        &args[i].start,
-                        &args[i].end,
-                        &args[i].file_data,
+                        start,
+                        end,
+                        file_data,
                         &arg.identifier,
                         &arg.type_hint.type_value,
-                        &args[i].data_type,
+                        &args[i],
                     ))
                 }
             }
@@ -125,12 +170,20 @@ impl Function {
                 args[i].to_owned(),
                 arg.type_hint.clone(),
                 true,
-                (&args[i].start, &args[i].end, &args[i].file_data),
+                Some(i),
+                (start, end, file_data),
             )?;
         }
-        match self.body.visit(&mut func_context) {
-            Ok(v) => Ok(v.unwrap()),
-            Err(v) => Err(v),
+        Ok(func_context)
+    }
+}
+
+impl Trace for Function {
+    fn trace(&self, tracer: &mut Tracer) {
+        for argument in self.arguments.iter() {
+            if let Some(v) = &argument.default_value {
+                v.trace(tracer)
+            }
         }
     }
 }
@@ -139,11 +192,11 @@ impl Function {
 pub(crate) struct Argument {
     identifier: String,
     type_hint: Type,
-    default_value: Option<Data>,
+    default_value: Option<Value>,
 }
 
 impl Argument {
-    pub(crate) fn new(identifier: String, type_hint: Type, default_value: Option<Data>) -> Self {
+    pub(crate) fn new(identifier: String, type_hint: Type, default_value: Option<Value>) -> Self {
         Self {
             identifier,
             type_hint,
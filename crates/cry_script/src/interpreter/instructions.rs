@@ -1,27 +1,31 @@
-use std::{fmt::Display, path::Path, rc::Rc};
+use std::{fmt::Display, rc::Rc};
 
 use crate::{
-    exceptions::{
-        interpreter_exceptions::{CantRunInContext, InvalidFilePath, ReturnFromRoot},
-        Exception,
-    },
-    parser::data::{Data, DataType},
-    run_with_instructions, FileData, Position, Returnable,
+    exceptions::{interpreter_exceptions::ReturnFromRoot, Exception},
+    parser::data::Value,
+    resolver::Slot,
+    run_with_instructions, FileData, Flow, Position,
 };
 pub mod binary_op;
 pub mod unary_op;
 
 use self::{binary_op::BinaryOperator, unary_op::UnaryOperator};
 
-use super::{context::Context, function::Function, type_hint::Type, Class};
+use super::{
+    context::Context,
+    function::{Function, FunctionBody},
+    type_hint::Type,
+    Class,
+};
 
 #[macro_export]
 macro_rules! returnable {
     ($expr:expr $(,)?) => {
         match $expr {
-            $crate::Returnable::Return(val) => return Ok(crate::Returnable::Return(val)),
-            $crate::Returnable::Break(val) => val,
-            $crate::Returnable::Evaluate(val) => val,
+            $crate::Flow::Return(val) => return Ok($crate::Flow::Return(val)),
+            v @ $crate::Flow::TailCall(..) => return Ok(v),
+            $crate::Flow::Break(val) => val,
+            $crate::Flow::Evaluate(val) => val,
         }
     };
 }
@@ -46,13 +50,17 @@ pub(crate) enum InstructionType {
         data: Box<Instruction>,
         type_hint: Type,
         is_final: bool,
+        /// Set by the resolver, `None` if the variable can only be found by name
+        slot: Option<usize>,
     },
     VarUpdate {
         identifier: String,
         data: Box<Instruction>,
+        slot: Option<Slot>,
     },
     VarAccess {
         identifier: String,
+        slot: Option<Slot>,
     },
     IfStatement {
         condition: Option<Box<Instruction>>,
@@ -77,6 +85,8 @@ pub(crate) enum InstructionType {
     },
     ReturnStatement {
         value: Box<Instruction>,
+        /// Set by the resolver when `value` calls the function the statement is in
+        tail_call: bool,
     },
     BreakStatement {
         value: Box<Instruction>,
@@ -105,7 +115,7 @@ pub(crate) enum InstructionType {
         comment: String,
         value: Box<Instruction>,
     },
-    Data(Data),
+    Data(Value),
 }
 
 #[derive(Debug, Clone)]
@@ -131,121 +141,61 @@ impl Instruction {
         }
     }
 
-    pub(crate) fn visit(&self, context_ptr: *mut Context) -> Result<Returnable, Exception> {
-        let context_ref = unsafe { &mut *context_ptr };
+    pub(crate) fn visit(&self, context: &Context) -> Result<Flow, Exception> {
+        let position = (&self.start, &self.end, &self.file_data);
+        context.runtime.borrow_mut().budget.step(position)?;
         match &self.instruction_type {
-            InstructionType::Data(data) => Ok(Returnable::Evaluate(data.clone())),
+            InstructionType::Data(data) => Ok(Flow::Evaluate(data.clone())),
             InstructionType::BinaryOperation {
                 left,
                 right,
                 operator,
             } => {
-                let mut base_left = returnable!(left.visit(context_ptr)?);
-                let base_right = returnable!(right.visit(context_ptr)?);
-
-                let original_left = base_left.original();
-                let original_right = base_right.original();
-
-                Ok(Returnable::Evaluate(match operator {
-                    BinaryOperator::AddAssign => {
-                        let data =
-                            Data::add(&base_left, &base_right, original_left, original_right)?;
-                        base_left.original_mut().data_type = data.data_type;
-                        base_left
-                    }
-                    BinaryOperator::RemoveAssign => {
-                        let data =
-                            Data::sub(&base_left, &base_right, original_left, original_right)?;
-                        base_left.original_mut().data_type = data.data_type;
-                        base_left
-                    }
-                    BinaryOperator::MultiplyAssign => {
-                        let data =
-                            Data::mul(&base_left, &base_right, original_left, original_right)?;
-                        base_left.original_mut().data_type = data.data_type;
-                        base_left
-                    }
-                    BinaryOperator::DivideAssign => {
-                        let data =
-                            Data::div(&base_left, &base_right, original_left, original_right)?;
-                        base_left.original_mut().data_type = data.data_type;
-                        base_left
-                    }
-                    BinaryOperator::PowerAssign => {
-                        let data =
-                            Data::pow(&base_left, &base_right, original_left, original_right)?;
-                        base_left.original_mut().data_type = data.data_type;
-                        base_left
-                    }
-                    _ => {
-                        operator.operate(&base_left, &base_right, original_left, original_right)?
-                        // dbg!(&x.data_type, &operator, &base_left.data_type, base_right.data_type);
-                        // x
-                    }
-                }))
+                let base_left = returnable!(left.visit(context)?);
+                let base_right = returnable!(right.visit(context)?);
+                Ok(Flow::Evaluate(operator.evaluate(
+                    base_left,
+                    &base_right,
+                    position,
+                )?))
             }
-            InstructionType::UnaryOperation { value, operator } => Ok(Returnable::Evaluate({
-                let data_ref = returnable!(value.visit(context_ptr)?);
-                operator.operate(&data_ref, data_ref.original())?
+            InstructionType::UnaryOperation { value, operator } => Ok(Flow::Evaluate({
+                let data_ref = returnable!(value.visit(context)?);
+                operator.operate(&data_ref.original(), position)?
             })),
-            InstructionType::UseStatement { file_path } => {
-                if !file_path.starts_with("std_") && !Path::new(file_path).exists() {
-                    return Err(InvalidFilePath::call(
-                        &self.start,
-                        &self.end,
-                        &self.file_data,
-                        file_path,
-                    ));
-                }
-                Ok(Returnable::Evaluate(returnable!(Context::import_file(
-                    context_ptr,
-                    file_path,
-                    self.file_data.clone()
-                )?)))
-            }
-            InstructionType::VarAccess { identifier } => {
-                let original = context_ref
-                    .access_variable(identifier, (&self.start, &self.end, &self.file_data))?;
-                Ok(Returnable::Evaluate(Data::new(
-                    self.file_data.clone(),
-                    self.start.clone(),
-                    self.end.clone(),
-                    DataType::Reference(original),
-                )))
+            InstructionType::UseStatement { file_path } => Ok(Flow::Evaluate(returnable!(
+                Context::use_file(context, file_path, position)?
+            ))),
+            InstructionType::VarAccess { identifier, slot } => {
+                let original = context.access_variable_in_slot(identifier, *slot, position)?;
+                Ok(Flow::Evaluate(Value::Reference(original)))
             }
             InstructionType::VarAssign {
                 identifier,
                 data,
                 type_hint,
                 is_final,
+                slot,
             } => {
-                let data = returnable!(data.visit(context_ptr)?); // To prevent runtime borrow errors
-                context_ref.assign_variable(
+                let data = returnable!(data.visit(context)?); // To prevent runtime borrow errors
+                context.assign_variable(
                     identifier.clone(),
                     data,
                     type_hint.clone(),
                     *is_final,
-                    (&self.start, &self.end, &self.file_data),
+                    *slot,
+                    position,
                 )?;
-                Ok(Returnable::Evaluate(Data::null(
-                    self.file_data.clone(),
-                    self.start.clone(),
-                    self.end.clone(),
-                )))
+                Ok(Flow::Evaluate(Value::Null))
             }
-            InstructionType::VarUpdate { identifier, data } => {
-                let data = returnable!(data.visit(context_ptr)?); // To prevent runtime borrow errors
-                Context::update_variable(
-                    context_ptr,
-                    identifier,
-                    data,
-                    (&self.start, &self.end, &self.file_data),
-                )?;
-                Ok(Returnable::Evaluate(Data::null(
-                    self.file_data.clone(),
-                    self.start.clone(),
-                    self.end.clone(),
-                )))
+            InstructionType::VarUpdate {
+                identifier,
+                data,
+                slot,
+            } => {
+                let data = returnable!(data.visit(context)?); // To prevent runtime borrow errors
+                Context::update_variable_in_slot(context, identifier, *slot, data, position)?;
+                Ok(Flow::Evaluate(Value::Null))
             }
             InstructionType::IfStatement {
                 condition,
@@ -253,113 +203,116 @@ impl Instruction {
                 else_value,
             } => {
                 if (condition.is_some()
-                    && returnable!(condition.as_ref().unwrap().visit(context_ptr)?).as_bool()?)
+                    && returnable!(condition.as_ref().unwrap().visit(context)?)
+                        .as_bool(position)?)
                     || condition.is_none()
                 {
-                    body.visit(context_ptr)
+                    body.visit(context)
                 } else if else_value.is_some() {
-                    else_value.as_ref().unwrap().visit(context_ptr)
+                    else_value.as_ref().unwrap().visit(context)
                 } else {
-                    Ok(Returnable::Evaluate(Data::null(
-                        self.file_data.clone(),
-                        self.start.clone(),
-                        self.end.clone(),
-                    )))
+                    Ok(Flow::Evaluate(Value::Null))
                 }
             }
             InstructionType::WhileStatement { condition, body } => {
-                let mut return_value = Returnable::Evaluate(Data::null(
-                    self.file_data.clone(),
-                    self.start.clone(),
-                    self.end.clone(),
-                ));
-                while condition.visit(context_ptr)?.unwrap().as_bool()? {
-                    return_value = match body.visit(context_ptr)? {
-                        Returnable::Return(v) => return Ok(Returnable::Return(v)),
-                        Returnable::Evaluate(v) => Returnable::Evaluate(v),
-                        Returnable::Break(v) => {
-                            return_value = Returnable::Evaluate(v);
+                let mut return_value = Flow::Evaluate(Value::Null);
+                while condition.visit(context)?.unwrap().as_bool(position)? {
+                    return_value = match body.visit(context)? {
+                        Flow::Return(v) => return Ok(Flow::Return(v)),
+                        v @ Flow::TailCall(..) => return Ok(v),
+                        Flow::Evaluate(v) => Flow::Evaluate(v),
+                        Flow::Break(v) => {
+                            return_value = Flow::Evaluate(v);
                             break;
                         }
-                    }
+                    };
+                    // Loop back-edge, an empty body still has to pay for every iteration
+                    context.runtime.borrow_mut().budget.step(position)?;
                 }
                 Ok(return_value)
             }
             InstructionType::Section { body } => Ok(run_with_instructions(
                 body,
-                &mut Context::new(context_ptr, self.file_data.clone()),
-                self.file_data.clone(),
+                &Context::new(context, self.file_data.clone()),
             )?),
-            InstructionType::ReturnStatement { value } => {
-                if context_ref.depth() == 0 {
+            InstructionType::ReturnStatement { value, tail_call } => {
+                if context.depth() == 0 {
                     return Err(ReturnFromRoot::call(
                         &self.start,
                         &self.end,
                         &self.file_data,
                     ));
                 }
-                let v = value.visit(context_ptr)?;
-                Ok(Returnable::Return(returnable!(v)))
+                if let (
+                    true,
+                    InstructionType::FunctionCall {
+                        identifier,
+                        arguments,
+                    },
+                ) = (tail_call, &value.instruction_type)
+                {
+                    let arguments = arguments
+                        .iter()
+                        .map(|x| Ok(x.visit(context)?.unwrap()))
+                        .collect::<Result<_, Exception>>()?;
+                    return Context::tail_call(context, identifier, arguments, position);
+                }
+                let v = value.visit(context)?;
+                Ok(Flow::Return(returnable!(v)))
             }
             InstructionType::BreakStatement { value } => {
-                if context_ref.depth() == 0 {
+                if context.depth() == 0 {
                     return Err(ReturnFromRoot::call(
                         &self.start,
                         &self.end,
                         &self.file_data,
                     ));
                 }
-                let v = value.visit(context_ptr)?;
-                Ok(Returnable::Break(returnable!(v)))
+                let v = value.visit(context)?;
+                Ok(Flow::Break(returnable!(v)))
             }
             InstructionType::FunctionDeclaration {
                 identifier,
                 body,
                 arguments,
             } => {
-                context_ref.declare_function(Function::new(
+                context.declare_function(Function::new(
                     arguments,
-                    *body.clone(),
+                    FunctionBody::Tree(*body.clone()),
                     self.start.clone(),
                     self.end.clone(),
                     identifier.clone(),
-                    context_ptr,
-                ));
-                Ok(Returnable::Evaluate(Data::null(
-                    self.file_data.clone(),
-                    self.start.clone(),
-                    self.end.clone(),
-                )))
+                    context,
+                )?)?;
+                Ok(Flow::Evaluate(Value::Null))
             }
             InstructionType::FunctionCall {
                 identifier,
                 arguments,
-            } => Ok(Returnable::Evaluate(Context::call_function(
-                context_ptr,
+            } => Ok(Flow::Evaluate(Context::call_function(
+                context,
                 identifier,
                 arguments
                     .iter()
-                    .map(|x| match x.visit(context_ptr) {
-                        Ok(v) => v.unwrap(),
-                        Err(v) => v.run(),
-                    })
-                    .collect(),
-                (&self.start, &self.end, &self.file_data),
+                    .map(|x| Ok(x.visit(context)?.unwrap()))
+                    .collect::<Result<_, Exception>>()?,
+                position,
             )?)),
             InstructionType::DoCatch {
                 catch_body,
                 do_body,
-            } => match do_body.visit(context_ptr) {
+            } => match do_body.visit(context) {
                 Ok(v) => Ok(v),
-                Err(_) => catch_body.visit(context_ptr),
+                Err(e) if e.kind().is_catchable() => catch_body.visit(context),
+                Err(e) => Err(e),
             },
             InstructionType::As {
                 convert_type,
                 value,
-            } => Ok(Returnable::Evaluate(
-                returnable!(value.visit(context_ptr)?).convert_to(convert_type)?,
+            } => Ok(Flow::Evaluate(
+                returnable!(value.visit(context)?).convert_to(convert_type, position)?,
             )),
-            InstructionType::DocComment { comment: _, value } => value.visit(context_ptr),
+            InstructionType::DocComment { comment: _, value } => value.visit(context),
             InstructionType::ClassDeclaration { identifier, body } => {
                 let class = Class::new(
                     identifier.clone(),
@@ -368,59 +321,28 @@ impl Instruction {
                         InstructionType::Section { body } => body.clone(),
                         _ => panic!(),
                     },
-                    context_ptr,
+                    context,
                 )?;
-                context_ref.declare_class(class);
-                Ok(Returnable::Evaluate(Data::null(
-                    self.file_data.clone(),
-                    self.start.clone(),
-                    self.end.clone(),
-                )))
+                context.declare_class(class);
+                Ok(Flow::Evaluate(Value::Null))
             }
             InstructionType::ClassInstantiation {
                 identifier,
                 constructor_arguments,
-            } => Ok(Returnable::Evaluate(Context::new_class(
-                context_ptr,
+            } => Ok(Flow::Evaluate(Context::new_class(
+                context,
                 identifier,
                 constructor_arguments
                     .iter()
-                    .map(|x| match x.visit(context_ptr) {
-                        Ok(v) => v.unwrap(),
-                        Err(v) => v.run(),
-                    })
-                    .collect(),
-                (
-                    &self.start.clone(),
-                    &self.end.clone(),
-                    &self.file_data.clone(),
-                ),
+                    .map(|x| Ok(x.visit(context)?.unwrap()))
+                    .collect::<Result<_, Exception>>()?,
+                position,
             )?)),
             InstructionType::InContextOf { context_of, run } => {
-                let context_of = returnable!(context_of.visit(context_ptr)?);
-                let reference = context_of.data_type.original();
-                match reference {
-                    DataType::Class(mut v) => {
-                        let parent = v.context.parent;
-                        v.context.parent = Some(context_ptr);
-                        let return_value = run.visit(&mut v.context);
-                        v.context.parent = parent;
-                        Ok(return_value?)
-                    }
-                    _ => Err(CantRunInContext::call(
-                        &self.start,
-                        &self.end,
-                        &self.file_data,
-                        &reference,
-                    )),
-                }
+                let context_of = returnable!(context_of.visit(context)?);
+                Context::run_in_context_of(context, &context_of, position, |v| run.visit(v))
             }
-            InstructionType::Pass => Ok(Returnable::Evaluate(Data::new(
-                self.file_data.clone(),
-                self.start.clone(),
-                self.end.clone(),
-                DataType::Null,
-            ))),
+            InstructionType::Pass => Ok(Flow::Evaluate(Value::Null)),
         }
     }
 }
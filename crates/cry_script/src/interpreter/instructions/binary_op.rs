@@ -1,9 +1,10 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
 use crate::{
     exceptions::{parser_exceptions::UnexpectedToken, Exception},
     lexer::token::{Token, TokenType},
-    parser::data::{Data, DataType},
+    parser::data::Value,
+    FileData, Position,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
@@ -31,53 +32,58 @@ pub(crate) enum BinaryOperator {
 }
 
 impl BinaryOperator {
+    /// Applies the operator, assignment operators write the result through `base_left`
+    pub(crate) fn evaluate(
+        &self,
+        mut base_left: Value,
+        base_right: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        let original_left = base_left.original().into_owned();
+        let original_right = base_right.original();
+
+        let data = match self {
+            BinaryOperator::AddAssign => original_left.add(&original_right, position)?,
+            BinaryOperator::RemoveAssign => original_left.sub(&original_right, position)?,
+            BinaryOperator::MultiplyAssign => original_left.mul(&original_right, position)?,
+            BinaryOperator::DivideAssign => original_left.div(&original_right, position)?,
+            BinaryOperator::PowerAssign => original_left.pow(&original_right, position)?,
+            _ => return self.operate(&original_left, &original_right, position),
+        };
+        base_left.set_original(data).map_err(|v| v.at(position))?;
+        Ok(base_left)
+    }
+
+    /// Applies a non assigning operator to two [`Value::original`]s
     pub(crate) fn operate(
         &self,
-        n1: &Data,
-        n2: &Data,
-        data1: &Data,
-        data2: &Data,
-    ) -> Result<Data, Exception> {
+        data1: &Value,
+        data2: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
         match self {
-            BinaryOperator::Add => Data::add(n1, n2, data1, data2),
-            BinaryOperator::Remove => Data::sub(n1, n2, data1, data2),
-            BinaryOperator::Multiply => Data::mul(n1, n2, data1, data2),
-            BinaryOperator::Divide => Data::div(n1, n2, data1, data2),
-            BinaryOperator::Power => Data::pow(n1, n2, data1, data2),
+            BinaryOperator::Add => data1.add(data2, position),
+            BinaryOperator::Remove => data1.sub(data2, position),
+            BinaryOperator::Multiply => data1.mul(data2, position),
+            BinaryOperator::Divide => data1.div(data2, position),
+            BinaryOperator::Power => data1.pow(data2, position),
 
-            BinaryOperator::EqualsTo => Ok(data1.eq(n2)?),
-            BinaryOperator::NotEquals => Ok(data1.ne(n2)?),
-            BinaryOperator::GreaterEquals => Ok(data1.geq(n2)?),
-            BinaryOperator::GreaterThan => Ok(data1.gt(n2)?),
-            BinaryOperator::LesserEquals => Ok(data1.leq(n2)?),
-            BinaryOperator::LesserThan => Ok(data1.lt(n2)?),
-            BinaryOperator::And =>
-                Ok(Data::new(
-                    data1.file_data.clone(),
-                    data1.start.clone(),
-                    data2.end.clone(),
-                    if data1.as_bool()? && data2.as_bool()? {
-                        DataType::Integer(1)
-                    } else {
-                        DataType::Integer(0)
-                    },
-                )),
-            BinaryOperator::Or => Ok(Data::new(
-                data1.file_data.clone(),
-                data1.start.clone(),
-                data2.end.clone(),
-                if data1.as_bool()? || data2.as_bool()? {
-                    DataType::Integer(1)
-                } else {
-                    DataType::Integer(0)
-                },
-            )),
+            BinaryOperator::EqualsTo => Ok(data1.eq(data2, position)?),
+            BinaryOperator::NotEquals => Ok(data1.ne(data2, position)?),
+            BinaryOperator::GreaterEquals => Ok(data1.geq(data2, position)?),
+            BinaryOperator::GreaterThan => Ok(data1.gt(data2, position)?),
+            BinaryOperator::LesserEquals => Ok(data1.leq(data2, position)?),
+            BinaryOperator::LesserThan => Ok(data1.lt(data2, position)?),
+            BinaryOperator::And => {
+                Ok((data1.as_bool(position)? && data2.as_bool(position)?).into())
+            }
+            BinaryOperator::Or => Ok((data1.as_bool(position)? || data2.as_bool(position)?).into()),
             _ => panic!(),
         }
     }
 
-    pub(crate) fn from(value: &Token, expected: &[TokenType]) -> Self {
-        match value.token_type {
+    pub(crate) fn from(value: &Token, expected: &[TokenType]) -> Result<Self, Exception> {
+        Ok(match value.token_type {
             TokenType::Plus => BinaryOperator::Add,
             TokenType::Minus => BinaryOperator::Remove,
             TokenType::Multiply => BinaryOperator::Multiply,
@@ -117,13 +123,13 @@ impl BinaryOperator {
                         .as_str(),
                     )
                 }
-                UnexpectedToken::call(
+                return Err(UnexpectedToken::call(
                     value.fetch(),
                     &expected_message,
                     value.token_type.to_string().as_str(),
-                )
+                ));
             }
-        }
+        })
     }
 }
 
@@ -1,9 +1,10 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
 use crate::{
     exceptions::{parser_exceptions::UnexpectedToken, Exception},
     lexer::token::{Token, TokenType},
-    parser::data::{Data, DataType},
+    parser::data::Value,
+    FileData, Position,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
@@ -14,47 +15,33 @@ pub(crate) enum UnaryOperator {
 }
 
 impl UnaryOperator {
-    pub(crate) fn operate(&self, n: &Data, data: &Data) -> Result<Data, Exception> {
+    /// Applies the operator to a [`Value::original`]
+    pub(crate) fn operate(
+        &self,
+        data: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
         Ok(match self {
-            UnaryOperator::Minus => {
-                let rhs = Data::new(
-                    data.file_data.clone(),
-                    data.start.clone(),
-                    data.end.clone(),
-                    DataType::Integer(-1),
-                );
-                Data::mul(n, &rhs, data, &rhs.original())?
-            }
-            UnaryOperator::Plus => {
-                let rhs = Data::new(
-                    data.file_data.clone(),
-                    data.start.clone(),
-                    data.end.clone(),
-                    DataType::Integer(0),
-                );
-                Data::add(n, &rhs, data, rhs.original())?
-            }
-            UnaryOperator::Not => Data::new(
-                data.file_data.clone(),
-                data.start.clone(),
-                data.end.clone(),
-                (!data.as_bool()?).into(),
-            ),
+            UnaryOperator::Minus => data.mul(&Value::Integer(-1), position)?,
+            UnaryOperator::Plus => data.add(&Value::Integer(0), position)?,
+            UnaryOperator::Not => (!data.as_bool(position)?).into(),
         })
     }
 }
 
-impl From<&Token> for UnaryOperator {
-    fn from(value: &Token) -> Self {
+impl TryFrom<&Token> for UnaryOperator {
+    type Error = Exception;
+
+    fn try_from(value: &Token) -> Result<Self, Exception> {
         match value.token_type {
-            TokenType::Plus => UnaryOperator::Plus,
-            TokenType::Minus => UnaryOperator::Minus,
-            TokenType::ExclamationMark => UnaryOperator::Not,
-            _ => UnexpectedToken::call(
+            TokenType::Plus => Ok(UnaryOperator::Plus),
+            TokenType::Minus => Ok(UnaryOperator::Minus),
+            TokenType::ExclamationMark => Ok(UnaryOperator::Not),
+            _ => Err(UnexpectedToken::call(
                 value.fetch(),
                 "\'+\', \'-\', or \'!\'",
                 value.token_type.to_string().as_str(),
-            ),
+            )),
         }
     }
 }
@@ -0,0 +1,205 @@
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    engine::{Backend, Capabilities, Engine, FileSystemAccess, Limits},
+    exceptions::{
+        interpreter_exceptions::{PermissionDenied, StepLimitExceeded, TimeLimitExceeded},
+        lint_exceptions::LintWarning,
+        Exception,
+    },
+    lints::{Lint, LintLevel, Warning},
+    FileData, Position,
+};
+
+/// Reading the clock on every instruction is noticeably slow,
+/// so the deadline is only checked once every this many steps
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+/// State shared by every context of a single run
+pub(crate) struct Runtime {
+    pub(crate) budget: Budget,
+    pub(crate) capabilities: Capabilities,
+    pub(crate) backend: Backend,
+    /// Whether files are run through the optimizer before they run
+    pub(crate) optimize: bool,
+    /// Set through [`Engine::set_args`](crate::engine::Engine::set_args)
+    pub(crate) args: Vec<String>,
+    /// Environment variables the script reads and writes, a copy of the host's
+    pub(crate) env: HashMap<String, String>,
+    /// Levels set through [`Engine::set_lint_level`](crate::engine::Engine::set_lint_level),
+    /// lints that aren't in here warn
+    pub(crate) lint_levels: HashMap<Lint, LintLevel>,
+    /// Warnings of every file linted so far
+    pub(crate) warnings: Vec<Exception>,
+    /// Generators of the script's `Random` instances indexed by the id `std_rand_new`
+    /// gave them, the first one seeds the others
+    pub(crate) generators: Vec<StdRng>,
+    /// Parts of the script's `Split` instances indexed by the id `std_split_new` gave them,
+    /// the string is only split once no matter how often its parts are read
+    pub(crate) splits: Vec<Vec<Rc<String>>>,
+}
+
+impl Runtime {
+    /// The state of a run with the settings of `engine`
+    pub(crate) fn new(engine: &Engine) -> Self {
+        let mut capabilities = engine.capabilities.clone();
+        if let FileSystemAccess::Restricted(root) = &mut capabilities.file_system {
+            if let Ok(v) = root.canonicalize() {
+                *root = v
+            }
+        }
+        Self {
+            budget: Budget::new(&engine.limits),
+            capabilities,
+            backend: engine.backend,
+            optimize: engine.optimize,
+            args: engine.args.clone(),
+            env: engine.script_env(),
+            lint_levels: engine.lint_levels.clone(),
+            warnings: vec![],
+            generators: vec![engine
+                .seed
+                .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)],
+            splits: vec![],
+        }
+    }
+
+    /// Keeps the warnings of a file around, failing with every denied one
+    pub(crate) fn report(&mut self, warnings: Vec<Warning>) -> Result<(), Exception> {
+        let mut denied = vec![];
+        for warning in warnings {
+            match self
+                .lint_levels
+                .get(&warning.lint)
+                .copied()
+                .unwrap_or_default()
+            {
+                LintLevel::Allow => {}
+                LintLevel::Warn => self
+                    .warnings
+                    .push(LintWarning::call(&warning, LintLevel::Warn)),
+                LintLevel::Deny => denied.push(LintWarning::call(&warning, LintLevel::Deny)),
+            }
+        }
+        if denied.is_empty() {
+            Ok(())
+        } else {
+            Err(Exception::from_diagnostics(denied))
+        }
+    }
+
+    /// Maps a path coming from a script to the path the host should open,
+    /// failing if the file system capability doesn't cover it
+    pub(crate) fn resolve_path(
+        &self,
+        path: &str,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<PathBuf, Exception> {
+        match &self.capabilities.file_system {
+            FileSystemAccess::Full => Ok(PathBuf::from(path)),
+            FileSystemAccess::Denied => Err(PermissionDenied::call(
+                start,
+                end,
+                file_data,
+                &format!(
+                    "can't access \"{}\" since the host doesn't allow file system access",
+                    path
+                ),
+            )),
+            FileSystemAccess::Restricted(root) => match canonicalize_lenient(&root.join(path)) {
+                Some(v) if v.starts_with(root) => Ok(v),
+                _ => Err(PermissionDenied::call(
+                    start,
+                    end,
+                    file_data,
+                    &format!(
+                        "can't access \"{}\" since it is outside of {}",
+                        path,
+                        root.display()
+                    ),
+                )),
+            },
+        }
+    }
+}
+
+/// Like [`Path::canonicalize`] but also works for paths that don't exist yet as long as
+/// one of their ancestors does, the missing part may not contain `..`
+fn canonicalize_lenient(path: &Path) -> Option<PathBuf> {
+    if let Ok(v) = path.canonicalize() {
+        return Some(v);
+    }
+    let mut existing = path.to_path_buf();
+    let mut missing = vec![];
+    while !existing.exists() {
+        match existing.components().next_back()? {
+            Component::Normal(v) => missing.push(v.to_os_string()),
+            _ => return None,
+        }
+        existing.pop();
+    }
+    let mut resolved = existing.canonicalize().ok()?;
+    resolved.extend(missing.iter().rev());
+    Some(resolved)
+}
+
+pub(crate) struct Budget {
+    steps: u64,
+    max_steps: Option<u64>,
+    time_limit: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    fn new(limits: &Limits) -> Self {
+        let mut budget = Self {
+            steps: 0,
+            max_steps: limits.max_steps,
+            time_limit: limits.time_limit,
+            deadline: None,
+        };
+        budget.reset();
+        budget
+    }
+
+    /// Starts counting from zero and moves the deadline to `time_limit` from now
+    pub(crate) fn reset(&mut self) {
+        self.steps = 0;
+        self.deadline = self.time_limit.map(|limit| Instant::now() + limit);
+    }
+
+    pub(crate) fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    #[inline(always)]
+    pub(crate) fn step(
+        &mut self,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<(), Exception> {
+        self.steps += 1;
+        if let Some(max_steps) = self.max_steps {
+            if self.steps > max_steps {
+                return Err(StepLimitExceeded::call(start, end, file_data, max_steps));
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if self.steps.is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= deadline {
+                return Err(TimeLimitExceeded::call(
+                    start,
+                    end,
+                    file_data,
+                    &self.time_limit.unwrap(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
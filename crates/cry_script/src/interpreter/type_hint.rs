@@ -1,6 +1,10 @@
 use std::{fmt::Display, rc::Rc};
 
-use crate::{exceptions::parser_exceptions::NotATypeHint, lexer::token::Token, FileData, Position};
+use crate::{
+    exceptions::{parser_exceptions::NotATypeHint, Exception},
+    lexer::token::Token,
+    FileData, Position,
+};
 
 #[derive(Debug, Clone)]
 pub struct Type {
@@ -35,9 +39,11 @@ pub enum TypeHint {
     None,
 }
 
-impl From<&Token> for Type {
-    fn from(tkn: &Token) -> Self {
-        Self::new(
+impl TryFrom<&Token> for Type {
+    type Error = Exception;
+
+    fn try_from(tkn: &Token) -> Result<Self, Exception> {
+        Ok(Self::new(
             match &tkn.token_type {
                 crate::lexer::token::TokenType::TypeHint(v) => match v {
                     crate::lexer::token::TypeHintToken::Integer => TypeHint::Integer,
@@ -45,12 +51,19 @@ impl From<&Token> for Type {
                     crate::lexer::token::TypeHintToken::String => TypeHint::String,
                 },
                 crate::lexer::token::TokenType::Identifier(v) => TypeHint::Class(v.clone()),
-                _ => NotATypeHint::call(&tkn.start, &tkn.end, &tkn.file_data, &tkn.token_type),
+                _ => {
+                    return Err(NotATypeHint::call(
+                        &tkn.start,
+                        &tkn.end,
+                        &tkn.file_data,
+                        &tkn.token_type,
+                    ))
+                }
             },
             tkn.start.clone(),
             tkn.end.clone(),
             tkn.file_data.clone(),
-        )
+        ))
     }
 }
 
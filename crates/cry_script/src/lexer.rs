@@ -5,14 +5,17 @@ use std::rc::Rc;
 use utils::CharUtils;
 
 use crate::{
-    exceptions::lexer_exceptions::{
-        InvalidAmountOfDots, UnknownToken, UnmatchedDedentToken, UnterminatedIndentation,
-        UnterminatedString,
+    exceptions::{
+        lexer_exceptions::{
+            InvalidAmountOfDots, InvalidInterpolation, InvalidUnicodeEscape, UnknownToken,
+            UnmatchedDedentToken, UnterminatedIndentation, UnterminatedString,
+        },
+        Exception,
     },
     Annotation, Keyword, Position,
 };
 
-use self::token::{Token, TokenType, TypeHintToken};
+use self::token::{StringPart, Token, TokenType, TypeHintToken};
 
 use super::FileData;
 
@@ -29,7 +32,21 @@ pub struct Lexer {
 // #
 // #############################
 impl Lexer {
-    pub(crate) fn lex(file_data: Rc<FileData>) -> Vec<Token> {
+    pub(crate) fn lex(file_data: Rc<FileData>) -> Result<Vec<Token>, Exception> {
+        let (tokens, lexer) = Lexer::tokens(file_data.clone())?;
+        if lexer.indent_level != 0 {
+            let pos = Position::new(lexer.current_index + 1);
+            return Err(UnterminatedIndentation::call(&pos, &pos, &file_data));
+        }
+        Ok(tokens)
+    }
+
+    /// How many curly brackets are still open at the end of the file
+    pub(crate) fn open_indentation(file_data: Rc<FileData>) -> Result<usize, Exception> {
+        Ok(Lexer::tokens(file_data)?.1.indent_level)
+    }
+
+    fn tokens(file_data: Rc<FileData>) -> Result<(Vec<Token>, Lexer), Exception> {
         let characters: Vec<char> = file_data.data.chars().collect();
 
         let mut lexer = Self {
@@ -44,7 +61,7 @@ impl Lexer {
         while let Some(chr) = lexer.current_char() {
             match comment_type {
                 Comment::None => {
-                    let token = lexer.token(&mut comment_type);
+                    let token = lexer.token(&mut comment_type)?;
                     if let Some(token) = token {
                         tokens.push(token)
                     }
@@ -75,16 +92,15 @@ impl Lexer {
             TokenType::EndOfFile,
             file_data.clone(),
         ));
-        if lexer.indent_level != 0 {
-            let pos = Position::new(lexer.current_index + 1);
-            UnterminatedIndentation::call(&pos, &pos, &file_data)
-        }
-        tokens
+        Ok((tokens, lexer))
     }
 
-    fn token(&mut self, comment_handle: &mut Comment) -> Option<Token> {
+    fn token(&mut self, comment_handle: &mut Comment) -> Result<Option<Token>, Exception> {
         let start = self.current_index;
-        let current_char = self.current_char()?;
+        let current_char = match self.current_char() {
+            Some(v) => v,
+            None => return Ok(None),
+        };
         let token_kind = match current_char {
             '/' => match self.peak() {
                 Some(chr) => match chr {
@@ -136,9 +152,14 @@ impl Lexer {
             // Multi Character tokens
             //
             // -------------------------
-            '"' => self.generate_string(),
-            '0'..='9' => self.generate_number(),
-            'a'..='z' | 'A'..='Z' => self.generate_keyword(),
+            '"' => self.generate_string(false, false)?,
+            'f' | 'r' if self.peak() == Some(&'"') => {
+                let interpolated = current_char == &'f';
+                self.advance();
+                self.generate_string(interpolated, !interpolated)?
+            }
+            '0'..='9' => self.generate_number()?,
+            chr if chr.is_identifier_start() => self.generate_keyword(),
 
             // -------------------------
             //
@@ -175,14 +196,13 @@ impl Lexer {
             }
             '}' => {
                 if self.indent_level == 0 {
-                    UnmatchedDedentToken::call(
+                    return Err(UnmatchedDedentToken::call(
                         &Position::new(start),
                         &Position::new((self.current_index as i32).max(0) as usize),
                         &self.file_data,
-                    )
-                } else {
-                    self.indent_level -= 1;
+                    ));
                 }
+                self.indent_level -= 1;
                 TokenType::Dedent
             }
             '(' => TokenType::LeftParenthesis,
@@ -196,7 +216,7 @@ impl Lexer {
                         &Position::new(self.current_index),
                         &self.file_data,
                     ),
-                )
+                )?
             }),
             '\n' => TokenType::NewLine,
 
@@ -205,20 +225,20 @@ impl Lexer {
             // Ignored Character tokens
             //
             // -------------------------
-            '\r' | ' ' | '\t' => return None,
+            '\r' | ' ' | '\t' => return Ok(None),
 
             _ => {
                 let pos = Position::new((self.current_index as i32).max(0) as usize);
-                UnknownToken::call(&pos, &pos, &self.file_data)
+                return Err(UnknownToken::call(&pos, &pos, &self.file_data));
             }
         };
 
-        Some(Token::new(
+        Ok(Some(Token::new(
             Position::new(start),
             Position::new(self.current_index),
             token_kind,
             self.file_data.clone(),
-        ))
+        )))
     }
 }
 
@@ -289,7 +309,7 @@ impl Lexer {
 // #
 // #############################
 impl Lexer {
-    fn generate_number(&mut self) -> TokenType {
+    fn generate_number(&mut self) -> Result<TokenType, Exception> {
         let start = self.current_index;
         let mut number_string = String::new();
         let mut dot_count = 0;
@@ -307,26 +327,53 @@ impl Lexer {
         }
         self.retreat();
         match dot_count {
-            0 => TokenType::Integer(number_string.parse().unwrap()),
-            1 => TokenType::Float(number_string.parse().unwrap()),
-            _ => InvalidAmountOfDots::call(
+            0 => Ok(TokenType::Integer(number_string.parse().unwrap())),
+            1 => Ok(TokenType::Float(number_string.parse().unwrap())),
+            _ => Err(InvalidAmountOfDots::call(
                 &Position::new(start),
                 &Position::new(self.current_index - 1),
                 &self.file_data,
-            ),
+            )),
         }
     }
 
-    fn generate_string(&mut self) -> TokenType {
+    /// Lexes a string literal, `interpolated` strings also take `{expression}`s
+    /// and `{{` or `}}` for literal curly brackets, `raw` strings keep backslashes as they are
+    ///
+    /// Strings opened with `"""` keep their line breaks and tabs. If the quotes are followed by
+    /// a line break the string is a block: the lines lose the indentation they share, and the
+    /// line breaks after the opening and before the closing quotes aren't part of it.
+    fn generate_string(&mut self, interpolated: bool, raw: bool) -> Result<TokenType, Exception> {
         let start = self.current_index;
         let mut string = String::new();
+        let mut parts = vec![];
+        let multi_line = self.is_triple_quote();
+        let mut block = None;
+        if multi_line {
+            self.advance();
+            self.advance();
+            if self.peak() == Some(&'\n') {
+                self.advance();
+                block = Some(self.block_indentation(raw));
+            }
+        }
+        // How much indentation is left to strip from the current line of a block
+        let mut indentation = block.map_or(0, |v| v.0);
         self.advance();
         while let Some(chr) = self.current_char() {
             let chr = *chr;
-            if "\n\r\t\0".contains(chr) {
+            if chr == '"' && (!multi_line || self.is_triple_quote()) {
+                break;
+            } else if indentation > 0 && (chr == ' ' || chr == '\t') {
+                indentation -= 1;
+                self.advance();
+                continue;
+            } else if chr == '\n' && multi_line {
+                indentation = block.map_or(0, |v| v.0);
+            } else if chr == '\r' || chr == '\0' || !(multi_line || raw) && "\n\t".contains(chr) {
                 self.advance();
                 continue;
-            } else if chr == '\\' && self.peak().is_some() {
+            } else if chr == '\\' && !raw && self.peak().is_some() {
                 string.push(match self.peak().unwrap() {
                     'n' => '\n',
                     'r' => '\r',
@@ -334,6 +381,7 @@ impl Lexer {
                     '\\' => '\\',
                     '0' => '\0',
                     '"' => '\"',
+                    'u' => self.unicode_escape()?,
                     _ => {
                         self.advance();
                         continue;
@@ -341,27 +389,186 @@ impl Lexer {
                 });
                 self.advance();
                 self.advance();
+                indentation = 0;
                 continue;
-            } else if chr == '"' {
-                break;
+            } else if interpolated && (chr == '{' || chr == '}') {
+                if self.peak() == Some(&chr) {
+                    self.advance();
+                } else if chr == '}' {
+                    let pos = Position::new(self.current_index);
+                    return Err(InvalidInterpolation::call(
+                        &pos,
+                        &pos,
+                        &self.file_data,
+                        "a single } has to be written as }}",
+                    ));
+                } else {
+                    parts.push(StringPart::Literal(std::mem::take(&mut string)));
+                    parts.push(StringPart::Expression(self.interpolation(start)?));
+                    self.advance();
+                    indentation = 0;
+                    continue;
+                }
+            } else {
+                indentation = 0;
             }
             string.push(chr);
             self.advance();
         }
         if self.current_char() != Some(&'"') {
-            UnterminatedString::call(
+            return Err(UnterminatedString::call(
+                &Position::new(start),
+                &Position::new(self.current_index),
+                &self.file_data,
+            ));
+        }
+        if multi_line {
+            self.advance();
+            self.advance();
+        }
+        if matches!(block, Some((_, true))) {
+            string.pop();
+        }
+        if parts.is_empty() {
+            return Ok(TokenType::String(string));
+        }
+        parts.push(StringPart::Literal(string));
+        Ok(TokenType::InterpolatedString(parts))
+    }
+
+    #[inline(always)]
+    fn is_triple_quote(&self) -> bool {
+        self.current_char() == Some(&'"')
+            && self.peak() == Some(&'"')
+            && self.peak_by(2) == Some(&'"')
+    }
+
+    /// The indentation the lines of the block string the lexer is in share, and whether its
+    /// closing quotes are on a line of their own, the lexer has to be on the line break
+    /// following the opening quotes
+    fn block_indentation(&self, raw: bool) -> (usize, bool) {
+        let mut indentation = usize::MAX;
+        let mut line_indentation = Some(0);
+        let mut index = self.current_index + 1;
+        while let Some(&chr) = self.characters.get(index).filter(|v| **v != '\0') {
+            match chr {
+                '"' if self.characters[index..].starts_with(&['"'; 3]) => {
+                    return match line_indentation {
+                        Some(v) => (indentation.min(v), true),
+                        None => (indentation, false),
+                    };
+                }
+                '\n' => line_indentation = Some(0),
+                ' ' | '\t' => {
+                    line_indentation = line_indentation.map(|v| v + 1);
+                }
+                '\r' => {}
+                _ => {
+                    if let Some(v) = line_indentation.take() {
+                        indentation = indentation.min(v)
+                    }
+                    if chr == '\\' && !raw {
+                        index += 1;
+                    }
+                }
+            }
+            index += 1;
+        }
+        (0, false)
+    }
+
+    /// The tokens of the `{expression}` the lexer is on, leaves the lexer on its closing bracket
+    fn interpolation(&mut self, string_start: usize) -> Result<Vec<Token>, Exception> {
+        let start = self.current_index;
+        let indent_level = self.indent_level;
+        let mut tokens = vec![];
+        let mut comment_type = Comment::None;
+        self.advance();
+        loop {
+            match self.current_char() {
+                Some('}') if self.indent_level == indent_level => break,
+                // The expression has to fit on one line, new lines would end the statement
+                None | Some('\n') => {
+                    return Err(UnterminatedString::call(
+                        &Position::new(string_start),
+                        &Position::new(self.current_index),
+                        &self.file_data,
+                    ))
+                }
+                Some(_) => {
+                    if let Some(token) = self.token(&mut comment_type)? {
+                        tokens.push(token)
+                    }
+                    if !matches!(comment_type, Comment::None) {
+                        let pos = Position::new(self.current_index);
+                        return Err(InvalidInterpolation::call(
+                            &pos,
+                            &pos,
+                            &self.file_data,
+                            "comments can't be used inside an interpolated string",
+                        ));
+                    }
+                    self.advance()
+                }
+            }
+        }
+        if tokens.is_empty() {
+            return Err(InvalidInterpolation::call(
+                &Position::new(start),
+                &Position::new(self.current_index),
+                &self.file_data,
+                "expected an expression between the curly brackets",
+            ));
+        }
+        let end = Position::new(self.current_index);
+        tokens.push(Token::new(
+            end.clone(),
+            end,
+            TokenType::EndOfFile,
+            self.file_data.clone(),
+        ));
+        Ok(tokens)
+    }
+
+    /// The character a `\u{...}` escape stands for, the string lexer steps over what remains of it
+    fn unicode_escape(&mut self) -> Result<char, Exception> {
+        let start = self.current_index;
+        self.advance();
+        let mut digits = String::new();
+        let terminated = if self.peak() == Some(&'{') {
+            self.advance();
+            while let Some(chr) = self.peak().filter(|v| v.is_ascii_hexdigit()) {
+                digits.push(*chr);
+                self.advance();
+            }
+            self.peak() == Some(&'}')
+        } else {
+            false
+        };
+        if terminated {
+            self.advance();
+        }
+        match u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(chr) if terminated && digits.len() <= 6 => {
+                // The caller steps over the escape's last two characters
+                self.retreat();
+                Ok(chr)
+            }
+            _ => Err(InvalidUnicodeEscape::call(
                 &Position::new(start),
                 &Position::new(self.current_index),
                 &self.file_data,
-            )
+            )),
         }
-        TokenType::String(string)
     }
 
     fn generate_keyword(&mut self) -> TokenType {
         let mut string = String::new();
         while let Some(chr) = self.characters.get(self.current_index) {
-            if !chr.is_alphabetic() && !chr.is_number() && chr != &'_' {
+            if !chr.is_identifier_continue() {
                 break;
             }
             string.push(*chr);
@@ -2,7 +2,7 @@ use std::{fmt::Display, rc::Rc};
 
 use crate::{Annotation, FileData, Keyword, Position};
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub(crate) struct Token {
     pub(crate) start: Position,
     pub(crate) end: Position,
@@ -61,6 +61,8 @@ pub(crate) enum TokenType {
     Integer(i32),
     Float(f32),
     String(String),
+    /// A `f"..."` string that has at least one `{expression}`
+    InterpolatedString(Vec<StringPart>),
     Bool(bool),
     Identifier(String),
     Keyword(Keyword),
@@ -91,6 +93,13 @@ pub(crate) enum TokenType {
     Null,
 }
 
+#[derive(PartialEq, Clone)]
+pub(crate) enum StringPart {
+    Literal(String),
+    /// The tokens between the curly brackets, ending in [`TokenType::EndOfFile`]
+    Expression(Vec<Token>),
+}
+
 impl TokenType {
     pub fn token_type(&self) -> String {
         match self {
@@ -119,6 +128,7 @@ impl TokenType {
             TokenType::Integer(_) => "integer",
             TokenType::Float(_) => "float",
             TokenType::String(_) => "string",
+            TokenType::InterpolatedString(_) => "interpolated_string",
             TokenType::Bool(_) => "bool",
             TokenType::Identifier(_) => "identifier",
             TokenType::Keyword(_) => "keyword",
@@ -182,6 +192,19 @@ impl Display for TokenType {
                 TokenType::Integer(value) => value.to_string(),
                 TokenType::Float(value) => value.to_string(),
                 TokenType::String(value) => value.to_string(),
+                TokenType::InterpolatedString(parts) => parts
+                    .iter()
+                    .map(|v| match v {
+                        StringPart::Literal(v) => v.clone(),
+                        StringPart::Expression(tokens) => format!(
+                            "{{{}}}",
+                            tokens
+                                .iter()
+                                .map(|v| v.token_type.to_string())
+                                .collect::<String>()
+                        ),
+                    })
+                    .collect(),
                 TokenType::Identifier(value) => value.to_string(),
                 TokenType::Keyword(value) => format!("{}", value),
                 TokenType::Bool(value) => value.to_string(),
@@ -1,61 +1,48 @@
 #![allow(dead_code)]
 
+pub mod engine;
 pub mod exceptions;
 pub mod interpreter;
 pub mod lexer;
+pub mod lints;
+pub mod optimizer;
 pub mod parser;
+pub mod resolver;
 pub mod variables;
+pub mod vm;
 
-use std::{fmt::Display, fs::File, io::Read, rc::Rc, time::Instant};
+use std::{fmt::Display, fs, rc::Rc, time::Instant};
 
 use colored::Colorize;
-use exceptions::{lexer_exceptions::InvalidAnnotation, Exception};
+use engine::{Backend, Engine};
+use exceptions::{
+    interpreter_exceptions::InvalidFilePath, lexer_exceptions::InvalidAnnotation, Exception,
+};
 use include_dir::{include_dir, Dir};
 use interpreter::{
     context::Context,
+    function::Function,
     instructions::{Instruction, InstructionType},
 };
-use parser::{data::Data, Parser};
-use variables::Variables;
+use lints::{declared_names, Linter};
+use optimizer::Optimizer;
+use parser::{data::Value, Parser};
+use resolver::{Resolver, RootScope};
+use utils::SourceMap;
+use vm::compiler::Compiler;
 
 use self::lexer::Lexer;
 
 const STD_DIR: Dir = include_dir!("std_lib/");
-const STD_FILES: [&str; 3] = ["std_rand", "std_math", "std_file"];
+const STD_FILES: [&str; 5] = [
+    "std_rand",
+    "std_math",
+    "std_file",
+    "std_process",
+    "std_string",
+];
 
 pub fn run(root_file_path: &str) -> u128 {
-    let mut variables = Variables::new();
-
-    let mut file_data = String::new();
-    match File::open(root_file_path) {
-        Ok(v) => v,
-        Err(_) => Exception::new("Err: Unable to find startup file".red().bold().to_string()).run(),
-    }
-    .read_to_string(&mut file_data)
-    .unwrap();
-    // file_data.push('\0');
-    let mut context = Context::new_root(
-        Rc::new(FileData {
-            data: file_data,
-            path: root_file_path.to_string(),
-        }),
-        &mut variables,
-    );
-    match Context::import_data(
-        &mut context,
-        Rc::new(FileData::new(
-            STD_DIR
-                .get_file("std.cry")
-                .unwrap()
-                .contents_utf8()
-                .unwrap()
-                .to_string(),
-            "std".to_string(),
-        )),
-    ) {
-        Ok(_) => {}
-        Err(v) => v.run(),
-    };
     println!(
         "{} {}{}",
         "Running".bright_green(),
@@ -63,56 +50,88 @@ pub fn run(root_file_path: &str) -> u128 {
         "...".bright_green()
     );
     let time = Instant::now();
-    match run_from_file(root_file_path, &mut context) {
+    match Engine::new().run_file(root_file_path) {
         Ok(_) => {}
         Err(v) => v.run(),
     };
     time.elapsed().as_nanos()
-    // println!("total time {}", x);
-    // x
 }
 
-pub fn run_from_file(file_path: &str, context: *mut Context) -> Result<Returnable, Exception> {
-    // let file_open_time = Instant::now();
-    let mut file_data = String::new();
-    File::open(file_path)
-        .unwrap()
-        .read_to_string(&mut file_data)
-        .unwrap();
-    file_data.push('\0');
-    // println!("file open time {}", file_open_time.elapsed().as_nanos());
+pub(crate) fn run_from_file(
+    file_path: &str,
+    context: &Context,
+    root_scope: RootScope,
+    (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+) -> Result<Flow, Exception> {
+    let source = fs::read_to_string(file_path)
+        .map_err(|_| InvalidFilePath::call(start, end, file_data, &file_path.to_string()))?;
     run_with_data(
-        Rc::new(FileData::new(
-            file_data.replace('\r', ""),
-            file_path.to_string(),
-        )),
+        Rc::new(FileData::from_source(&source, file_path.to_string())),
         context,
+        root_scope,
     )
 }
 
 pub(crate) fn run_with_data(
     file_data: Rc<FileData>,
-    context: *mut Context,
-) -> Result<Returnable, Exception> {
-    let tokens = Lexer::lex(file_data.clone());
-    let instructions = Parser::parse(file_data.clone(), tokens);
-    run_with_instructions(&instructions, context, file_data)
+    context: &Context,
+    root_scope: RootScope,
+) -> Result<Flow, Exception> {
+    let tokens = Lexer::lex(file_data.clone())?;
+    let mut instructions = Parser::parse(file_data.clone(), tokens)?;
+    lint(&instructions, &file_data, context, root_scope)?;
+    let (backend, optimize) = {
+        let runtime = context.runtime.borrow();
+        (runtime.backend, runtime.optimize)
+    };
+    if optimize {
+        Optimizer::optimize(&mut instructions);
+    }
+    Resolver::resolve(&mut instructions, root_scope);
+    match backend {
+        Backend::TreeWalker => run_with_instructions(&instructions, context),
+        Backend::Bytecode => vm::run(&Compiler::compile_file(&instructions, file_data), context),
+    }
+}
+
+/// Runs the linter over a file that isn't part of the standard library
+pub(crate) fn lint(
+    instructions: &[Instruction],
+    file_data: &Rc<FileData>,
+    context: &Context,
+    root_scope: RootScope,
+) -> Result<(), Exception> {
+    if file_data.path == "std" || STD_FILES.contains(&file_data.path.as_str()) {
+        return Ok(());
+    }
+    let exports = |file_path: &str| {
+        let source = if STD_FILES.contains(&file_path) {
+            STD_DIR
+                .get_file(format!("{}.cry", file_path))?
+                .contents_utf8()?
+                .to_string()
+        } else {
+            let position = Position::new(0);
+            let path = context
+                .runtime
+                .borrow()
+                .resolve_path(file_path, (&position, &position, file_data))
+                .ok()?;
+            fs::read_to_string(path).ok()?
+        };
+        let file_data = Rc::new(FileData::from_source(&source, file_path.to_string()));
+        let tokens = Lexer::lex(file_data.clone()).ok()?;
+        Some(declared_names(&Parser::parse(file_data, tokens).ok()?))
+    };
+    let warnings = Linter::lint(instructions, root_scope, &exports);
+    context.runtime.borrow_mut().report(warnings)
 }
 
 pub(crate) fn run_with_instructions(
     instructions: &Vec<Instruction>,
-    context: *mut Context,
-    file_data: Rc<FileData>,
-) -> Result<Returnable, Exception> {
+    context: &Context,
+) -> Result<Flow, Exception> {
     // let run_time = Instant::now();
-    let start = match instructions.last() {
-        Some(v) => v.start.clone(),
-        None => Position::new(0),
-    };
-    let end = match instructions.last() {
-        Some(v) => v.end.clone(),
-        None => Position::new(0),
-    };
 
     for instruction in instructions {
         if matches!(
@@ -141,7 +160,7 @@ pub(crate) fn run_with_instructions(
         }
     }
 
-    let mut return_value = Data::new(file_data, start, end, parser::data::DataType::Null);
+    let mut return_value = Value::Null;
     for instruction in instructions {
         if matches!(
             instruction.instruction_type,
@@ -149,40 +168,41 @@ pub(crate) fn run_with_instructions(
         ) {
             continue;
         }
-        let value = match match instruction.visit(context) {
-            Ok(v) => v,
-            Err(e) => {
-                println!(
-                    "{}",
-                    instructions
-                        .iter()
-                        .map(|z| format!("{} ", z.instruction_type))
-                        .collect::<String>()
-                );
-                e.run()
-            }
-        } {
-            Returnable::Return(val) => return Ok(Returnable::Return(val)),
-            Returnable::Evaluate(val) => val,
-            Returnable::Break(val) => val,
+        let value = match instruction.visit(context)? {
+            Flow::Return(val) => return Ok(Flow::Return(val)),
+            v @ Flow::TailCall(..) => return Ok(v),
+            Flow::Evaluate(val) => val,
+            Flow::Break(val) => val,
         };
         return_value = value
     }
-    Ok(Returnable::Evaluate(return_value))
+    Ok(Flow::Evaluate(return_value))
 }
 #[derive(Debug, PartialEq, Eq, PartialOrd)]
 struct FileData {
     data: String,
     path: String,
+    source_map: SourceMap,
 }
 
 impl FileData {
     pub fn new(data: String, path: String) -> Self {
-        Self { data, path }
+        Self {
+            source_map: SourceMap::new(&data),
+            data,
+            path,
+        }
+    }
+
+    /// Drops carriage returns and adds the terminator the lexer expects at the end of a file
+    pub(crate) fn from_source(source: &str, path: String) -> Self {
+        let mut data = source.replace('\r', "");
+        data.push('\0');
+        Self::new(data, path)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 struct Position {
     value: usize,
 }
@@ -248,19 +268,21 @@ impl Display for Keyword {
     }
 }
 
+/// How a run finished, handed to the host
 #[derive(Debug)]
 pub enum Returnable {
-    Return(Data),
-    Evaluate(Data),
-    Break(Data),
+    Return(Value),
+    Evaluate(Value),
+    Break(Value),
 }
 
 impl Returnable {
-    pub(crate) fn unwrap(self) -> Data {
+    /// Resolves references so the value stays valid once the run's variables are cleared
+    pub(crate) fn into_original(self) -> Self {
         match self {
-            Returnable::Return(v) => v,
-            Returnable::Evaluate(v) => v,
-            Returnable::Break(v) => v,
+            Returnable::Return(v) => Returnable::Return(v.original().into_owned()),
+            Returnable::Evaluate(v) => Returnable::Evaluate(v.original().into_owned()),
+            Returnable::Break(v) => Returnable::Break(v.original().into_owned()),
         }
     }
 }
@@ -276,22 +298,59 @@ impl Display for Returnable {
                 Returnable::Break(_) => "break",
             },
             match self {
-                Returnable::Return(v) => v.data_type.to_string(),
-                Returnable::Evaluate(v) => v.data_type.to_string(),
-                Returnable::Break(v) => v.data_type.to_string(),
+                Returnable::Return(v) => v.to_string(),
+                Returnable::Evaluate(v) => v.to_string(),
+                Returnable::Break(v) => v.to_string(),
             }
         )
     }
 }
 
+/// How an instruction finished, [`Returnable`] plus the tail calls that only travel
+/// from `Instruction::visit` up to the `Function::call` that runs them
+#[derive(Debug)]
+pub(crate) enum Flow {
+    Return(Value),
+    Evaluate(Value),
+    Break(Value),
+    /// A function returning the result of calling itself, run by the caller's
+    /// `Function::call` loop instead of growing the stack
+    TailCall(Context, Rc<Function>, Vec<Value>),
+}
+
+impl Flow {
+    pub(crate) fn unwrap(self) -> Value {
+        match self {
+            Flow::Return(v) => v,
+            Flow::Evaluate(v) => v,
+            Flow::Break(v) => v,
+            Flow::TailCall(..) => unreachable!("tail calls are run by Function::call"),
+        }
+    }
+
+    /// What the host sees, tail calls never reach it since a `return` outside of a
+    /// function is an error
+    pub(crate) fn into_returnable(self) -> Returnable {
+        match self {
+            Flow::Return(v) => Returnable::Return(v),
+            Flow::Evaluate(v) => Returnable::Evaluate(v),
+            Flow::Break(v) => Returnable::Break(v),
+            Flow::TailCall(..) => unreachable!("tail calls are run by Function::call"),
+        }
+    }
+}
+
 #[derive(PartialEq, Clone)]
 pub(crate) enum Annotation {
     DocComment(String),
 }
 
 impl Annotation {
-    fn from(_v: String, (start, end, file_data): (&Position, &Position, &Rc<FileData>)) -> Self {
-        InvalidAnnotation::call(start, end, file_data)
+    fn from(
+        _v: String,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Self, Exception> {
+        Err(InvalidAnnotation::call(start, end, file_data))
     }
 }
 
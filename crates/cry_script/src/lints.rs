@@ -0,0 +1,488 @@
+//! Finds code that runs fine but probably doesn't do what it was meant to.
+//!
+//! Scoping is dynamic, any function a file calls can read its variables by name, so a
+//! variable only counts as unused when nothing in the whole file mentions its name.
+
+use std::{collections::HashSet, fmt::Display, rc::Rc};
+
+use crate::{
+    interpreter::{
+        instructions::{Instruction, InstructionType},
+        type_hint::{Type, TypeHint},
+    },
+    resolver::RootScope,
+    FileData, Position,
+};
+
+/// Something the linter checks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// A variable whose name nothing reads
+    UnusedVariables,
+    /// A `use` of a file none of whose declarations are used
+    UnusedImports,
+    /// A variable declared again while the earlier declaration is still visible
+    Shadowing,
+    /// Statements following a `return` or `break` in the same block
+    UnreachableCode,
+    /// A class declaring two methods with the same name, only the last one can be called
+    DuplicateMethods,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedVariables,
+        Lint::UnusedImports,
+        Lint::Shadowing,
+        Lint::UnreachableCode,
+        Lint::DuplicateMethods,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::UnusedImports => "unused_imports",
+            Lint::Shadowing => "shadowing",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::DuplicateMethods => "duplicate_methods",
+        }
+    }
+
+    /// Stable code reported with the lint's diagnostics
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "W0001",
+            Lint::UnusedImports => "W0002",
+            Lint::Shadowing => "W0003",
+            Lint::UnreachableCode => "W0004",
+            Lint::DuplicateMethods => "W0005",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|v| v.name() == name)
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// What happens when a lint finds something
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LintLevel {
+    /// Nothing is reported
+    Allow,
+    /// A warning is reported and the script still runs
+    #[default]
+    Warn,
+    /// An error is reported and the file doesn't run
+    Deny,
+}
+
+impl Display for LintLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warn"),
+            LintLevel::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+pub(crate) struct Warning {
+    pub(crate) lint: Lint,
+    pub(crate) start: Position,
+    pub(crate) end: Position,
+    pub(crate) file_data: Rc<FileData>,
+    pub(crate) message: String,
+    pub(crate) note: String,
+    /// Another declaration the warning is about, with a message describing it
+    pub(crate) related: Option<(Position, Position, String)>,
+}
+
+pub(crate) struct Linter<'a> {
+    warnings: Vec<Warning>,
+    /// Every name the file reads, calls, instantiates or uses as a type
+    used: HashSet<String>,
+    /// Variables declared in the function being linted and where, innermost scope last
+    scopes: Vec<Vec<(String, Position, Position)>>,
+    /// Names a file declares at its top level, `None` if it can't be read
+    exports: &'a dyn Fn(&str) -> Option<Vec<String>>,
+    /// Whether the scope being linted is the top level of a file other files declare into,
+    /// whatever is declared there may be used somewhere else
+    shared_top_level: bool,
+}
+
+impl<'a> Linter<'a> {
+    pub(crate) fn lint(
+        instructions: &[Instruction],
+        root_scope: RootScope,
+        exports: &'a dyn Fn(&str) -> Option<Vec<String>>,
+    ) -> Vec<Warning> {
+        let mut linter = Linter {
+            warnings: vec![],
+            used: HashSet::new(),
+            scopes: vec![vec![]],
+            exports,
+            shared_top_level: root_scope == RootScope::Shared,
+        };
+        for instruction in instructions {
+            linter.collect_used(instruction)
+        }
+        linter.block(instructions);
+        linter.warnings.sort_by_key(|v| v.start.value);
+        linter.warnings
+    }
+
+    fn warn(&mut self, lint: Lint, instruction: &Instruction, message: String, note: &str) {
+        self.warn_between(lint, instruction, instruction, message, note)
+    }
+
+    fn warn_between(
+        &mut self,
+        lint: Lint,
+        first: &Instruction,
+        last: &Instruction,
+        message: String,
+        note: &str,
+    ) {
+        self.warnings.push(Warning {
+            lint,
+            start: first.start.clone(),
+            end: last.end.clone(),
+            file_data: first.file_data.clone(),
+            message,
+            note: note.to_string(),
+            related: None,
+        })
+    }
+
+    /// Points the last warning to the declaration it's about
+    fn related(&mut self, (start, end): (&Position, &Position), message: &str) {
+        if let Some(v) = self.warnings.last_mut() {
+            v.related = Some((start.clone(), end.clone(), message.to_string()))
+        }
+    }
+
+    /// Lints `f` with none of the current scopes visible, like a function body
+    fn frame(&mut self, root: Vec<(String, Position, Position)>, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.scopes, vec![root]);
+        let shared_top_level = std::mem::replace(&mut self.shared_top_level, false);
+        f(self);
+        self.scopes = outer;
+        self.shared_top_level = shared_top_level;
+    }
+
+    fn block(&mut self, instructions: &[Instruction]) {
+        if let Some(end) = instructions.iter().position(|v| {
+            matches!(
+                v.instruction_type,
+                InstructionType::ReturnStatement { .. } | InstructionType::BreakStatement { .. }
+            )
+        }) {
+            // Declarations are hoisted so they still run after a return
+            let unreachable: Vec<_> = instructions[end + 1..]
+                .iter()
+                .filter(|v| !is_hoisted(v))
+                .collect();
+            if let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) {
+                self.warn_between(
+                    Lint::UnreachableCode,
+                    first,
+                    last,
+                    "unreachable code".to_string(),
+                    "everything after a return or break in the same block never runs",
+                )
+            }
+        }
+        for instruction in instructions {
+            self.instruction(instruction)
+        }
+    }
+
+    fn instruction(&mut self, instruction: &Instruction) {
+        match &instruction.instruction_type {
+            InstructionType::VarAssign {
+                identifier, data, ..
+            } => {
+                self.instruction(data);
+                self.declare(identifier, instruction)
+            }
+            InstructionType::UseStatement { file_path } => {
+                if let Some(names) = (self.exports)(file_path) {
+                    if !names.is_empty() && !names.iter().any(|v| self.used.contains(v)) {
+                        self.warn(
+                            Lint::UnusedImports,
+                            instruction,
+                            format!("unused import \"{}\"", file_path),
+                            "nothing the file declares is used, consider removing the use statement",
+                        )
+                    }
+                }
+            }
+            InstructionType::Section { body } => {
+                self.scopes.push(vec![]);
+                self.block(body);
+                self.scopes.pop();
+            }
+            InstructionType::FunctionDeclaration {
+                body, arguments, ..
+            } => {
+                for (_, _, default_value) in arguments {
+                    if let Some(v) = default_value {
+                        self.instruction(v)
+                    }
+                }
+                let arguments = arguments
+                    .iter()
+                    .map(|(v, _, _)| {
+                        (
+                            v.clone(),
+                            instruction.start.clone(),
+                            instruction.end.clone(),
+                        )
+                    })
+                    .collect();
+                self.frame(arguments, |slf| slf.instruction(body))
+            }
+            InstructionType::ClassDeclaration { identifier, body } => {
+                if let InstructionType::Section { body } = &body.instruction_type {
+                    self.class_body(identifier, body)
+                }
+            }
+            InstructionType::InContextOf { context_of, run } => {
+                self.instruction(context_of);
+                self.frame(vec![], |slf| slf.instruction(run))
+            }
+            InstructionType::BinaryOperation { left, right, .. } => {
+                self.instruction(left);
+                self.instruction(right)
+            }
+            InstructionType::IfStatement {
+                condition,
+                body,
+                else_value,
+            } => {
+                if let Some(v) = condition {
+                    self.instruction(v)
+                }
+                self.instruction(body);
+                if let Some(v) = else_value {
+                    self.instruction(v)
+                }
+            }
+            InstructionType::WhileStatement { condition, body } => {
+                self.instruction(condition);
+                self.instruction(body)
+            }
+            InstructionType::DoCatch {
+                do_body,
+                catch_body,
+            } => {
+                self.instruction(do_body);
+                self.instruction(catch_body)
+            }
+            InstructionType::FunctionCall { arguments, .. }
+            | InstructionType::ClassInstantiation {
+                constructor_arguments: arguments,
+                ..
+            } => {
+                for argument in arguments {
+                    self.instruction(argument)
+                }
+            }
+            InstructionType::VarUpdate { data: value, .. }
+            | InstructionType::UnaryOperation { value, .. }
+            | InstructionType::ReturnStatement { value, .. }
+            | InstructionType::BreakStatement { value }
+            | InstructionType::As { value, .. }
+            | InstructionType::DocComment { value, .. } => self.instruction(value),
+            InstructionType::VarAccess { .. }
+            | InstructionType::Data(_)
+            | InstructionType::Pass => {}
+        }
+    }
+
+    /// Members are reached from outside the class, so only their values and methods are linted
+    fn class_body(&mut self, class: &str, body: &[Instruction]) {
+        let mut methods: Vec<(&str, &Instruction)> = vec![];
+        self.frame(vec![], |slf| {
+            for instruction in body {
+                match &instruction.instruction_type {
+                    InstructionType::VarAssign { data, .. } => slf.instruction(data),
+                    InstructionType::FunctionDeclaration { identifier, .. } => {
+                        if let Some((_, first)) = methods.iter().find(|(v, _)| v == identifier) {
+                            let first = (&first.start, &first.end);
+                            slf.warn(
+                                Lint::DuplicateMethods,
+                                instruction,
+                                format!("{} declares the method {} twice", class, identifier),
+                                "only the last declaration can be called, consider renaming one of them",
+                            );
+                            slf.related(first, "first declared here")
+                        }
+                        methods.push((identifier, instruction));
+                        slf.instruction(instruction)
+                    }
+                    _ => slf.instruction(instruction),
+                }
+            }
+        })
+    }
+
+    fn declare(&mut self, identifier: &str, instruction: &Instruction) {
+        if let Some((_, start, end)) = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|v| v.iter().rev().find(|(v, _, _)| v == identifier))
+            .cloned()
+        {
+            self.warn(
+                Lint::Shadowing,
+                instruction,
+                format!("{} shadows an earlier declaration", identifier),
+                "the earlier variable can't be reached by name anymore, consider renaming one of them",
+            );
+            self.related((&start, &end), "declared earlier here")
+        }
+        let exported = self.shared_top_level && self.scopes.len() == 1;
+        if !exported && !self.used.contains(identifier) {
+            self.warn(
+                Lint::UnusedVariables,
+                instruction,
+                format!("unused variable {}", identifier),
+                "nothing reads it, consider removing it",
+            )
+        }
+        self.scopes.last_mut().unwrap().push((
+            identifier.to_string(),
+            instruction.start.clone(),
+            instruction.end.clone(),
+        ))
+    }
+
+    fn collect_used(&mut self, instruction: &Instruction) {
+        match &instruction.instruction_type {
+            InstructionType::VarAccess { identifier, .. } => {
+                self.used.insert(identifier.clone());
+            }
+            InstructionType::FunctionCall {
+                identifier,
+                arguments,
+            }
+            | InstructionType::ClassInstantiation {
+                identifier,
+                constructor_arguments: arguments,
+            } => {
+                self.used.insert(identifier.clone());
+                for argument in arguments {
+                    self.collect_used(argument)
+                }
+            }
+            InstructionType::VarAssign {
+                data, type_hint, ..
+            } => {
+                self.collect_type(type_hint);
+                self.collect_used(data)
+            }
+            InstructionType::As {
+                convert_type,
+                value,
+            } => {
+                self.collect_type(convert_type);
+                self.collect_used(value)
+            }
+            InstructionType::FunctionDeclaration {
+                body, arguments, ..
+            } => {
+                for (_, type_hint, default_value) in arguments {
+                    self.collect_type(type_hint);
+                    if let Some(v) = default_value {
+                        self.collect_used(v)
+                    }
+                }
+                self.collect_used(body)
+            }
+            InstructionType::Section { body } => {
+                for v in body {
+                    self.collect_used(v)
+                }
+            }
+            InstructionType::BinaryOperation { left, right, .. } => {
+                self.collect_used(left);
+                self.collect_used(right)
+            }
+            InstructionType::IfStatement {
+                condition,
+                body,
+                else_value,
+            } => {
+                if let Some(v) = condition {
+                    self.collect_used(v)
+                }
+                self.collect_used(body);
+                if let Some(v) = else_value {
+                    self.collect_used(v)
+                }
+            }
+            InstructionType::WhileStatement {
+                condition: left,
+                body: right,
+            }
+            | InstructionType::DoCatch {
+                do_body: left,
+                catch_body: right,
+            }
+            | InstructionType::InContextOf {
+                context_of: left,
+                run: right,
+            } => {
+                self.collect_used(left);
+                self.collect_used(right)
+            }
+            InstructionType::VarUpdate { data: value, .. }
+            | InstructionType::UnaryOperation { value, .. }
+            | InstructionType::ReturnStatement { value, .. }
+            | InstructionType::BreakStatement { value }
+            | InstructionType::DocComment { value, .. }
+            | InstructionType::ClassDeclaration { body: value, .. } => self.collect_used(value),
+            InstructionType::UseStatement { .. }
+            | InstructionType::Data(_)
+            | InstructionType::Pass => {}
+        }
+    }
+
+    fn collect_type(&mut self, type_hint: &Type) {
+        if let TypeHint::Class(v) = &type_hint.type_value {
+            self.used.insert(v.clone());
+        }
+    }
+}
+
+/// Names a file declares at its top level, which is what a `use` of it brings in
+pub(crate) fn declared_names(instructions: &[Instruction]) -> Vec<String> {
+    instructions
+        .iter()
+        .filter_map(|v| match &v.instruction_type {
+            InstructionType::VarAssign { identifier, .. }
+            | InstructionType::FunctionDeclaration { identifier, .. }
+            | InstructionType::ClassDeclaration { identifier, .. } => Some(identifier.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_hoisted(instruction: &Instruction) -> bool {
+    match &instruction.instruction_type {
+        InstructionType::UseStatement { .. }
+        | InstructionType::ClassDeclaration { .. }
+        | InstructionType::FunctionDeclaration { .. } => true,
+        InstructionType::DocComment { value, .. } => is_hoisted(value),
+        _ => false,
+    }
+}
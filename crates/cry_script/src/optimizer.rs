@@ -0,0 +1,315 @@
+//! Folds constant expressions and removes code that can never run.
+//!
+//! Only literals are folded, anything that fails to evaluate (like `1 / 0` or an
+//! overflowing `2147483647 + 1`) is left alone so the error is still raised at runtime,
+//! where a `do` block can catch it.
+//! String repetition isn't folded either, its result can be any size and only the
+//! runtime enforces the step, time and memory limits.
+
+use crate::{
+    interpreter::instructions::{binary_op::BinaryOperator, Instruction, InstructionType},
+    parser::data::Value,
+};
+
+pub(crate) struct Optimizer;
+
+impl Optimizer {
+    pub(crate) fn optimize(instructions: &mut Vec<Instruction>) {
+        Optimizer::block(instructions)
+    }
+
+    fn block(instructions: &mut Vec<Instruction>) {
+        for instruction in instructions.iter_mut() {
+            Optimizer::instruction(instruction)
+        }
+        // Declarations are hoisted so they still have to run after a return
+        if let Some(end) = instructions
+            .iter()
+            .position(|v| matches!(v.instruction_type, InstructionType::ReturnStatement { .. }))
+        {
+            let mut index = 0;
+            instructions.retain(|v| {
+                index += 1;
+                index <= end + 1
+                    || matches!(
+                        v.instruction_type,
+                        InstructionType::UseStatement { .. }
+                            | InstructionType::ClassDeclaration { .. }
+                            | InstructionType::FunctionDeclaration { .. }
+                    )
+            });
+        }
+    }
+
+    fn instruction(instruction: &mut Instruction) {
+        let position = (&instruction.start, &instruction.end, &instruction.file_data);
+        match &mut instruction.instruction_type {
+            InstructionType::BinaryOperation {
+                left,
+                right,
+                operator,
+            } => {
+                Optimizer::instruction(left);
+                Optimizer::instruction(right);
+                if let (Some(left), Some(right)) = (literal(left), literal(right)) {
+                    if repeats_string(operator, left, right) {
+                        return;
+                    }
+                    if let Ok(v) = operator.operate(left, right, position) {
+                        instruction.instruction_type = InstructionType::Data(v)
+                    }
+                }
+            }
+            InstructionType::UnaryOperation { value, operator } => {
+                Optimizer::instruction(value);
+                if let Some(value) = literal(value) {
+                    if let Ok(v) = operator.operate(value, position) {
+                        instruction.instruction_type = InstructionType::Data(v)
+                    }
+                }
+            }
+            InstructionType::As {
+                convert_type,
+                value,
+            } => {
+                Optimizer::instruction(value);
+                if let Some(value) = literal(value) {
+                    if let Ok(v) = value.convert_to(convert_type, position) {
+                        instruction.instruction_type = InstructionType::Data(v)
+                    }
+                }
+            }
+            InstructionType::IfStatement {
+                condition,
+                body,
+                else_value,
+            } => {
+                if let Some(v) = condition {
+                    Optimizer::instruction(v)
+                }
+                let taken = match condition {
+                    Some(v) => literal(v).and_then(|v| v.as_bool(position).ok()),
+                    None => Some(true),
+                };
+                // Branches that can't run are dropped before they're optimized
+                match taken {
+                    Some(true) => {
+                        *instruction = *body.clone();
+                        Optimizer::instruction(instruction)
+                    }
+                    Some(false) => match else_value {
+                        Some(v) => {
+                            *instruction = *v.clone();
+                            Optimizer::instruction(instruction)
+                        }
+                        None => instruction.instruction_type = InstructionType::Pass,
+                    },
+                    None => {
+                        Optimizer::instruction(body);
+                        if let Some(v) = else_value {
+                            Optimizer::instruction(v)
+                        }
+                    }
+                }
+            }
+            InstructionType::WhileStatement { condition, body } => {
+                Optimizer::instruction(condition);
+                if let Some(Ok(false)) = literal(condition).map(|v| v.as_bool(position)) {
+                    instruction.instruction_type = InstructionType::Pass
+                } else {
+                    Optimizer::instruction(body)
+                }
+            }
+            InstructionType::Section { body } => Optimizer::block(body),
+            InstructionType::FunctionDeclaration {
+                body, arguments, ..
+            } => {
+                for (_, _, default_value) in arguments.iter_mut() {
+                    if let Some(v) = default_value {
+                        Optimizer::instruction(v)
+                    }
+                }
+                Optimizer::instruction(body)
+            }
+            InstructionType::ClassDeclaration { body, .. } => {
+                // Class bodies aren't run like a block, only their members are visited
+                if let InstructionType::Section { body } = &mut body.instruction_type {
+                    for v in body.iter_mut() {
+                        Optimizer::instruction(v)
+                    }
+                }
+            }
+            InstructionType::VarAssign { data, .. } | InstructionType::VarUpdate { data, .. } => {
+                Optimizer::instruction(data)
+            }
+            InstructionType::DoCatch {
+                do_body,
+                catch_body,
+            } => {
+                Optimizer::instruction(do_body);
+                Optimizer::instruction(catch_body)
+            }
+            InstructionType::InContextOf { context_of, run } => {
+                Optimizer::instruction(context_of);
+                Optimizer::instruction(run)
+            }
+            InstructionType::FunctionCall { arguments, .. }
+            | InstructionType::ClassInstantiation {
+                constructor_arguments: arguments,
+                ..
+            } => {
+                for v in arguments.iter_mut() {
+                    Optimizer::instruction(v)
+                }
+            }
+            InstructionType::ReturnStatement { value, .. }
+            | InstructionType::BreakStatement { value }
+            | InstructionType::DocComment { value, .. } => Optimizer::instruction(value),
+            InstructionType::VarAccess { .. }
+            | InstructionType::UseStatement { .. }
+            | InstructionType::Data(_)
+            | InstructionType::Pass => {}
+        }
+    }
+}
+
+/// Values that can be evaluated without running anything
+fn literal(instruction: &Instruction) -> Option<&Value> {
+    match &instruction.instruction_type {
+        InstructionType::Data(
+            v @ (Value::Integer(_) | Value::Float(_) | Value::String(_) | Value::Null),
+        ) => Some(v),
+        _ => None,
+    }
+}
+
+/// `*` and `^` on a string repeat it
+fn repeats_string(operator: &BinaryOperator, left: &Value, right: &Value) -> bool {
+    matches!(operator, BinaryOperator::Multiply | BinaryOperator::Power)
+        && matches!((left, right), (Value::String(_), _) | (_, Value::String(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser, FileData};
+
+    fn optimized(source: &str) -> Vec<InstructionType> {
+        let file_data = Rc::new(FileData::new(source.to_string(), "test".to_string()));
+        let tokens = Lexer::lex(file_data.clone()).unwrap();
+        let mut instructions = Parser::parse(file_data, tokens).unwrap();
+        Optimizer::optimize(&mut instructions);
+        instructions
+            .into_iter()
+            .map(|v| v.instruction_type)
+            .collect()
+    }
+
+    /// The value assigned by a single `var` statement
+    fn assigned(source: &str) -> InstructionType {
+        match optimized(source).pop() {
+            Some(InstructionType::VarAssign { data, .. }) => data.instruction_type,
+            v => panic!("expected an assignment, got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn folds_constants() {
+        assert!(matches!(
+            assigned("var day = 60 * 60 * 24"),
+            InstructionType::Data(Value::Integer(86400))
+        ));
+        assert!(matches!(
+            assigned("var v = -(2 * 3) + 0.5"),
+            InstructionType::Data(Value::Float(v)) if v == -5.5
+        ));
+        assert!(matches!(
+            assigned("var v = \"12\" as int"),
+            InstructionType::Data(Value::Integer(12))
+        ));
+    }
+
+    #[test]
+    fn leaves_failing_expressions_to_the_runtime() {
+        assert!(matches!(
+            assigned("var v = 1 / 0"),
+            InstructionType::BinaryOperation { .. }
+        ));
+        assert!(matches!(
+            assigned("var v = \"abc\" as int"),
+            InstructionType::As { .. }
+        ));
+        assert!(matches!(
+            assigned("var v = 2147483647 + 1"),
+            InstructionType::BinaryOperation { .. }
+        ));
+    }
+
+    #[test]
+    fn removes_dead_branches_before_folding_them() {
+        let instructions =
+            optimized("if 0 {\n    var x = 2147483647 + 1\n}\nwhile 0 {\n    2 ^ 40\n}");
+        assert!(matches!(
+            instructions[..],
+            [InstructionType::Pass, InstructionType::Pass]
+        ));
+    }
+
+    #[test]
+    fn leaves_string_repetition_to_the_runtime() {
+        for source in ["var v = \"ab\" * 100000", "var v = \"ab\" ^ 20"] {
+            assert!(matches!(
+                assigned(source),
+                InstructionType::BinaryOperation { .. }
+            ));
+        }
+        assert!(matches!(
+            assigned("var v = \"ab\" + 1"),
+            InstructionType::Data(Value::String(_))
+        ));
+    }
+
+    #[test]
+    fn removes_dead_branches_and_loops() {
+        let instructions = optimized(
+            "while 0 {\n    println(1)\n}\nif 0 {\n    println(2)\n} else {\n    println(3)\n}\nif 0 {\n    println(4)\n}",
+        );
+        assert!(matches!(instructions[0], InstructionType::Pass));
+        let InstructionType::Section { body } = &instructions[1] else {
+            panic!("expected the else body, got {:?}", instructions[1])
+        };
+        assert!(matches!(
+            &body[0].instruction_type,
+            InstructionType::FunctionCall { arguments, .. }
+                if matches!(
+                    arguments[0].instruction_type,
+                    InstructionType::Data(Value::Integer(3))
+                )
+        ));
+        assert!(matches!(instructions[2], InstructionType::Pass));
+    }
+
+    #[test]
+    fn keeps_declarations_after_return() {
+        let instructions = optimized(
+            "fn f() {\n    return g()\n    println(1)\n    fn g() {\n        2\n    }\n}",
+        );
+        let InstructionType::FunctionDeclaration { body, .. } = &instructions[0] else {
+            panic!("expected a function, got {:?}", instructions[0])
+        };
+        let InstructionType::Section { body } = &body.instruction_type else {
+            panic!("expected a body, got {:?}", body)
+        };
+        assert_eq!(body.len(), 2);
+        assert!(matches!(
+            body[0].instruction_type,
+            InstructionType::ReturnStatement { .. }
+        ));
+        assert!(matches!(
+            body[1].instruction_type,
+            InstructionType::FunctionDeclaration { .. }
+        ));
+    }
+}
@@ -1,14 +1,14 @@
 pub mod data;
 
-use core::panic;
 use std::{mem::discriminant, rc::Rc};
 
 use crate::{
-    exceptions::parser_exceptions::{
-        InvalidInstructionInClass, UnexpectedToken, UnterminatedParenthesis,
+    exceptions::{
+        parser_exceptions::{InvalidInstructionInClass, UnexpectedToken, UnterminatedParenthesis},
+        Exception,
     },
     interpreter::type_hint::{Type, TypeHint},
-    lexer::token::{Token, TokenType},
+    lexer::token::{StringPart, Token, TokenType},
     Annotation, FileData, Keyword, Position,
 };
 
@@ -16,12 +16,14 @@ use crate::interpreter::instructions::{
     binary_op::BinaryOperator, unary_op::UnaryOperator, Instruction, InstructionType,
 };
 
-use self::data::Data;
+use self::data::Value;
 
 pub(crate) struct Parser {
     tokens: Vec<Token>,
     current_index: usize,
     file_data: Rc<FileData>,
+    /// Errors of statements that failed to parse, reported together once the file is done
+    diagnostics: Vec<Exception>,
 }
 
 // #############################
@@ -30,23 +32,47 @@ pub(crate) struct Parser {
 // #
 // #############################
 impl Parser {
-    pub(crate) fn parse(file_data: Rc<FileData>, tokens: Vec<Token>) -> Vec<Instruction> {
+    pub(crate) fn parse(
+        file_data: Rc<FileData>,
+        tokens: Vec<Token>,
+    ) -> Result<Vec<Instruction>, Exception> {
         if tokens.is_empty() {
-            return vec![];
+            return Ok(vec![]);
         }
         let mut parser = Parser {
             tokens,
             current_index: 0,
             file_data,
+            diagnostics: vec![],
         };
 
-        match parser.parse_section().instruction_type {
-            InstructionType::Section { body } => body,
-            _ => panic!(),
+        let body = parser.parse_body();
+        if parser.diagnostics.is_empty() {
+            Ok(body)
+        } else {
+            Err(Exception::from_diagnostics(parser.diagnostics))
         }
     }
 
-    pub(crate) fn parse_section(&mut self) -> Instruction {
+    pub(crate) fn parse_section(&mut self) -> Result<Instruction, Exception> {
+        let instructions = self.parse_body();
+        Ok(Instruction::new(
+            match instructions.first() {
+                Some(v) => v.start.clone(),
+                None => Position::new(0),
+            },
+            match instructions.last() {
+                Some(v) => v.end.clone(),
+                None => Position::new(0),
+            },
+            self.file_data.clone(),
+            InstructionType::Section { body: instructions },
+        ))
+    }
+
+    /// Parses statements until the dedent that closes the section or the end of the file,
+    /// statements that fail to parse are recorded in the diagnostics
+    fn parse_body(&mut self) -> Vec<Instruction> {
         if self.current_token().unwrap().token_type == TokenType::Indent {
             self.advance()
         }
@@ -59,39 +85,42 @@ impl Parser {
                     break;
                 }
             }
-            instructions.push(self.parse_once());
+            match self.parse_once() {
+                Ok(v) => instructions.push(v),
+                Err(e) => self.recover(e),
+            }
             self.skip_new_line();
             // println!("{}", self.current_token().unwrap().token_type);
         }
         // self.advance();
-        Instruction::new(
-            match instructions.first() {
-                Some(v) => v.start.clone(),
-                None => Position::new(0),
-            },
-            match instructions.last() {
-                Some(v) => v.end.clone(),
-                None => Position::new(0),
-            },
-            self.file_data.clone(),
-            InstructionType::Section { body: instructions },
-        )
+        instructions
     }
 
-    fn parse_once(&mut self) -> Instruction {
+    fn parse_once(&mut self) -> Result<Instruction, Exception> {
         self.skip_new_line();
         // println!("{}", self.current_token().unwrap().token_type);
         let value = match &self.current_token().unwrap().token_type {
-            TokenType::Identifier(_) => self.identifier_statement(),
-            TokenType::Annotation(_) => self.annotation(),
+            // Anything that isn't an assignment is an expression, like `x + 1`
+            TokenType::Identifier(_) => match self.peak().map(|v| &v.token_type) {
+                Some(
+                    TokenType::Equals
+                    | TokenType::PlusEquals
+                    | TokenType::MinusEquals
+                    | TokenType::MultiplyEquals
+                    | TokenType::DivideEquals
+                    | TokenType::PowerEquals,
+                ) => self.identifier_statement()?,
+                _ => self.expr()?,
+            },
+            TokenType::Annotation(v) => self.annotation(v.clone())?,
             TokenType::Keyword(v) => match v {
-                Keyword::Var | Keyword::Final => self.var_statement(),
-                Keyword::Use => self.use_statement(),
-                Keyword::Pass => self.pass_statement(),
-                Keyword::Function => self.function_declaration(),
-                _ => self.expr(),
+                Keyword::Var | Keyword::Final => self.var_statement()?,
+                Keyword::Use => self.use_statement()?,
+                Keyword::Pass => self.pass_statement()?,
+                Keyword::Function => self.function_declaration()?,
+                _ => self.expr()?,
             },
-            _ => self.expr(),
+            _ => self.expr()?,
         };
         self.advance();
         // println!("{}", self.current_token().unwrap().token_type);
@@ -102,7 +131,7 @@ impl Parser {
                     if self.current_token().unwrap().token_type != TokenType::Dedent
                         && self.current_token().unwrap().token_type != TokenType::EndOfFile
                     {
-                        UnexpectedToken::call(
+                        return Err(UnexpectedToken::call(
                             self.current_token().unwrap().fetch(),
                             "new line or dedent token",
                             self.current_token()
@@ -110,16 +139,41 @@ impl Parser {
                                 .token_type
                                 .to_string()
                                 .as_str(),
-                        )
+                        ));
                     }
                 }
             }
         } else if self.peak_behind_by(1).is_none()
             || self.peak_behind_by(1).unwrap().token_type != TokenType::EndOfFile
         {
-            UnexpectedToken::call(self.last_token().unwrap().fetch(), "end of file", "nothing")
+            return Err(UnexpectedToken::call(
+                self.last_token().unwrap().fetch(),
+                "end of file",
+                "nothing",
+            ));
         };
-        value
+        Ok(value)
+    }
+
+    /// Records the error of a statement that failed to parse and skips the rest of it,
+    /// stopping after its new line or before the dedent that closes the current section
+    fn recover(&mut self, exception: Exception) {
+        self.diagnostics.push(exception);
+        let mut depth = 0;
+        while let Some(token) = self.current_token() {
+            match token.token_type {
+                TokenType::EndOfFile => return,
+                TokenType::Dedent if depth == 0 => return,
+                TokenType::NewLine if depth == 0 => {
+                    self.advance();
+                    return;
+                }
+                TokenType::Indent => depth += 1,
+                TokenType::Dedent => depth -= 1,
+                _ => {}
+            }
+            self.advance()
+        }
     }
 }
 
@@ -204,12 +258,12 @@ impl Parser {
 // #
 // #############################
 impl Parser {
-    fn expr(&mut self) -> Instruction {
+    fn expr(&mut self) -> Result<Instruction, Exception> {
         let v = self.binary_op(
             Parser::comparison_expr,
             Parser::comparison_expr,
             &[TokenType::And, TokenType::Or],
-        );
+        )?;
         if self.peak().is_some()
             && matches!(
                 self.peak().unwrap().token_type,
@@ -218,8 +272,8 @@ impl Parser {
         {
             self.advance();
             self.advance();
-            let convert_type = Type::from(self.current_token().unwrap());
-            return Instruction::new(
+            let convert_type = Type::try_from(self.current_token().unwrap())?;
+            return Ok(Instruction::new(
                 v.start.clone(),
                 self.current_token().unwrap().end.clone(),
                 self.file_data.clone(),
@@ -227,19 +281,19 @@ impl Parser {
                     convert_type,
                     value: Box::new(v),
                 },
-            );
+            ));
         }
-        v
+        Ok(v)
     }
 
-    fn comparison_expr(&mut self) -> Instruction {
+    fn comparison_expr(&mut self) -> Result<Instruction, Exception> {
         if self.current_token().is_some()
             && self.current_token().unwrap().token_type == TokenType::ExclamationMark
         {
             let start = self.current_token().unwrap().start.clone();
             self.advance();
-            let comp_expr = self.comparison_expr();
-            return Instruction::new(
+            let comp_expr = self.comparison_expr()?;
+            return Ok(Instruction::new(
                 start,
                 comp_expr.end.clone(),
                 self.file_data.clone(),
@@ -247,7 +301,7 @@ impl Parser {
                     value: Box::new(comp_expr),
                     operator: UnaryOperator::Not,
                 },
-            );
+            ));
         }
         self.binary_op(
             Parser::arith_expr,
@@ -263,7 +317,7 @@ impl Parser {
         )
     }
 
-    fn arith_expr(&mut self) -> Instruction {
+    fn arith_expr(&mut self) -> Result<Instruction, Exception> {
         self.binary_op(
             Parser::term,
             Parser::term,
@@ -271,7 +325,7 @@ impl Parser {
         )
     }
 
-    fn term(&mut self) -> Instruction {
+    fn term(&mut self) -> Result<Instruction, Exception> {
         self.binary_op(
             Parser::factor,
             Parser::factor,
@@ -279,20 +333,20 @@ impl Parser {
         )
     }
 
-    fn power(&mut self) -> Instruction {
+    fn power(&mut self) -> Result<Instruction, Exception> {
         self.binary_op(Parser::atom, Parser::factor, &[TokenType::Power])
     }
 
-    fn factor(&mut self) -> Instruction {
+    fn factor(&mut self) -> Result<Instruction, Exception> {
         if self.current_token().is_some()
             && [TokenType::Plus, TokenType::Minus]
                 .contains(&self.current_token().unwrap().token_type)
         {
             let start_pos = self.current_token().unwrap().start.clone();
-            let operator = UnaryOperator::from(self.current_token().unwrap());
+            let operator = UnaryOperator::try_from(self.current_token().unwrap())?;
             self.advance();
-            let factor = self.factor();
-            return Instruction::new(
+            let factor = self.factor()?;
+            return Ok(Instruction::new(
                 start_pos,
                 factor.end.clone(),
                 self.file_data.clone(),
@@ -300,33 +354,39 @@ impl Parser {
                     value: Box::new(factor),
                     operator,
                 },
-            );
+            ));
         }
         self.power()
     }
 
-    fn atom(&mut self) -> Instruction {
+    fn atom(&mut self) -> Result<Instruction, Exception> {
         let instruction = match self.current_token().cloned() {
             Some(token) => {
                 // : Maybe there's a way to avoid cloning the token
                 match token.token_type {
                     TokenType::Keyword(keyword) => match keyword {
-                        Keyword::If => self.if_statement(),
-                        Keyword::Class => self.class_declaration(),
-                        Keyword::Return => self.return_statement(),
-                        Keyword::Break => self.break_statement(),
-                        Keyword::New => self.class_instantiate(),
-                        Keyword::While => self.while_statement(),
-                        Keyword::Do => self.do_catch(),
-                        _ => panic!("how tf"),
+                        Keyword::If => self.if_statement()?,
+                        Keyword::Class => self.class_declaration()?,
+                        Keyword::Return => self.return_statement()?,
+                        Keyword::Break => self.break_statement()?,
+                        Keyword::New => self.class_instantiate()?,
+                        Keyword::While => self.while_statement()?,
+                        Keyword::Do => self.do_catch()?,
+                        _ => {
+                            return Err(UnexpectedToken::call(
+                                self.current_token().unwrap().fetch(),
+                                "an expression",
+                                self.current_token_type_str().as_str(),
+                            ))
+                        }
                     },
                     TokenType::Indent => {
                         self.advance();
-                        self.parse_section()
+                        self.parse_section()?
                     }
                     TokenType::LeftParenthesis => {
                         self.advance();
-                        let mut expr = self.expr();
+                        let mut expr = self.expr()?;
                         self.advance();
                         self.skip_new_line();
                         if self.current_token().is_none()
@@ -335,19 +395,26 @@ impl Parser {
                                 TokenType::RightParenthesis
                             )
                         {
-                            UnterminatedParenthesis::call(&expr.start, &expr.end, &expr.file_data)
+                            return Err(UnterminatedParenthesis::call(
+                                &expr.start,
+                                &expr.end,
+                                &expr.file_data,
+                            ));
                         }
                         expr.start = token.start.advance_by_owned(-2);
                         expr.end.advance_by(1);
                         expr
                     }
-                    TokenType::Identifier(_) => self.identifier_expression(),
+                    TokenType::Identifier(_) => self.identifier_expression()?,
+                    TokenType::InterpolatedString(parts) => {
+                        self.interpolated_string((&token.start, &token.end), parts)?
+                    }
                     TokenType::NewLine => {
                         self.advance();
-                        self.atom()
+                        self.atom()?
                     }
                     _ => {
-                        let data = Data::from(&token);
+                        let data = Value::try_from(&token)?;
                         Instruction::new(
                             token.start.clone(),
                             token.end.clone(),
@@ -357,29 +424,79 @@ impl Parser {
                     }
                 }
             }
-            None => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "an integer, float, or identifier",
-                self.current_token_type_str().as_str(),
-            ),
+            None => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "an integer, float, or identifier",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         };
-        instruction
+        Ok(instruction)
+    }
+
+    /// Adds every part of the string to an empty string, so values are converted
+    /// to strings the same way `+` converts them
+    fn interpolated_string(
+        &self,
+        (start, end): (&Position, &Position),
+        parts: Vec<StringPart>,
+    ) -> Result<Instruction, Exception> {
+        let instruction = |instruction_type| {
+            Instruction::new(
+                start.clone(),
+                end.clone(),
+                self.file_data.clone(),
+                instruction_type,
+            )
+        };
+        let mut string = instruction(InstructionType::Data(Value::from(String::new())));
+        for part in parts {
+            let value = match part {
+                StringPart::Literal(v) if v.is_empty() => continue,
+                StringPart::Literal(v) => instruction(InstructionType::Data(Value::from(v))),
+                StringPart::Expression(tokens) => {
+                    let mut parser = Parser {
+                        tokens,
+                        current_index: 0,
+                        file_data: self.file_data.clone(),
+                        diagnostics: vec![],
+                    };
+                    let value = parser.expr()?;
+                    parser.advance();
+                    if !parser.expect(TokenType::EndOfFile) {
+                        return Err(UnexpectedToken::call(
+                            parser.current_token().unwrap().fetch(),
+                            "}",
+                            parser.current_token_type_str().as_str(),
+                        ));
+                    }
+                    value
+                }
+            };
+            string = instruction(InstructionType::BinaryOperation {
+                left: Box::new(string),
+                right: Box::new(value),
+                operator: BinaryOperator::Add,
+            });
+        }
+        Ok(string)
     }
 
     fn binary_op(
         &mut self,
-        left_func: fn(&mut Parser) -> Instruction,
-        right_func: fn(&mut Parser) -> Instruction,
+        left_func: fn(&mut Parser) -> Result<Instruction, Exception>,
+        right_func: fn(&mut Parser) -> Result<Instruction, Exception>,
         operation_tokens: &[TokenType],
-    ) -> Instruction {
-        let mut left = left_func(self);
+    ) -> Result<Instruction, Exception> {
+        let mut left = left_func(self)?;
         self.advance();
         while self.current_token().is_some()
             && operation_tokens.contains(&self.current_token().unwrap().token_type)
         {
-            let operator = BinaryOperator::from(self.current_token().unwrap(), operation_tokens);
+            let operator = BinaryOperator::from(self.current_token().unwrap(), operation_tokens)?;
             self.advance();
-            let right = right_func(self);
+            let right = right_func(self)?;
             self.advance();
             left = Instruction::new(
                 left.start.clone(),
@@ -393,21 +510,23 @@ impl Parser {
             );
         }
         self.retreat();
-        left
+        Ok(left)
     }
 }
 
 impl Parser {
-    fn identifier_statement(&mut self) -> Instruction {
-        fn change_assign(slf: &mut Parser) -> Instruction {
+    fn identifier_statement(&mut self) -> Result<Instruction, Exception> {
+        fn change_assign(slf: &mut Parser) -> Result<Instruction, Exception> {
             let start = slf.current_token().unwrap().start.clone();
             let identifier = match slf.current_token_type() {
                 TokenType::Identifier(v) => v.clone(),
-                _ => UnexpectedToken::call(
-                    slf.current_token().unwrap().fetch(),
-                    "[identifier]",
-                    slf.current_token_type_str().as_str(),
-                ),
+                _ => {
+                    return Err(UnexpectedToken::call(
+                        slf.current_token().unwrap().fetch(),
+                        "[identifier]",
+                        slf.current_token_type_str().as_str(),
+                    ))
+                }
             };
             slf.advance();
             let operator = BinaryOperator::from(
@@ -419,10 +538,10 @@ impl Parser {
                     TokenType::DivideEquals,
                     TokenType::PowerEquals,
                 ],
-            );
+            )?;
             slf.advance();
-            let expr = slf.expr();
-            Instruction::new(
+            let expr = slf.expr()?;
+            Ok(Instruction::new(
                 start.clone(),
                 expr.end.clone(),
                 slf.file_data.clone(),
@@ -431,29 +550,36 @@ impl Parser {
                         start,
                         expr.end.clone(),
                         slf.file_data.clone(),
-                        InstructionType::VarAccess { identifier },
+                        InstructionType::VarAccess {
+                            identifier,
+                            slot: None,
+                        },
                     )),
                     right: Box::new(expr),
                     operator,
                 },
-            )
+            ))
         }
         match &match self.current_token() {
             Some(v) => v,
-            None => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "[identifier]",
-                self.current_token_type_str().as_str(),
-            ),
+            None => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "[identifier]",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .token_type
         {
             TokenType::Identifier(v) => v,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "[identifier]",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "[identifier]",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         };
         if let Some(x) = self.peak() {
             match x.token_type {
@@ -470,30 +596,33 @@ impl Parser {
         }
     }
 
-    fn identifier_expression(&mut self) -> Instruction {
+    fn identifier_expression(&mut self) -> Result<Instruction, Exception> {
         let initial_value = match &self.current_token().unwrap().token_type {
             TokenType::Identifier(identifier) => match self.peak().unwrap().token_type {
-                TokenType::LeftParenthesis => self.function_call(),
+                TokenType::LeftParenthesis => self.function_call()?,
                 _ => Instruction::new(
                     self.current_token().unwrap().start.clone(),
                     self.current_token().unwrap().end.clone(),
                     self.file_data.clone(),
                     InstructionType::VarAccess {
                         identifier: identifier.clone(),
+                        slot: None,
                     },
                 ),
             },
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "[identifier]",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "[identifier]",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         };
         if self.peak().is_some() && self.peak().unwrap().token_type == TokenType::Dot {
             self.advance();
             self.advance();
-            let run = self.identifier_statement();
-            Instruction::new(
+            let run = self.identifier_statement()?;
+            Ok(Instruction::new(
                 initial_value.start.clone(),
                 run.end.clone(),
                 self.file_data.clone(),
@@ -501,12 +630,12 @@ impl Parser {
                     context_of: Box::new(initial_value),
                     run: Box::new(run),
                 },
-            )
+            ))
         } else {
-            initial_value
+            Ok(initial_value)
         }
     }
-    fn update_variable(&mut self) -> Instruction {
+    fn update_variable(&mut self) -> Result<Instruction, Exception> {
         enum Type {
             Equals,
             PlusEquals,
@@ -518,20 +647,24 @@ impl Parser {
         let start = self.current_token().unwrap().start.clone();
         let identifier = match &match self.current_token() {
             Some(v) => v,
-            None => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "[identifier] = [expression]",
-                self.current_token_type_str().as_str(),
-            ),
+            None => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "[identifier] = [expression]",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .token_type
         {
             TokenType::Identifier(v) => v,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "[identifier] = [expression]",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "[identifier] = [expression]",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .clone();
         self.advance();
@@ -542,15 +675,17 @@ impl Parser {
             TokenType::DivideEquals => Type::DivideEquals,
             TokenType::MultiplyEquals => Type::MultiplyEquals,
             TokenType::PowerEquals => Type::PowerEquals,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                format!("{} = [expression]", identifier).as_str(),
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    format!("{} = [expression]", identifier).as_str(),
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         };
         self.advance();
-        let expr = self.expr();
-        Instruction::new(
+        let expr = self.expr()?;
+        Ok(Instruction::new(
             start.clone(),
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
@@ -573,6 +708,7 @@ impl Parser {
                                 self.file_data.clone(),
                                 InstructionType::VarAccess {
                                     identifier: identifier.clone(),
+                                    slot: None,
                                 },
                             )),
                             right: Box::new(expr),
@@ -581,114 +717,125 @@ impl Parser {
                                 Type::MinusEquals => BinaryOperator::Remove,
                                 Type::MultiplyEquals => BinaryOperator::Multiply,
                                 Type::DivideEquals => BinaryOperator::Divide,
-                                Type::Equals => UnexpectedToken::call(
-                                    self.current_token().unwrap().fetch(),
-                                    format!("{} = [expression]", identifier).as_str(),
-                                    self.current_token()
-                                        .unwrap()
-                                        .token_type
-                                        .to_string()
-                                        .as_str(),
-                                ),
+                                Type::Equals => {
+                                    return Err(UnexpectedToken::call(
+                                        self.current_token().unwrap().fetch(),
+                                        format!("{} = [expression]", identifier).as_str(),
+                                        self.current_token()
+                                            .unwrap()
+                                            .token_type
+                                            .to_string()
+                                            .as_str(),
+                                    ))
+                                }
                                 Type::PowerEquals => BinaryOperator::Power,
                             },
                         },
                     ),
                 }),
+                slot: None,
             },
-        )
+        ))
     }
-    fn use_statement(&mut self) -> Instruction {
+    fn use_statement(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::Use)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "use \"[file path]\"",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         let file_path = match &match self.current_token() {
             Some(v) => v,
-            None => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "use \"[file path]\"",
-                self.current_token_type_str().as_str(),
-            ),
+            None => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "use \"[file path]\"",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .token_type
         {
             TokenType::String(v) => v,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "use \"[file path]\"",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "use \"[file path]\"",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .clone();
-        Instruction::new(
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
             InstructionType::UseStatement { file_path },
-        )
+        ))
     }
 
-    fn pass_statement(&mut self) -> Instruction {
-        Instruction::new(
+    fn pass_statement(&mut self) -> Result<Instruction, Exception> {
+        Ok(Instruction::new(
             self.current_token().unwrap().start.clone(),
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
             InstructionType::Pass,
-        )
+        ))
     }
 
-    fn var_statement(&mut self) -> Instruction {
+    fn var_statement(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         let is_final = if self.expect(TokenType::Keyword(Keyword::Final)) {
             self.advance();
             if !self.expect(TokenType::Keyword(Keyword::Var)) {
-                UnexpectedToken::call(
+                return Err(UnexpectedToken::call(
                     self.current_token().unwrap().fetch(),
                     "final var [identifier] = [expression]",
                     self.current_token_type_str().as_str(),
-                )
+                ));
             }
             true
         } else {
             if !self.expect(TokenType::Keyword(Keyword::Var)) {
-                UnexpectedToken::call(
+                return Err(UnexpectedToken::call(
                     self.current_token().unwrap().fetch(),
                     "var [identifier] = [expression]",
                     self.current_token_type_str().as_str(),
-                )
+                ));
             }
             false
         };
         self.advance();
         let identifier = match &match self.current_token() {
             Some(v) => v,
-            None => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "var [identifier] = [expression]",
-                self.current_token_type_str().as_str(),
-            ),
+            None => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "var [identifier] = [expression]",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .token_type
         {
             TokenType::Identifier(v) => v,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "var [identifier] = [expression]",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "var [identifier] = [expression]",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .clone();
         let type_hint =
             if self.peak().is_some() && self.peak().unwrap().token_type == TokenType::Colon {
                 self.advance();
                 self.advance();
-                let x = Type::from(self.current_token().unwrap());
+                let x = Type::try_from(self.current_token().unwrap())?;
                 x
             } else {
                 Type::new(
@@ -701,7 +848,7 @@ impl Parser {
         self.advance();
         let data = Box::new(if !self.expect(TokenType::Equals) {
             if !self.expect(TokenType::NewLine) && !self.expect(TokenType::EndOfFile) {
-                UnexpectedToken::call(
+                return Err(UnexpectedToken::call(
                     self.current_token().unwrap().fetch(),
                     &format!("var {} = [expression]", identifier),
                     self.current_token()
@@ -709,25 +856,20 @@ impl Parser {
                         .token_type
                         .to_string()
                         .as_str(),
-                )
+                ));
             }
             self.retreat();
             Instruction::new(
                 start.clone(),
                 self.current_token().unwrap().end.clone(),
                 self.file_data.clone(),
-                InstructionType::Data(Data::new(
-                    self.file_data.clone(),
-                    start.clone(),
-                    self.current_token().unwrap().end.clone(),
-                    data::DataType::Null,
-                )),
+                InstructionType::Data(Value::Null),
             )
         } else {
             self.advance();
-            self.expr()
+            self.expr()?
         });
-        Instruction::new(
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
@@ -736,56 +878,57 @@ impl Parser {
                 data,
                 type_hint,
                 is_final,
+                slot: None,
             },
-        )
+        ))
     }
 
-    fn if_statement(&mut self) -> Instruction {
+    fn if_statement(&mut self) -> Result<Instruction, Exception> {
         self.if_statement_w_start(self.current_token().unwrap().start.clone())
     }
 
-    fn if_statement_w_start(&mut self, start: Position) -> Instruction {
+    fn if_statement_w_start(&mut self, start: Position) -> Result<Instruction, Exception> {
         if !self.expect(TokenType::Keyword(Keyword::If)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "if [expression] { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let expr = self.expr();
+        let expr = self.expr()?;
         self.advance();
         self.skip_new_line();
         if !self.expect(TokenType::Indent) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "if [expression] { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let body = self.parse_section();
+        let body = self.parse_section()?;
         self.advance();
+        // New lines are only skipped if an else follows, otherwise they end the statement
+        let mut after = self.current_index;
         self.skip_new_line();
         let mut else_value: Option<Box<Instruction>> = None;
-        while let Some(token) = self.current_token() {
-            let start = token.start.clone();
-            if !self.expect(TokenType::Keyword(Keyword::Else)) {
-                break;
-            }
-            else_value = Some(Box::new(if let Some(token) = self.peak() {
-                if token.token_type != TokenType::Keyword(Keyword::If) {
-                    self.else_statement()
-                } else {
+        if self.current_token().is_some() && self.expect(TokenType::Keyword(Keyword::Else)) {
+            let start = self.current_token().unwrap().start.clone();
+            else_value = Some(Box::new(match self.peak() {
+                Some(token) if token.token_type == TokenType::Keyword(Keyword::If) => {
+                    self.advance();
+                    let value = self.if_statement_w_start(start)?;
                     self.advance();
-                    self.if_statement_w_start(start)
+                    value
                 }
-            } else {
-                self.else_statement()
+                _ => self.else_statement()?,
             }));
+            after = self.current_index;
         }
+        self.current_index = after;
         self.retreat();
-        Instruction::new(
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
@@ -794,32 +937,32 @@ impl Parser {
                 body: Box::new(body),
                 else_value,
             },
-        )
+        ))
     }
 
-    fn else_statement(&mut self) -> Instruction {
+    fn else_statement(&mut self) -> Result<Instruction, Exception> {
         // println!("else");
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::Else)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "else { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         self.skip_new_line();
         if !self.expect(TokenType::Indent) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "else { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let body = self.parse_section();
+        let body = self.parse_section()?;
         self.advance();
-        Instruction::new(
+        Ok(Instruction::new(
             start,
             body.end.clone(),
             self.file_data.clone(),
@@ -828,32 +971,32 @@ impl Parser {
                 body: Box::new(body),
                 else_value: None,
             },
-        )
+        ))
     }
 
-    fn while_statement(&mut self) -> Instruction {
+    fn while_statement(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::While)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "while [expression] { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let expr = self.expr();
+        let expr = self.expr()?;
         self.advance();
         self.skip_new_line();
         if !self.expect(TokenType::Indent) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "while [expression] { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let body = self.parse_section();
-        Instruction::new(
+        let body = self.parse_section()?;
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
@@ -861,43 +1004,44 @@ impl Parser {
                 condition: Box::new(expr),
                 body: Box::new(body),
             },
-        )
+        ))
     }
 
-    fn return_statement(&mut self) -> Instruction {
+    fn return_statement(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::Return)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "return [expression]",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let expr = self.expr();
-        Instruction::new(
+        let expr = self.expr()?;
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
             InstructionType::ReturnStatement {
                 value: Box::new(expr),
+                tail_call: false,
             },
-        )
+        ))
     }
 
-    fn break_statement(&mut self) -> Instruction {
+    fn break_statement(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::Break)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "break",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         if self.current_token_type() == &TokenType::NewLine {
             self.retreat();
-            return Instruction::new(
+            return Ok(Instruction::new(
                 start.clone(),
                 self.current_token().unwrap().end.clone(),
                 self.file_data.clone(),
@@ -906,53 +1050,50 @@ impl Parser {
                         start.clone(),
                         self.current_token().unwrap().end.clone(),
                         self.file_data.clone(),
-                        InstructionType::Data(Data::new(
-                            self.file_data.clone(),
-                            start,
-                            self.current_token().unwrap().end.clone(),
-                            data::DataType::Null,
-                        )),
+                        InstructionType::Data(Value::Null),
                     )),
                 },
-            );
+            ));
         }
-        let expr = self.expr();
-        Instruction::new(
+        let expr = self.expr()?;
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
             InstructionType::BreakStatement {
                 value: Box::new(expr),
             },
-        )
+        ))
     }
 
-    fn function_declaration(&mut self) -> Instruction {
+    fn function_declaration(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::Function)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "function [identifier]() { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         self.skip_new_line();
         let identifier = match &self.current_token().unwrap().token_type {
             TokenType::Identifier(identifier) => identifier.clone(),
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "function [identifier]() { ... }",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "function [identifier]() { ... }",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         };
         self.advance();
         if !self.expect(TokenType::LeftParenthesis) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "function [identifier]() { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         let mut arguments = vec![];
@@ -960,11 +1101,13 @@ impl Parser {
             if !arguments.is_empty() {
                 match self.current_token_type() {
                     TokenType::Comma => self.advance(),
-                    _ => UnexpectedToken::call(
-                        self.current_token().unwrap().fetch(),
-                        ",",
-                        &self.current_token_type_str(),
-                    ),
+                    _ => {
+                        return Err(UnexpectedToken::call(
+                            self.current_token().unwrap().fetch(),
+                            ",",
+                            &self.current_token_type_str(),
+                        ))
+                    }
                 }
             }
             match &self.current_token().unwrap().token_type {
@@ -979,23 +1122,27 @@ impl Parser {
                             match self.current_token() {
                                 Some(v) => match v.token_type {
                                     TokenType::TypeHint(_) | TokenType::Identifier(_) => {
-                                        Type::from(self.current_token().unwrap())
+                                        Type::try_from(self.current_token().unwrap())?
+                                    }
+                                    _ => {
+                                        return Err(UnexpectedToken::call(
+                                            self.current_token().unwrap().fetch(),
+                                            "a type hint",
+                                            self.current_token()
+                                                .unwrap()
+                                                .token_type
+                                                .to_string()
+                                                .as_str(),
+                                        ))
                                     }
-                                    _ => UnexpectedToken::call(
+                                },
+                                None => {
+                                    return Err(UnexpectedToken::call(
                                         self.current_token().unwrap().fetch(),
                                         "a type hint",
-                                        self.current_token()
-                                            .unwrap()
-                                            .token_type
-                                            .to_string()
-                                            .as_str(),
-                                    ),
-                                },
-                                None => UnexpectedToken::call(
-                                    self.current_token().unwrap().fetch(),
-                                    "a type hint",
-                                    self.current_token_type_str().as_str(),
-                                ),
+                                        self.current_token_type_str().as_str(),
+                                    ))
+                                }
                             }
                         } else {
                             Type::new(
@@ -1010,18 +1157,20 @@ impl Parser {
                         {
                             self.advance();
                             self.advance();
-                            Some(self.expr())
+                            Some(self.expr()?)
                         } else {
                             None
                         },
                     ));
                 }
                 TokenType::RightParenthesis => break,
-                _ => UnexpectedToken::call(
-                    self.current_token().unwrap().fetch(),
-                    ")",
-                    &self.current_token_type_str(),
-                ),
+                _ => {
+                    return Err(UnexpectedToken::call(
+                        self.current_token().unwrap().fetch(),
+                        ")",
+                        &self.current_token_type_str(),
+                    ))
+                }
             }
             self.advance();
             if self.current_token_type() == &TokenType::RightParenthesis {
@@ -1029,23 +1178,23 @@ impl Parser {
             }
         }
         if !self.expect(TokenType::RightParenthesis) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "function [identifier]() { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         if !self.expect(TokenType::Indent) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "function [identifier]() { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let body = self.parse_section();
-        Instruction::new(
+        let body = self.parse_section()?;
+        Ok(Instruction::new(
             start,
             body.end.clone(),
             self.file_data.clone(),
@@ -1054,39 +1203,41 @@ impl Parser {
                 body: Box::new(body),
                 arguments,
             },
-        )
+        ))
     }
 
-    fn function_call(&mut self) -> Instruction {
+    fn function_call(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         let identifier = match self.current_token_type() {
             TokenType::Identifier(v) => v,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "[identifier]()",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "[identifier]()",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .clone();
         self.advance();
         if !self.expect(TokenType::LeftParenthesis) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 &format!("{}()", identifier),
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let arguments = self.function_arguments();
+        let arguments = self.function_arguments()?;
         self.advance();
         if !self.expect(TokenType::RightParenthesis) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "[identifier]()",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
-        Instruction::new(
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
@@ -1094,46 +1245,46 @@ impl Parser {
                 identifier,
                 arguments,
             },
-        )
+        ))
     }
 
-    fn do_catch(&mut self) -> Instruction {
+    fn do_catch(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::Do)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "do { ... } catch { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         if !self.expect(TokenType::Indent) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "do { ... } catch { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let do_body = self.parse_section();
+        let do_body = self.parse_section()?;
         self.advance();
         if !self.expect(TokenType::Keyword(Keyword::Catch)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "do { ... } catch { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         if !self.expect(TokenType::Indent) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "do { ... } catch { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
-        let catch_body = self.parse_section();
-        Instruction::new(
+        let catch_body = self.parse_section()?;
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
@@ -1141,26 +1292,28 @@ impl Parser {
                 catch_body: Box::new(catch_body),
                 do_body: Box::new(do_body),
             },
-        )
+        ))
     }
 
-    fn class_declaration(&mut self) -> Instruction {
+    fn class_declaration(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::Class)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "class [identifier] { ... }",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         let identifier = match self.current_token_type() {
             TokenType::Identifier(v) => v,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "class [identifier] { ... }",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "class [identifier] { ... }",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .clone();
         self.advance();
@@ -1175,20 +1328,22 @@ impl Parser {
                     break;
                 }
             }
-            let instruction = self.parse_once();
-            // println!("{:?}", instruction.instruction_type);
+            let instruction = match self.parse_once() {
+                Ok(v) => v,
+                Err(e) => {
+                    self.recover(e);
+                    continue;
+                }
+            };
             match instruction.instruction_type {
                 InstructionType::FunctionDeclaration { .. } | InstructionType::VarAssign { .. } => {
                     instructions.push(instruction)
                 }
-                _ => {
-                    println!("{}", instruction.instruction_type);
-                    InvalidInstructionInClass::call(
-                        &instruction.start,
-                        &instruction.end,
-                        &self.file_data,
-                    )
-                }
+                _ => self.diagnostics.push(InvalidInstructionInClass::call(
+                    &instruction.start,
+                    &instruction.end,
+                    &self.file_data,
+                )),
             }
         }
         let body = Box::new(Instruction::new(
@@ -1197,52 +1352,54 @@ impl Parser {
             self.file_data.clone(),
             InstructionType::Section { body: instructions },
         ));
-        Instruction::new(
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
             InstructionType::ClassDeclaration { identifier, body },
-        )
+        ))
     }
 
-    fn class_instantiate(&mut self) -> Instruction {
+    fn class_instantiate(&mut self) -> Result<Instruction, Exception> {
         let start = self.current_token().unwrap().start.clone();
         if !self.expect(TokenType::Keyword(Keyword::New)) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 "new [identifier]()",
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
         let identifier = match self.current_token_type() {
             TokenType::Identifier(v) => v,
-            _ => UnexpectedToken::call(
-                self.current_token().unwrap().fetch(),
-                "new [identifier]()",
-                self.current_token_type_str().as_str(),
-            ),
+            _ => {
+                return Err(UnexpectedToken::call(
+                    self.current_token().unwrap().fetch(),
+                    "new [identifier]()",
+                    self.current_token_type_str().as_str(),
+                ))
+            }
         }
         .clone();
         self.advance();
         if !self.expect(TokenType::LeftParenthesis) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 &format!("new {}()", identifier),
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
         self.advance();
-        let constructor_arguments = self.function_arguments();
+        let constructor_arguments = self.function_arguments()?;
         self.advance();
         if !self.expect(TokenType::RightParenthesis) {
-            UnexpectedToken::call(
+            return Err(UnexpectedToken::call(
                 self.current_token().unwrap().fetch(),
                 &format!("new {}()", identifier),
                 self.current_token_type_str().as_str(),
-            )
+            ));
         }
-        Instruction::new(
+        Ok(Instruction::new(
             start,
             self.current_token().unwrap().end.clone(),
             self.file_data.clone(),
@@ -1250,31 +1407,26 @@ impl Parser {
                 identifier,
                 constructor_arguments,
             },
-        )
+        ))
     }
 
-    fn annotation(&mut self) -> Instruction {
-        match match self.current_token_type() {
-            TokenType::Annotation(v) => v,
-            _ => panic!(),
-        }
-        .clone()
-        {
+    fn annotation(&mut self, annotation: Annotation) -> Result<Instruction, Exception> {
+        match annotation {
             Annotation::DocComment(v) => {
                 let start = self.current_token().unwrap().start.clone();
                 self.advance();
-                let value = Box::new(self.parse_once());
-                Instruction::new(
+                let value = Box::new(self.parse_once()?);
+                Ok(Instruction::new(
                     start,
                     self.current_token().unwrap().end.clone(),
                     self.file_data.clone(),
                     InstructionType::DocComment { comment: v, value },
-                )
+                ))
             }
         }
     }
 
-    fn function_arguments(&mut self) -> Vec<Instruction> {
+    fn function_arguments(&mut self) -> Result<Vec<Instruction>, Exception> {
         let mut arguments = vec![];
         while self.current_token().is_some() {
             self.skip_new_line();
@@ -1282,20 +1434,22 @@ impl Parser {
                 match self.current_token_type() {
                     TokenType::RightParenthesis => break,
                     TokenType::Comma => self.advance(),
-                    _ => UnexpectedToken::call(
-                        self.current_token().unwrap().fetch(),
-                        ",",
-                        &self.current_token_type_str(),
-                    ),
+                    _ => {
+                        return Err(UnexpectedToken::call(
+                            self.current_token().unwrap().fetch(),
+                            ",",
+                            &self.current_token_type_str(),
+                        ))
+                    }
                 }
             }
             match &self.current_token().unwrap().token_type {
                 TokenType::RightParenthesis => break,
-                _ => arguments.push(self.expr()),
+                _ => arguments.push(self.expr()?),
             }
             self.advance()
         }
         self.retreat();
-        arguments
+        Ok(arguments)
     }
 }
@@ -1,61 +1,68 @@
-#![allow(clippy::cast_ref_to_mut)]
-use std::{cell::RefCell, fmt::Display, rc::Rc};
+use std::{borrow::Cow, fmt::Display, mem::size_of, rc::Rc};
 
 use crate::{
     exceptions::{
         interpreter_exceptions::{
-            IntegerDivisionByZero, InvalidBinaryOperation, InvalidType, TypeConversion,
+            IntegerDivisionByZero, IntegerOverflow, InvalidBinaryOperation, InvalidType,
+            TypeConversion,
         },
         parser_exceptions::UnexpectedToken,
         Exception,
     },
     interpreter::{
-        context::Context,
+        context::{Context, Scope},
         function::Function,
         type_hint::{Type, TypeHint},
         ClassVariable,
     },
     lexer::token::{Token, TokenType},
-    variables::VariableReference,
+    variables::{
+        gc::{Trace, Tracer},
+        HeapFull, VariableReference,
+    },
     FileData, Position,
 };
 
+/// A runtime value, errors involving it are reported at the instruction that uses it
 #[derive(Clone, Debug)]
-pub(crate) enum DataType {
+pub enum Value {
     Integer(i32),
     Float(f32),
-    String(String),
-    Function(Box<Function>),
-    Class(Box<ClassVariable>),
+    String(Rc<String>),
+    Function(Rc<Function>),
+    Class(Rc<ClassVariable>),
     Reference(VariableReference),
     Null,
 }
 
-impl DataType {
+// Values are cloned all the time so every variant has to fit in a pointer
+const _: () = assert!(size_of::<Value>() == 16);
+
+impl Value {
     pub fn data_type(&self) -> String {
         match self {
-            DataType::Integer(_) => "integer".to_string(),
-            DataType::Float(_) => "float".to_string(),
-            DataType::String(_) => "string".to_string(),
-            DataType::Null => "null".to_string(),
-            DataType::Function(_) => "function".to_string(),
-            DataType::Class(v) => v.class_name.clone(),
-            DataType::Reference(v) => format!("ref({})", (**v).data.data_type.data_type()),
+            Value::Integer(_) => "integer".to_string(),
+            Value::Float(_) => "float".to_string(),
+            Value::String(_) => "string".to_string(),
+            Value::Null => "null".to_string(),
+            Value::Function(_) => "function".to_string(),
+            Value::Class(v) => v.class_name.clone(),
+            Value::Reference(v) => format!("ref({})", v.data().data_type()),
         }
     }
 
-    pub fn is_of_type(
+    pub(crate) fn is_of_type(
         &self,
         t: &Type,
         identifier: &String,
         (start, end, file_data): (&Position, &Position, &Rc<FileData>),
     ) -> Result<bool, Exception> {
         match (&t.type_value, &self) {
-            (TypeHint::Integer, crate::parser::data::DataType::Integer(_))
-            | (TypeHint::String, crate::parser::data::DataType::String(_))
-            | (TypeHint::Float, crate::parser::data::DataType::Float(_))
+            (TypeHint::Integer, Value::Integer(_))
+            | (TypeHint::String, Value::String(_))
+            | (TypeHint::Float, Value::Float(_))
             | (TypeHint::None, _) => Ok(true),
-            (TypeHint::Class(identifier), crate::parser::data::DataType::Class(class)) => {
+            (TypeHint::Class(identifier), Value::Class(class)) => {
                 Ok(&class.class_name == identifier)
             }
             (_, Self::Null) => Ok(true),
@@ -70,509 +77,441 @@ impl DataType {
         }
     }
 
-    pub fn original(&self) -> Self {
-        match self {
-            DataType::Reference(v) => (**v).data.data_type.original(),
-            _ => self.clone(),
-        }
+    pub(crate) fn as_bool(
+        &self,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<bool, Exception> {
+        Ok(match self {
+            Value::Integer(v) => *v > 0,
+            Value::Float(v) => *v > 0.,
+            Value::String(v) => !v.is_empty(),
+            Value::Function(_) => false,
+            Value::Null => false,
+            Value::Reference(v) => v.data().as_bool((start, end, file_data))?,
+            Value::Class(v) => Context::call_function(
+                &v.context,
+                &"as_bool".to_string(),
+                vec![],
+                (start, end, file_data),
+            )?
+            .as_bool((start, end, file_data))?,
+        })
     }
-}
 
-impl From<bool> for DataType {
-    fn from(v: bool) -> Self {
-        match v {
-            true => DataType::Integer(1),
-            false => DataType::Integer(0),
+    /// Follows references until it reaches the value they point to
+    pub fn original(&self) -> Cow<'_, Value> {
+        match self {
+            Value::Reference(v) => Cow::Owned(v.data().original().into_owned()),
+            _ => Cow::Borrowed(self),
         }
     }
-    // fn into(self) -> DataType {
-    //     match self {
-    //         true => DataType::Integer(1),
-    //         false => DataType::Integer(0),
-    //     }
-    // }
-}
 
-// TODO: Maybe try to not clone these values IDK
-impl From<&Token> for DataType {
-    fn from(value: &Token) -> Self {
-        match &value.token_type {
-            TokenType::Integer(value) => DataType::Integer(*value),
-            TokenType::Float(value) => DataType::Float(*value),
-            TokenType::String(value) => DataType::String(value.clone()),
-            TokenType::Bool(value) => DataType::Integer(if *value { 1 } else { 0 }),
-            TokenType::Null => DataType::Null,
-            _ => UnexpectedToken::call(
-                value.fetch(),
-                "a data type (example: 10)",
-                value.token_type.to_string().as_str(),
-            ),
+    /// Replaces the value [`Value::original`] would return, writing through references
+    pub(crate) fn set_original(&mut self, data: Value) -> Result<(), HeapFull> {
+        match self {
+            Value::Reference(v) => v.set_original(data),
+            _ => {
+                *self = data;
+                Ok(())
+            }
         }
     }
-}
 
-impl Display for DataType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Bytes the value keeps alive besides itself, shared functions aren't counted
+    pub(crate) fn heap_size(&self) -> usize {
         match self {
-            DataType::Integer(v) => write!(f, "{}", v),
-            DataType::Float(v) => write!(f, "{}", v),
-            DataType::String(v) => write!(f, "{}", v),
-            DataType::Function(v) => write!(f, "{}", v.identifier),
-            DataType::Reference(v) => write!(f, "{}", (**v).data.data_type),
-            DataType::Null => write!(f, "null"),
-            DataType::Class(v) => write!(f, "{}", v.class_name),
+            Value::String(v) => v.capacity(),
+            Value::Class(v) => {
+                size_of::<ClassVariable>() + size_of::<Scope>() + v.class_name.capacity()
+            }
+            _ => 0,
         }
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct Data {
-    pub(crate) file_data: Rc<FileData>,
-    pub(crate) start: Position,
-    pub(crate) end: Position,
-    pub(crate) data_type: DataType,
-}
-
-#[derive(Clone, Debug)]
-pub struct DataReference {
-    pub(crate) file_data: Rc<FileData>,
-    pub(crate) start: Position,
-    pub(crate) end: Position,
-    pub(crate) original_data: Rc<RefCell<Data>>,
-}
-
-impl DataReference {
-    pub(crate) fn new(
-        file_data: Rc<FileData>,
-        start: Position,
-        end: Position,
-        original_data: Rc<RefCell<Data>>,
-    ) -> Self {
-        Self {
-            file_data,
-            start,
-            end,
-            original_data,
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        match v {
+            true => Value::Integer(1),
+            false => Value::Integer(0),
         }
     }
 }
 
-impl Data {
-    pub(crate) fn new(
-        file_data: Rc<FileData>,
-        start: Position,
-        end: Position,
-        data_type: DataType,
-    ) -> Self {
-        Self {
-            file_data,
-            start,
-            end,
-            data_type,
-        }
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::String(Rc::new(v))
     }
+}
 
-    pub fn as_bool(&self) -> Result<bool, Exception> {
-        Ok(match self.data_type.clone() {
-            DataType::Integer(v) => v > 0,
-            DataType::Float(v) => v > 0.,
-            DataType::String(v) => !v.is_empty(),
-            DataType::Function(_) => false,
-            DataType::Null => false,
-            DataType::Reference(v) => (*v).data.as_bool()?,
-            DataType::Class(mut v) => Context::call_function(
-                &mut v.context,
-                &"as_bool".to_string(),
-                vec![],
-                (&v.start, &v.end, &v.file_data),
-            )?
-            .as_bool()?,
+// TODO: Maybe try to not clone these values IDK
+impl TryFrom<&Token> for Value {
+    type Error = Exception;
+
+    fn try_from(value: &Token) -> Result<Self, Exception> {
+        Ok(match &value.token_type {
+            TokenType::Integer(value) => Value::Integer(*value),
+            TokenType::Float(value) => Value::Float(*value),
+            TokenType::String(value) => Value::from(value.clone()),
+            TokenType::Bool(value) => Value::Integer(if *value { 1 } else { 0 }),
+            TokenType::Null => Value::Null,
+            _ => {
+                return Err(UnexpectedToken::call(
+                    value.fetch(),
+                    "a data type (example: 10)",
+                    value.token_type.to_string().as_str(),
+                ))
+            }
         })
     }
+}
 
-    pub fn original(&self) -> &Data {
-        match &self.data_type {
-            DataType::Reference(v) => (*v).data.original(),
-            _ => self,
-        }
-    }
-
-    pub fn original_mut(&mut self) -> &mut Data {
-        match &self.data_type {
-            DataType::Reference(v) => unsafe {
-                &mut *((*v).data.original() as *const Data as *mut Data)
-            },
-            _ => self,
+impl Trace for Value {
+    fn trace(&self, tracer: &mut Tracer) {
+        match self {
+            Value::Function(v) => tracer.function(v),
+            Value::Class(v) => v.context.trace(tracer),
+            Value::Reference(v) => v.trace(tracer),
+            Value::Integer(_) | Value::Float(_) | Value::String(_) | Value::Null => {}
         }
     }
 }
 
-impl From<&Token> for Data {
-    fn from(v: &Token) -> Self {
-        Data::new(
-            v.file_data.clone(),
-            v.start.clone(),
-            v.end.clone(),
-            DataType::from(v),
-        )
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Integer(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::String(v) => write!(f, "{}", v),
+            Value::Function(v) => write!(f, "{}", v.identifier),
+            Value::Reference(v) => write!(f, "{}", v.data()),
+            Value::Null => write!(f, "null"),
+            Value::Class(v) => write!(f, "{}", v.class_name),
+        }
     }
 }
 
-impl Data {
+/// Operations between two values, both operands are expected to be [`Value::original`]s
+/// and `position` is the span of the operation
+impl Value {
     #[inline(always)]
-    pub fn eq(&self, other: &Data) -> Result<Data, Exception> {
-        Ok(Data::new(
-            self.file_data.clone(),
-            self.start.clone(),
-            other.end.clone(),
-            DataType::Integer(if data_eq(self, other)? { 1 } else { 0 }),
-        ))
+    pub(crate) fn eq(
+        &self,
+        other: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok(data_eq(self, other, position)?.into())
     }
 
     #[inline(always)]
-    pub fn ne(&self, other: &Data) -> Result<Data, Exception> {
-        Ok(Data::new(
-            self.file_data.clone(),
-            self.start.clone(),
-            other.end.clone(),
-            DataType::Integer(if !data_eq(self, other)? { 1 } else { 0 }),
-        ))
+    pub(crate) fn ne(
+        &self,
+        other: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok((!data_eq(self, other, position)?).into())
     }
 
     #[inline(always)]
-    pub fn gt(&self, other: &Data) -> Result<Data, Exception> {
-        Ok(Data::new(
-            self.file_data.clone(),
-            self.start.clone(),
-            other.end.clone(),
-            DataType::Integer(if data_gt(self, other)? { 1 } else { 0 }),
-        ))
+    pub(crate) fn gt(
+        &self,
+        other: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok(data_gt(self, other, position)?.into())
     }
 
     #[inline(always)]
-    pub fn geq(&self, other: &Data) -> Result<Data, Exception> {
-        Ok(Data::new(
-            self.file_data.clone(),
-            self.start.clone(),
-            other.end.clone(),
-            DataType::Integer(if data_gt(self, other)? || data_eq(self, other)? {
-                1
-            } else {
-                0
-            }),
-        ))
+    pub(crate) fn geq(
+        &self,
+        other: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok((data_gt(self, other, position)? || data_eq(self, other, position)?).into())
     }
 
     #[inline(always)]
-    pub fn lt(&self, other: &Data) -> Result<Data, Exception> {
-        Ok(Data::new(
-            self.file_data.clone(),
-            self.start.clone(),
-            other.end.clone(),
-            DataType::Integer(if !data_gt(self, other)? && !data_eq(self, other)? {
-                1
-            } else {
-                0
-            }),
-        ))
+    pub(crate) fn lt(
+        &self,
+        other: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok((!data_gt(self, other, position)? && !data_eq(self, other, position)?).into())
     }
 
     #[inline(always)]
-    pub fn leq(&self, other: &Data) -> Result<Data, Exception> {
-        Ok(Data::new(
-            self.file_data.clone(),
-            self.start.clone(),
-            other.end.clone(),
-            DataType::Integer(if !data_gt(self, other)? { 1 } else { 0 }),
-        ))
+    pub(crate) fn leq(
+        &self,
+        other: &Value,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok((!data_gt(self, other, position)?).into())
     }
 
     #[inline(always)]
     pub(crate) fn add(
-        slf: &Data,
-        rhs: &Data,
-        data1: &Data,
-        data2: &Data,
-    ) -> Result<Data, Exception> {
-        Ok(Data::new(
-            data1.file_data.clone(),
-            data1.start.clone(),
-            data2.end.clone(),
-            match (&data1.data_type, &data2.data_type) {
-                (DataType::Integer(n1), DataType::Integer(n2)) => DataType::Integer(n1 + n2),
-                (DataType::Integer(n1), DataType::Float(n2)) => DataType::Float(*n1 as f32 + n2),
-                (DataType::Integer(n1), DataType::String(n2)) => {
-                    DataType::String(format!("{}{}", n1, n2))
-                }
-                (DataType::Float(n1), DataType::Integer(n2)) => DataType::Float(n1 + *n2 as f32),
-                (DataType::Float(n1), DataType::Float(n2)) => DataType::Float(n1 + n2),
-                (DataType::String(_), _) | (_, DataType::String(_)) => DataType::String({
-                    let mut str: String = data1.data_type.to_string();
-                    str += data2.data_type.to_string().as_str();
-                    str
-                }),
-                _ => {
-                    return Err(InvalidBinaryOperation::call(
-                        &slf.start,
-                        &rhs.end,
-                        &slf.file_data,
-                        (&data1.data_type, &data2.data_type),
-                        "add",
-                    ))
-                }
-            },
-        ))
+        &self,
+        rhs: &Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok(match (self, rhs) {
+            (Value::Integer(n1), Value::Integer(n2)) => {
+                checked(n1.checked_add(*n2), (n1, "+", n2), (start, end, file_data))?
+            }
+            (Value::Integer(n1), Value::Float(n2)) => Value::Float(*n1 as f32 + n2),
+            (Value::Integer(n1), Value::String(n2)) => Value::from(format!("{}{}", n1, n2)),
+            (Value::Float(n1), Value::Integer(n2)) => Value::Float(n1 + *n2 as f32),
+            (Value::Float(n1), Value::Float(n2)) => Value::Float(n1 + n2),
+            (Value::String(_), _) | (_, Value::String(_)) => {
+                Value::from(format!("{}{}", self, rhs))
+            }
+            _ => {
+                return Err(InvalidBinaryOperation::call(
+                    start,
+                    end,
+                    file_data,
+                    (self, rhs),
+                    "add",
+                ))
+            }
+        })
     }
 
     #[inline(always)]
     pub(crate) fn sub(
-        slf: &Data,
-        rhs: &Data,
-        data1: &Data,
-        data2: &Data,
-    ) -> Result<Data, Exception> {
-        Ok(Data::new(
-            data1.file_data.clone(),
-            data1.start.clone(),
-            data2.end.clone(),
-            match (&data1.data_type, &data2.data_type) {
-                (DataType::Integer(n1), DataType::Integer(n2)) => DataType::Integer(n1 - n2),
-                (DataType::Integer(n1), DataType::Float(n2)) => DataType::Float(*n1 as f32 - n2),
-                (DataType::Float(n1), DataType::Integer(n2)) => DataType::Float(n1 - *n2 as f32),
-                (DataType::Float(n1), DataType::Float(n2)) => DataType::Float(n1 - n2),
-                _ => {
-                    return Err(InvalidBinaryOperation::call(
-                        &slf.start,
-                        &rhs.end,
-                        &slf.file_data,
-                        (&data1.data_type, &data2.data_type),
-                        "subtract",
-                    ))
-                }
-            },
-        ))
+        &self,
+        rhs: &Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok(match (self, rhs) {
+            (Value::Integer(n1), Value::Integer(n2)) => {
+                checked(n1.checked_sub(*n2), (n1, "-", n2), (start, end, file_data))?
+            }
+            (Value::Integer(n1), Value::Float(n2)) => Value::Float(*n1 as f32 - n2),
+            (Value::Float(n1), Value::Integer(n2)) => Value::Float(n1 - *n2 as f32),
+            (Value::Float(n1), Value::Float(n2)) => Value::Float(n1 - n2),
+            _ => {
+                return Err(InvalidBinaryOperation::call(
+                    start,
+                    end,
+                    file_data,
+                    (self, rhs),
+                    "subtract",
+                ))
+            }
+        })
     }
 
     #[inline(always)]
     pub(crate) fn mul(
-        slf: &Data,
-        rhs: &Data,
-        data1: &Data,
-        data2: &Data,
-    ) -> Result<Data, Exception> {
-        Ok(Data::new(
-            data1.file_data.clone(),
-            data1.start.clone(),
-            data2.end.clone(),
-            match (&data1.data_type, &data2.data_type) {
-                (DataType::Integer(n1), DataType::Integer(n2)) => DataType::Integer(n1 * n2),
-                (DataType::Integer(n1), DataType::Float(n2)) => DataType::Float(*n1 as f32 * n2),
-                (DataType::Float(n1), DataType::Integer(n2)) => DataType::Float(n1 * *n2 as f32),
-                (DataType::Float(n1), DataType::Float(n2)) => DataType::Float(n1 * n2),
-                (DataType::String(n1), DataType::Integer(n2)) => {
-                    let mut x = String::new();
-                    for _ in 0..*n2 {
-                        x = format!("{}{}", x, n1)
-                    }
-                    DataType::String(x)
-                }
-                _ => {
-                    return Err(InvalidBinaryOperation::call(
-                        &slf.start,
-                        &rhs.end,
-                        &slf.file_data,
-                        (&data1.data_type, &data2.data_type),
-                        "multiply",
-                    ))
+        &self,
+        rhs: &Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok(match (self, rhs) {
+            (Value::Integer(n1), Value::Integer(n2)) => {
+                checked(n1.checked_mul(*n2), (n1, "*", n2), (start, end, file_data))?
+            }
+            (Value::Integer(n1), Value::Float(n2)) => Value::Float(*n1 as f32 * n2),
+            (Value::Float(n1), Value::Integer(n2)) => Value::Float(n1 * *n2 as f32),
+            (Value::Float(n1), Value::Float(n2)) => Value::Float(n1 * n2),
+            (Value::String(n1), Value::Integer(n2)) => {
+                let mut x = String::new();
+                for _ in 0..*n2 {
+                    x = format!("{}{}", x, n1)
                 }
-            },
-        ))
+                Value::from(x)
+            }
+            _ => {
+                return Err(InvalidBinaryOperation::call(
+                    start,
+                    end,
+                    file_data,
+                    (self, rhs),
+                    "multiply",
+                ))
+            }
+        })
     }
 
     #[inline(always)]
     pub(crate) fn div(
-        slf: &Data,
-        rhs: &Data,
-        data1: &Data,
-        data2: &Data,
-    ) -> Result<Data, Exception> {
-        if match &data1.data_type {
-            DataType::Integer(_) => match &data2.data_type {
-                DataType::Integer(v) => v == &0,
-                _ => false,
-            },
-            _ => false,
-        } {
-            return Err(IntegerDivisionByZero::call(
-                &rhs.start,
-                &rhs.end,
-                &rhs.file_data,
-            ));
-        }
-        Ok(Data::new(
-            data1.file_data.clone(),
-            data1.start.clone(),
-            data2.end.clone(),
-            match (&data1.data_type, &data2.data_type) {
-                (DataType::Integer(n1), DataType::Integer(n2)) => DataType::Integer(n1 / n2),
-                (DataType::Integer(n1), DataType::Float(n2)) => DataType::Float(*n1 as f32 / n2),
-                (DataType::Float(n1), DataType::Integer(n2)) => DataType::Float(n1 / *n2 as f32),
-                (DataType::Float(n1), DataType::Float(n2)) => DataType::Float(n1 / n2),
-                _ => {
-                    return Err(InvalidBinaryOperation::call(
-                        &slf.start,
-                        &rhs.end,
-                        &slf.file_data,
-                        (&data1.data_type, &data2.data_type),
-                        "division",
-                    ))
-                }
-            },
-        ))
+        &self,
+        rhs: &Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok(match (self, rhs) {
+            (Value::Integer(_), Value::Integer(0)) => {
+                return Err(IntegerDivisionByZero::call(start, end, file_data))
+            }
+            (Value::Integer(n1), Value::Integer(n2)) => {
+                checked(n1.checked_div(*n2), (n1, "/", n2), (start, end, file_data))?
+            }
+            (Value::Integer(n1), Value::Float(n2)) => Value::Float(*n1 as f32 / n2),
+            (Value::Float(n1), Value::Integer(n2)) => Value::Float(n1 / *n2 as f32),
+            (Value::Float(n1), Value::Float(n2)) => Value::Float(n1 / n2),
+            _ => {
+                return Err(InvalidBinaryOperation::call(
+                    start,
+                    end,
+                    file_data,
+                    (self, rhs),
+                    "division",
+                ))
+            }
+        })
     }
 
     #[inline(always)]
     pub(crate) fn pow(
-        slf: &Data,
-        rhs: &Data,
-        data1: &Data,
-        data2: &Data,
-    ) -> Result<Data, Exception> {
-        Ok(Data::new(
-            data1.file_data.clone(),
-            data1.start.clone(),
-            data2.end.clone(),
-            match (&data1.data_type, &data2.data_type) {
-                (DataType::Integer(n1), DataType::Integer(n2)) => {
-                    DataType::Integer(n1.pow((*n2) as u32))
-                }
-                (DataType::Integer(n1), DataType::Float(n2)) => {
-                    DataType::Float((*n1 as f32).powf(*n2))
-                }
-                (DataType::Float(n1), DataType::Integer(n2)) => DataType::Float(n1.powi(*n2)),
-                (DataType::Float(n1), DataType::Float(n2)) => DataType::Float(n1.powf(*n2)),
-                (DataType::String(n1), DataType::Integer(n2)) => {
-                    let mut x = n1.clone();
-                    for _ in 0..*n2 - 1 {
-                        x = format!("{}{}", x, x)
-                    }
-                    DataType::String(x)
-                }
-                _ => {
-                    return Err(InvalidBinaryOperation::call(
-                        &slf.start,
-                        &rhs.end,
-                        &slf.file_data,
-                        (&data1.data_type, &data2.data_type),
-                        "multiply",
-                    ))
+        &self,
+        rhs: &Value,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        Ok(match (self, rhs) {
+            // A negative power is a fraction
+            (Value::Integer(n1), Value::Integer(n2)) if *n2 < 0 => {
+                Value::Float((*n1 as f32).powi(*n2))
+            }
+            (Value::Integer(n1), Value::Integer(n2)) => checked(
+                n1.checked_pow(*n2 as u32),
+                (n1, "^", n2),
+                (start, end, file_data),
+            )?,
+            (Value::Integer(n1), Value::Float(n2)) => Value::Float((*n1 as f32).powf(*n2)),
+            (Value::Float(n1), Value::Integer(n2)) => Value::Float(n1.powi(*n2)),
+            (Value::Float(n1), Value::Float(n2)) => Value::Float(n1.powf(*n2)),
+            (Value::String(n1), Value::Integer(n2)) => {
+                let mut x = n1.to_string();
+                for _ in 0..*n2 - 1 {
+                    x = format!("{}{}", x, x)
                 }
-            },
-        ))
+                Value::from(x)
+            }
+            _ => {
+                return Err(InvalidBinaryOperation::call(
+                    start,
+                    end,
+                    file_data,
+                    (self, rhs),
+                    "multiply",
+                ))
+            }
+        })
     }
 }
 
-impl Data {
-    pub(crate) fn null(file_data: Rc<FileData>, start: Position, end: Position) -> Data {
-        Self {
-            file_data,
+/// The result of integer arithmetic, or an error if it overflowed
+fn checked(
+    result: Option<i32>,
+    (n1, operator, n2): (&i32, &str, &i32),
+    (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+) -> Result<Value, Exception> {
+    match result {
+        Some(v) => Ok(Value::Integer(v)),
+        None => Err(IntegerOverflow::call(
             start,
             end,
-            data_type: DataType::Null,
-        }
-    }
-
-    pub(crate) fn null_zero(file_data: Rc<FileData>) -> Data {
-        Self::null(file_data, Position::new(0), Position::new(0))
+            file_data,
+            &format!("{} {} {}", n1, operator, n2),
+        )),
     }
+}
 
+impl Value {
     #[inline(always)]
-    pub fn convert_to(&self, convert_type: &Type) -> Result<Data, Exception> {
-        // TODO: bruh
-        let data = self.data_type.original();
-        Ok(Data::new(
-            self.file_data.clone(),
-            self.start.clone(),
-            convert_type.end.clone(),
-            match (&convert_type.type_value, &data.original()) {
-                (TypeHint::Integer, DataType::Integer(_)) => self.data_type.clone(),
-                (TypeHint::Integer, DataType::Float(i)) => DataType::Integer(*i as i32),
-                (TypeHint::Integer, DataType::String(i)) => DataType::Integer(match i.parse() {
-                    Ok(v) => v,
-                    Err(_) => return convert_exception(self, convert_type),
-                }),
-                (TypeHint::Integer, DataType::Null) => {
-                    return convert_exception(self, convert_type)
-                }
-                (TypeHint::String, DataType::Integer(i)) => DataType::String(i.to_string()),
-                (TypeHint::String, DataType::Float(i)) => DataType::String(i.to_string()),
-                (TypeHint::String, DataType::String(_)) => self.data_type.clone(),
-                (TypeHint::String, DataType::Null) => DataType::String("null".to_string()),
-                (TypeHint::Float, DataType::Integer(i)) => DataType::Float(*i as f32),
-                (TypeHint::Float, DataType::Float(_)) => self.data_type.clone(),
-                (TypeHint::Float, DataType::String(i)) => DataType::Float(match i.parse() {
-                    Ok(v) => v,
-                    Err(_) => return convert_exception(self, convert_type),
-                }),
-                (TypeHint::Float, DataType::Null) => return convert_exception(self, convert_type),
-                _ => return convert_exception(self, convert_type),
-            },
-        ))
+    pub(crate) fn convert_to(
+        &self,
+        convert_type: &Type,
+        position: (&Position, &Position, &Rc<FileData>),
+    ) -> Result<Value, Exception> {
+        let data = self.original();
+        Ok(match (&convert_type.type_value, data.as_ref()) {
+            (TypeHint::Integer, Value::Integer(_)) => data.into_owned(),
+            (TypeHint::Integer, Value::Float(i)) => Value::Integer(*i as i32),
+            (TypeHint::Integer, Value::String(i)) => Value::Integer(match i.parse() {
+                Ok(v) => v,
+                Err(_) => return convert_exception(&data, convert_type, position),
+            }),
+            (TypeHint::String, Value::Integer(i)) => Value::from(i.to_string()),
+            (TypeHint::String, Value::Float(i)) => Value::from(i.to_string()),
+            (TypeHint::String, Value::String(_)) => data.into_owned(),
+            (TypeHint::String, Value::Null) => Value::from("null".to_string()),
+            (TypeHint::Float, Value::Integer(i)) => Value::Float(*i as f32),
+            (TypeHint::Float, Value::Float(_)) => data.into_owned(),
+            (TypeHint::Float, Value::String(i)) => Value::Float(match i.parse() {
+                Ok(v) => v,
+                Err(_) => return convert_exception(&data, convert_type, position),
+            }),
+            _ => return convert_exception(&data, convert_type, position),
+        })
     }
 }
 
-fn convert_exception(data: &Data, convert_type: &Type) -> Result<Data, Exception> {
+fn convert_exception(
+    data: &Value,
+    convert_type: &Type,
+    (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+) -> Result<Value, Exception> {
     Err(TypeConversion::call(
-        &data.start,
-        &convert_type.end,
-        &convert_type.file_data,
-        &data.data_type.original(),
+        start,
+        end,
+        file_data,
+        data,
         convert_type.type_value.to_string().as_str(),
     ))
 }
 
-fn data_eq(n1: &Data, n2: &Data) -> Result<bool, Exception> {
-    Ok(match (n1.data_type.original(), &n2.data_type.original()) {
-        (DataType::Integer(v1), DataType::Integer(v2)) => &v1 == v2,
-        (DataType::Integer(v1), DataType::Float(v2)) => v1 as f32 == *v2,
-        (DataType::Integer(v1), DataType::String(v2)) => &v1.to_string() == v2,
-        (DataType::Float(v1), DataType::Integer(v2)) => v1 == (*v2) as f32,
-        (DataType::Float(v1), DataType::Float(v2)) => &v1 == v2,
-        (DataType::Float(v1), DataType::String(v2)) => &v1.to_string() == v2,
-        (DataType::String(v1), DataType::Integer(v2)) => v1 == v2.to_string(),
-        (DataType::String(v1), DataType::Float(v2)) => v1 == v2.to_string(),
-        (DataType::String(v1), DataType::String(v2)) => &v1 == v2,
-        (DataType::Null, DataType::Null) => true,
-        (DataType::Class(_), DataType::Null) | (DataType::Null, DataType::Class(_)) => false,
-        (DataType::Class(mut v), _) => Context::call_override_class_fn(
-            &mut v.context,
+fn data_eq(
+    n1: &Value,
+    n2: &Value,
+    position: (&Position, &Position, &Rc<FileData>),
+) -> Result<bool, Exception> {
+    Ok(match (n1.original().as_ref(), n2.original().as_ref()) {
+        (Value::Integer(v1), Value::Integer(v2)) => v1 == v2,
+        (Value::Integer(v1), Value::Float(v2)) => *v1 as f32 == *v2,
+        (Value::Integer(v1), Value::String(v2)) => v1.to_string() == **v2,
+        (Value::Float(v1), Value::Integer(v2)) => *v1 == (*v2) as f32,
+        (Value::Float(v1), Value::Float(v2)) => v1 == v2,
+        (Value::Float(v1), Value::String(v2)) => v1.to_string() == **v2,
+        (Value::String(v1), Value::Integer(v2)) => **v1 == v2.to_string(),
+        (Value::String(v1), Value::Float(v2)) => **v1 == v2.to_string(),
+        (Value::String(v1), Value::String(v2)) => v1 == v2,
+        (Value::Null, Value::Null) => true,
+        (Value::Class(_), Value::Null) | (Value::Null, Value::Class(_)) => false,
+        (Value::Class(v), _) => Context::call_override_class_fn(
+            &v.context,
             &"equals".to_string(),
             vec![n2.clone()],
-            (&n1.start, &n2.end, &n1.file_data),
+            position,
         )?
-        .as_bool()?,
+        .as_bool(position)?,
         _ => false,
     })
 }
 
-fn data_gt(n1: &Data, n2: &Data) -> Result<bool, Exception> {
-    Ok(match (n1.data_type.original(), &n2.data_type.original()) {
-        (DataType::Integer(v1), DataType::Integer(v2)) => &v1 > v2,
-        (DataType::Integer(v1), DataType::Float(v2)) => (v1) as f32 > *v2,
-        (DataType::Integer(v1), DataType::String(v2)) => v1 > (v2.len() as i32),
-        (DataType::Float(v1), DataType::Integer(v2)) => v1 > (*v2) as f32,
-        (DataType::Float(v1), DataType::Float(v2)) => &v1 > v2,
-        (DataType::Float(v1), DataType::String(v2)) => v1 > (v2.len() as f32),
-        (DataType::String(v1), DataType::Integer(v2)) => &(v1.len() as i32) > v2,
-        (DataType::String(v1), DataType::Float(v2)) => &(v1.len() as f32) == v2,
-        (DataType::String(v1), DataType::String(v2)) => v1.len() > v2.len(),
-        (DataType::Class(mut v), _) => Context::call_fn_no_std(
-            &mut v.context,
+fn data_gt(
+    n1: &Value,
+    n2: &Value,
+    position: (&Position, &Position, &Rc<FileData>),
+) -> Result<bool, Exception> {
+    Ok(match (n1.original().as_ref(), n2.original().as_ref()) {
+        (Value::Integer(v1), Value::Integer(v2)) => v1 > v2,
+        (Value::Integer(v1), Value::Float(v2)) => (*v1) as f32 > *v2,
+        (Value::Integer(v1), Value::String(v2)) => *v1 > (v2.chars().count() as i32),
+        (Value::Float(v1), Value::Integer(v2)) => *v1 > (*v2) as f32,
+        (Value::Float(v1), Value::Float(v2)) => v1 > v2,
+        (Value::Float(v1), Value::String(v2)) => *v1 > (v2.chars().count() as f32),
+        (Value::String(v1), Value::Integer(v2)) => (v1.chars().count() as i32) > *v2,
+        (Value::String(v1), Value::Float(v2)) => (v1.chars().count() as f32) == *v2,
+        (Value::String(v1), Value::String(v2)) => v1.chars().count() > v2.chars().count(),
+        (Value::Class(v), _) => Context::call_fn_no_std(
+            &v.context,
             &"greater".to_string(),
             vec![n2.clone()],
-            (&n1.start, &n2.end, &n1.file_data),
+            position,
         )?
-        .as_bool()?,
+        .as_bool(position)?,
         _ => false,
     })
 }
@@ -0,0 +1,271 @@
+//! Resolves variable accesses to `(depth, slot)` pairs before a file runs.
+//!
+//! Scoping is dynamic, a function's scope is a child of whatever called it, so only
+//! variables declared in the same function (or class body, or file) can be resolved.
+//! Anything else is left as `None` and looked up by name at runtime, just like a slot
+//! that turns out to be empty when the access runs.
+
+use std::collections::HashMap;
+
+use crate::interpreter::instructions::{Instruction, InstructionType};
+
+/// Where a variable lives relative to the scope accessing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Slot {
+    /// How many parents up the scope is
+    pub(crate) depth: usize,
+    /// Index into the scope's slots
+    pub(crate) index: usize,
+}
+
+/// Whether the top level of a file gets its own slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RootScope {
+    /// The file runs in a fresh scope, like the file an engine starts with
+    Owned,
+    /// The file runs in a scope other code declares into as well, like an imported file,
+    /// top level declarations are only reachable by name
+    Shared,
+}
+
+struct Scope {
+    /// `None` for names the resolver knows about but that don't have a slot
+    names: HashMap<String, Option<usize>>,
+    slots: usize,
+    has_slots: bool,
+    /// Something declares variables in this scope that the resolver can't see, like a `use`
+    opaque: bool,
+}
+
+impl Scope {
+    fn new(has_slots: bool) -> Self {
+        Self {
+            names: HashMap::new(),
+            slots: 0,
+            has_slots,
+            opaque: false,
+        }
+    }
+
+    fn declare(&mut self, identifier: &str) -> Option<usize> {
+        let index = match self.names.get(identifier) {
+            _ if !self.has_slots => None,
+            Some(Some(index)) => Some(*index),
+            _ => {
+                self.slots += 1;
+                Some(self.slots - 1)
+            }
+        };
+        self.names.insert(identifier.to_string(), index);
+        index
+    }
+}
+
+pub(crate) struct Resolver {
+    /// Scopes of the function being resolved, innermost last
+    scopes: Vec<Scope>,
+    /// Name of the function being resolved, `None` where a return can't be a tail call
+    function: Option<String>,
+}
+
+impl Resolver {
+    pub(crate) fn resolve(instructions: &mut [Instruction], root_scope: RootScope) {
+        let mut resolver = Resolver {
+            scopes: vec![Scope::new(root_scope == RootScope::Owned)],
+            function: None,
+        };
+        resolver.block(instructions);
+    }
+
+    /// Resolves `f` with none of the current scopes visible,
+    /// used wherever the runtime parent of a scope isn't known ahead of time
+    fn frame(&mut self, root: Scope, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.scopes, vec![root]);
+        f(self);
+        self.scopes = outer;
+    }
+
+    /// Resolves `f` as part of `function`
+    fn in_function(&mut self, function: Option<String>, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::replace(&mut self.function, function);
+        f(self);
+        self.function = outer;
+    }
+
+    /// Mirrors the order `run_with_instructions` runs a block in
+    fn block(&mut self, instructions: &mut [Instruction]) {
+        for instruction in instructions.iter() {
+            if matches!(
+                instruction.instruction_type,
+                InstructionType::UseStatement { .. }
+            ) {
+                self.scopes.last_mut().unwrap().opaque = true
+            }
+        }
+        for instruction in instructions.iter_mut() {
+            if matches!(
+                instruction.instruction_type,
+                InstructionType::ClassDeclaration { .. }
+            ) {
+                self.instruction(instruction)
+            }
+        }
+        for instruction in instructions.iter_mut() {
+            if matches!(
+                instruction.instruction_type,
+                InstructionType::FunctionDeclaration { .. }
+            ) {
+                self.instruction(instruction)
+            }
+        }
+        for instruction in instructions.iter_mut() {
+            if !matches!(
+                instruction.instruction_type,
+                InstructionType::UseStatement { .. }
+                    | InstructionType::ClassDeclaration { .. }
+                    | InstructionType::FunctionDeclaration { .. }
+            ) {
+                self.instruction(instruction)
+            }
+        }
+    }
+
+    fn instruction(&mut self, instruction: &mut Instruction) {
+        match &mut instruction.instruction_type {
+            InstructionType::VarAccess { identifier, slot } => *slot = self.lookup(identifier),
+            InstructionType::VarUpdate {
+                identifier,
+                data,
+                slot,
+            } => {
+                self.instruction(data);
+                *slot = self.lookup(identifier)
+            }
+            InstructionType::VarAssign {
+                identifier,
+                data,
+                slot,
+                ..
+            } => {
+                self.instruction(data);
+                *slot = self.declare(identifier)
+            }
+            InstructionType::Section { body } => {
+                self.scopes.push(Scope::new(true));
+                self.block(body);
+                self.scopes.pop();
+            }
+            InstructionType::FunctionDeclaration {
+                identifier,
+                body,
+                arguments,
+            } => {
+                for (_, _, default_value) in arguments.iter_mut() {
+                    if let Some(v) = default_value {
+                        self.instruction(v)
+                    }
+                }
+                self.scopes
+                    .last_mut()
+                    .unwrap()
+                    .names
+                    .insert(identifier.clone(), None);
+                // `Function::call` puts the n-th argument in the n-th slot
+                let mut scope = Scope::new(true);
+                for (index, (identifier, _, _)) in arguments.iter().enumerate() {
+                    scope.names.insert(identifier.clone(), Some(index));
+                }
+                scope.slots = arguments.len();
+                self.in_function(Some(identifier.clone()), |slf| {
+                    slf.frame(scope, |slf| slf.instruction(body))
+                })
+            }
+            InstructionType::ClassDeclaration { body, .. } => self.in_function(None, |slf| {
+                slf.frame(Scope::new(false), |slf| {
+                    if let InstructionType::Section { body } = &mut body.instruction_type {
+                        for instruction in body.iter_mut() {
+                            match &mut instruction.instruction_type {
+                                InstructionType::VarAssign { data, .. } => slf.instruction(data),
+                                _ => slf.instruction(instruction),
+                            }
+                        }
+                    }
+                })
+            }),
+            // The tail call would leave the context (or `do` block) before the function runs
+            InstructionType::InContextOf { context_of, run } => {
+                self.instruction(context_of);
+                self.in_function(None, |slf| {
+                    slf.frame(Scope::new(false), |slf| slf.instruction(run))
+                })
+            }
+            InstructionType::BinaryOperation { left, right, .. } => {
+                self.instruction(left);
+                self.instruction(right)
+            }
+            InstructionType::IfStatement {
+                condition,
+                body,
+                else_value,
+            } => {
+                if let Some(v) = condition {
+                    self.instruction(v)
+                }
+                self.instruction(body);
+                if let Some(v) = else_value {
+                    self.instruction(v)
+                }
+            }
+            InstructionType::WhileStatement { condition, body } => {
+                self.instruction(condition);
+                self.instruction(body)
+            }
+            InstructionType::DoCatch {
+                do_body,
+                catch_body,
+            } => {
+                self.in_function(None, |slf| slf.instruction(do_body));
+                self.instruction(catch_body)
+            }
+            InstructionType::FunctionCall { arguments, .. }
+            | InstructionType::ClassInstantiation {
+                constructor_arguments: arguments,
+                ..
+            } => {
+                for argument in arguments.iter_mut() {
+                    self.instruction(argument)
+                }
+            }
+            InstructionType::UnaryOperation { value, .. }
+            | InstructionType::BreakStatement { value }
+            | InstructionType::As { value, .. }
+            | InstructionType::DocComment { value, .. } => self.instruction(value),
+            InstructionType::ReturnStatement { value, tail_call } => {
+                self.instruction(value);
+                *tail_call = matches!(
+                    &value.instruction_type,
+                    InstructionType::FunctionCall { identifier, .. }
+                        if self.function.as_ref() == Some(identifier)
+                )
+            }
+            InstructionType::UseStatement { .. }
+            | InstructionType::Data(_)
+            | InstructionType::Pass => {}
+        }
+    }
+
+    fn declare(&mut self, identifier: &str) -> Option<usize> {
+        self.scopes.last_mut().unwrap().declare(identifier)
+    }
+
+    fn lookup(&self, identifier: &str) -> Option<Slot> {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            match scope.names.get(identifier) {
+                Some(index) => return index.map(|index| Slot { depth, index }),
+                None if scope.opaque => return None,
+                None => {}
+            }
+        }
+        None
+    }
+}
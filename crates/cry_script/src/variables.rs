@@ -1,138 +1,248 @@
-use colored::Colorize;
 use slotmap::{DefaultKey, SlotMap};
 use std::{
-    env,
-    fmt::Display,
+    cell::RefCell,
+    fmt::{Debug, Display},
     mem::size_of,
-    ops::{Deref, DerefMut},
+    rc::{Rc, Weak},
 };
 
-use crate::{exceptions::Exception, interpreter::type_hint::Type, parser::data::Data};
+use crate::{
+    engine::GcStats,
+    exceptions::{interpreter_exceptions::OutOfMemory, Exception},
+    interpreter::{context::Scope, type_hint::Type},
+    parser::data::Value,
+    FileData, Position,
+};
+
+use self::gc::{Trace, Tracer, MIN_ALLOCATIONS_BETWEEN_COLLECTIONS, MIN_TRACKED_SCOPES};
+
+pub(crate) mod gc;
 
-const SIZE_OF_DATA: usize = size_of::<Data>();
 pub struct Variables {
     map: SlotMap<DefaultKey, Variable>,
-    size: usize,
+    /// Set through [`Limits::max_memory`](crate::engine::Limits::max_memory)
+    max_memory: Option<usize>,
+    /// Bytes taken up by the variables in `map`, see [`Variable::size`]
     used: usize,
+    scopes: Vec<Weak<RefCell<Scope>>>,
+    scopes_after_prune: usize,
+    allocations_until_collection: usize,
+    stats: GcStats,
+    /// Handed to every declared variable's [`Pin`]
+    this: Weak<RefCell<Variables>>,
 }
 
 impl Variables {
-    pub(crate) fn new() -> Self {
-        let size = string_to_bytes(
-            env::var("CRYSCRIPT_VAR_MEMORY").unwrap_or_else(|_| "MB1024".to_string()),
-        );
-        Self {
-            map: SlotMap::with_capacity(size / SIZE_OF_DATA + 1),
-            size,
-            used: 0,
-        }
-    }
-    pub(crate) fn access_variable(&mut self, key: DefaultKey) -> VariableReference {
-        VariableReference::new(self.map.get_mut(key).unwrap())
+    pub(crate) fn new(max_memory: Option<usize>) -> Rc<RefCell<Self>> {
+        Rc::new_cyclic(|this| {
+            RefCell::new(Self {
+                map: SlotMap::new(),
+                max_memory,
+                used: 0,
+                scopes: vec![],
+                scopes_after_prune: MIN_TRACKED_SCOPES,
+                allocations_until_collection: MIN_ALLOCATIONS_BETWEEN_COLLECTIONS,
+                stats: GcStats::default(),
+                this: this.clone(),
+            })
+        })
     }
-    pub(crate) fn declare_variable(&mut self, var: Variable) -> DefaultKey {
-        self.used += SIZE_OF_DATA;
-        if self.used > (self.size as f32 * 0.8) as usize {
-            println!("{} {}", self.used, (self.size as f32 * 0.8));
-            self.garbage_collector()
-        } else if self.used > self.size {
-            panic!("too much memory")
+
+    pub(crate) fn stats(&self) -> GcStats {
+        GcStats {
+            live_variables: self.map.len() as u64,
+            live_bytes: self.used as u64,
+            ..self.stats
         }
-        self.map.insert(var)
     }
 
-    pub(crate) fn update_variable(&mut self, key: DefaultKey, data: Data) {
-        self.map.get_mut(key).unwrap().data = data
+    pub(crate) fn access_variable(
+        variables: &Rc<RefCell<Variables>>,
+        key: DefaultKey,
+    ) -> VariableReference {
+        VariableReference::new(variables.clone(), key)
     }
 
-    pub(crate) fn remove_variable(&mut self, key: DefaultKey) {
-        self.used -= SIZE_OF_DATA;
-        drop(self.map.remove(key).unwrap());
+    pub(crate) fn declare_variable(&mut self, mut var: Variable) -> Result<DefaultKey, HeapFull> {
+        self.allocations_until_collection = self.allocations_until_collection.saturating_sub(1);
+        if self.allocations_until_collection == 0 {
+            self.collect_garbage()
+        }
+        self.reserve(var.size())?;
+        Ok(self.map.insert_with_key(|key| {
+            var.pin = Rc::new(Pin {
+                variables: self.this.clone(),
+                key,
+            });
+            var
+        }))
     }
 
-    pub(crate) fn garbage_collector(&mut self) {
-        let mut v = vec![];
-        for (key, var) in self.map.iter() {
-            if var.used_places == 0 {
-                v.push(key)
-            }
-        }
-        for i in v {
-            self.remove_variable(i)
+    /// Replaces the data of a variable, accounting for the size difference
+    fn set_data(&mut self, key: DefaultKey, data: Value) -> Result<(), HeapFull> {
+        let (old, new) = (self.map[key].data.heap_size(), data.heap_size());
+        if new > old {
+            self.reserve(new - old)?
+        } else {
+            self.used -= old - new
         }
-        if self.used > self.size {
-            Exception::new(
-                "Err: Memory overflow, consider increasing maximum memory of the program"
-                    .red()
-                    .bold()
-                    .to_string(),
-            )
-            .run()
+        self.map[key].data = data;
+        Ok(())
+    }
+
+    /// Counts `bytes` as used, collecting garbage first if they don't fit
+    fn reserve(&mut self, bytes: usize) -> Result<(), HeapFull> {
+        if let Some(max_memory) = self.max_memory {
+            if self.used + bytes > max_memory {
+                self.collect_garbage();
+                if self.used + bytes > max_memory {
+                    return Err(HeapFull { max_memory });
+                }
+            }
         }
+        self.used += bytes;
+        Ok(())
+    }
+
+    /// Drops every variable, class instances keep their context alive
+    /// through the variables they are stored in so this is what breaks
+    /// those cycles once a run is over
+    pub(crate) fn clear(variables: &Rc<RefCell<Variables>>) {
+        let map = std::mem::take(&mut variables.borrow_mut().map);
+        variables.borrow_mut().used = 0;
+        drop(map)
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct VariableReference {
-    pub(crate) reference: *mut Variable,
+impl Debug for Variables {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Variables")
+            .field("map", &self.map)
+            .field("used", &self.used)
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+/// Shared by a variable and every reference to it, so a reference fits in a single pointer
+#[derive(Debug, Default)]
+pub(crate) struct Pin {
+    variables: Weak<RefCell<Variables>>,
+    key: DefaultKey,
+}
+
+/// A handle to a variable slot, the variable won't be garbage collected while a handle to it exists
+#[derive(Clone)]
+pub struct VariableReference {
+    pin: Rc<Pin>,
 }
 
 impl VariableReference {
-    pub(crate) fn new(reference: *mut Variable) -> Self {
-        let mut v = Self { reference };
-        (*v).used_places += 1;
-        v
+    fn new(variables: Rc<RefCell<Variables>>, key: DefaultKey) -> Self {
+        let pin = variables.borrow().map[key].pin.clone();
+        Self { pin }
     }
-}
 
-impl Deref for VariableReference {
-    type Target = Variable;
+    fn variables(&self) -> Rc<RefCell<Variables>> {
+        self.pin.variables.upgrade().unwrap()
+    }
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*self.reference }
+    pub(crate) fn with_variable<R>(&self, f: impl FnOnce(&Variable) -> R) -> R {
+        f(&self.variables().borrow().map[self.pin.key])
     }
-}
 
-impl DerefMut for VariableReference {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *self.reference }
+    pub(crate) fn data(&self) -> Value {
+        self.variables().borrow().map[self.pin.key].data.clone()
+    }
+
+    /// Replaces the data at the end of the reference chain
+    pub(crate) fn set_original(&self, data: Value) -> Result<(), HeapFull> {
+        let variables = self.variables();
+        let next = match &variables.borrow().map[self.pin.key].data {
+            Value::Reference(v) => Some(v.clone()),
+            _ => None,
+        };
+        match next {
+            Some(v) => v.set_original(data),
+            None => variables.borrow_mut().set_data(self.pin.key, data),
+        }
     }
 }
 
-impl Drop for VariableReference {
-    fn drop(&mut self) {
-        (*self).used_places -= 1
+impl Trace for VariableReference {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.variable(self.pin.key)
     }
 }
 
-impl Clone for VariableReference {
-    fn clone(&self) -> Self {
-        Self::new(self.reference)
+impl Debug for VariableReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VariableReference")
+            .field("key", &self.pin.key)
+            .finish()
     }
 }
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone)]
 pub(crate) struct VariableKey(pub String);
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub(crate) struct Variable {
-    pub(crate) data: Data,
+    pub(crate) data: Value,
     pub(crate) type_hint: Type,
     pub(crate) is_final: bool,
     pub(crate) identifier: String,
-    pub(crate) used_places: usize,
+    /// Every [`VariableReference`] holds a clone of this
+    pin: Rc<Pin>,
 }
 
 impl Variable {
-    pub(crate) fn new(data: Data, type_hint: Type, is_final: bool, identifier: String) -> Self {
+    pub(crate) fn new(data: Value, type_hint: Type, is_final: bool, identifier: String) -> Self {
         Self {
             data,
             type_hint,
             is_final,
-            used_places: 0,
             identifier,
+            // Bound to a key once the variable is declared
+            pin: Rc::default(),
         }
     }
+
+    pub(crate) fn used_places(&self) -> usize {
+        Rc::strong_count(&self.pin) - 1
+    }
+
+    /// Bytes counted against [`Limits::max_memory`](crate::engine::Limits::max_memory)
+    pub(crate) fn size(&self) -> usize {
+        size_of::<Variable>() + self.identifier.capacity() + self.data.heap_size()
+    }
+}
+
+/// The variables would take up more than the host allows, even after collecting garbage
+#[derive(Debug)]
+pub(crate) struct HeapFull {
+    max_memory: usize,
+}
+
+impl HeapFull {
+    pub(crate) fn at(
+        self,
+        (start, end, file_data): (&Position, &Position, &Rc<FileData>),
+    ) -> Exception {
+        OutOfMemory::call(start, end, file_data, self.max_memory)
+    }
+}
+
+/// A cloned variable is a new variable, references to the original don't carry over
+impl Clone for Variable {
+    fn clone(&self) -> Self {
+        Self::new(
+            self.data.clone(),
+            self.type_hint.clone(),
+            self.is_final,
+            self.identifier.clone(),
+        )
+    }
 }
 
 impl Display for Variable {
@@ -140,24 +250,7 @@ impl Display for Variable {
         write!(
             f,
             "data: {} type: {} final: {}",
-            self.data.data_type, self.type_hint.type_value, self.is_final
+            self.data, self.type_hint.type_value, self.is_final
         )
     }
 }
-
-fn string_to_bytes(string: String) -> usize {
-    let split = string.split_at(2);
-    let prefix = split.0;
-    let number = split.1.parse::<usize>().unwrap();
-    number
-        * match prefix {
-            "BT" => 1,
-            "KB" => 1_000,
-            "MB" => 1_000_000,
-            "GB" => 1_000_000_000,
-            "TB" => 1_000_000_000_000,
-            "PB" => 1_000_000_000_000_000,
-            "EB" => 1_000_000_000_000_000_000,
-            _ => panic!("invalid memory type"),
-        }
-}
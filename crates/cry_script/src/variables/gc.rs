@@ -0,0 +1,245 @@
+//! Mark and sweep collector for [`Variables`].
+//!
+//! Everything the interpreter hands around is reference counted, so instead of scanning
+//! the Rust stack roots are found the way CPython's cycle collector finds them: a node
+//! with more handles than the heap itself holds to it is referenced from the outside
+//! (a running function, a value being computed, the host) and everything reachable
+//! from such a node survives the collection.
+
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    rc::{Rc, Weak},
+};
+
+use slotmap::DefaultKey;
+
+use crate::interpreter::{context::Scope, function::Function, Class};
+
+use super::Variables;
+
+/// Implemented by everything that can hold on to a variable, a scope or a value containing either
+pub(crate) trait Trace {
+    /// Reports every node directly held by `self` to `tracer`
+    fn trace(&self, tracer: &mut Tracer);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Node {
+    Variable(DefaultKey),
+    Scope(usize),
+    Function(usize),
+    Class(usize),
+}
+
+#[derive(PartialEq, Eq)]
+enum Phase {
+    /// Counting how many handles to each node are held by other nodes
+    Count,
+    /// Marking everything reachable from the roots
+    Mark,
+}
+
+pub(crate) struct Tracer {
+    phase: Phase,
+    internal: HashMap<Node, usize>,
+    marked: HashSet<Node>,
+    pending: Vec<Node>,
+    scopes: HashMap<usize, Rc<RefCell<Scope>>>,
+    functions: HashMap<usize, Rc<Function>>,
+    classes: HashMap<usize, Rc<Class>>,
+    /// Set when a scope is mutably borrowed, its contents can't be traced so nothing is freed
+    busy: bool,
+}
+
+impl Tracer {
+    fn new(scopes: Vec<Rc<RefCell<Scope>>>) -> Self {
+        Self {
+            phase: Phase::Count,
+            internal: HashMap::new(),
+            marked: HashSet::new(),
+            pending: vec![],
+            scopes: scopes
+                .into_iter()
+                .map(|v| (Rc::as_ptr(&v) as usize, v))
+                .collect(),
+            functions: HashMap::new(),
+            classes: HashMap::new(),
+            busy: false,
+        }
+    }
+
+    /// A handle keeping the variable alive, such as a reference
+    pub(crate) fn variable(&mut self, key: DefaultKey) {
+        self.edge(Node::Variable(key), true)
+    }
+
+    /// A scope's own variable, scopes only know the key so it doesn't count as a handle
+    pub(crate) fn declared_variable(&mut self, key: DefaultKey) {
+        self.edge(Node::Variable(key), false)
+    }
+
+    pub(crate) fn scope(&mut self, scope: &Rc<RefCell<Scope>>) {
+        let id = Rc::as_ptr(scope) as usize;
+        if let Entry::Vacant(entry) = self.scopes.entry(id) {
+            entry.insert(scope.clone());
+            self.pending.push(Node::Scope(id));
+        }
+        self.edge(Node::Scope(id), true)
+    }
+
+    pub(crate) fn function(&mut self, function: &Rc<Function>) {
+        let id = Rc::as_ptr(function) as usize;
+        if let Entry::Vacant(entry) = self.functions.entry(id) {
+            entry.insert(function.clone());
+            self.pending.push(Node::Function(id));
+        }
+        self.edge(Node::Function(id), true)
+    }
+
+    pub(crate) fn class(&mut self, class: &Rc<Class>) {
+        let id = Rc::as_ptr(class) as usize;
+        if let Entry::Vacant(entry) = self.classes.entry(id) {
+            entry.insert(class.clone());
+            self.pending.push(Node::Class(id));
+        }
+        self.edge(Node::Class(id), true)
+    }
+
+    fn edge(&mut self, node: Node, is_handle: bool) {
+        match self.phase {
+            Phase::Count => {
+                if is_handle {
+                    *self.internal.entry(node).or_insert(0) += 1
+                }
+            }
+            Phase::Mark => self.mark(node),
+        }
+    }
+
+    fn mark(&mut self, node: Node) {
+        if self.marked.insert(node) {
+            self.pending.push(node)
+        }
+    }
+
+    fn internal(&self, node: Node) -> usize {
+        self.internal.get(&node).copied().unwrap_or(0)
+    }
+
+    /// Traces pending nodes until there are none left
+    fn drain(&mut self, variables: &Variables) {
+        while let Some(node) = self.pending.pop() {
+            match node {
+                Node::Variable(key) => {
+                    if let Some(v) = variables.map.get(key) {
+                        v.data.trace(self)
+                    }
+                }
+                Node::Scope(id) => {
+                    let scope = self.scopes[&id].clone();
+                    match scope.try_borrow() {
+                        Ok(v) => v.trace(self),
+                        Err(_) => self.busy = true,
+                    };
+                }
+                Node::Function(id) => self.functions[&id].clone().trace(self),
+                Node::Class(id) => self.classes[&id].clone().trace(self),
+            }
+        }
+    }
+}
+
+impl Variables {
+    /// Remembers a scope so the collector can find it, the heap doesn't keep it alive
+    pub(crate) fn track_scope(&mut self, scope: &Rc<RefCell<Scope>>) {
+        if self.scopes.len() >= self.scopes_after_prune * 2 {
+            self.scopes.retain(|v| v.strong_count() > 0);
+            self.scopes_after_prune = self.scopes.len().max(MIN_TRACKED_SCOPES);
+        }
+        self.scopes.push(Rc::downgrade(scope))
+    }
+
+    /// Frees every variable and scope that can't be reached from outside the heap
+    pub(crate) fn collect_garbage(&mut self) {
+        self.scopes.retain(|v| v.strong_count() > 0);
+        let scopes: Vec<_> = self.scopes.iter().filter_map(Weak::upgrade).collect();
+
+        let mut tracer = Tracer::new(scopes);
+        tracer.pending = self.map.keys().map(Node::Variable).collect();
+        tracer
+            .pending
+            .extend(tracer.scopes.keys().map(|v| Node::Scope(*v)));
+        tracer.drain(self);
+        if tracer.busy {
+            return;
+        }
+
+        // Anything with a handle the heap can't account for is a root,
+        // the tracer holds one handle to each scope, function and class itself
+        tracer.phase = Phase::Mark;
+        let mut roots = vec![];
+        for (key, variable) in self.map.iter() {
+            let node = Node::Variable(key);
+            if variable.used_places() > tracer.internal(node) {
+                roots.push(node)
+            }
+        }
+        for (id, v) in tracer.scopes.iter() {
+            let node = Node::Scope(*id);
+            if Rc::strong_count(v) - 1 > tracer.internal(node) {
+                roots.push(node)
+            }
+        }
+        for (id, v) in tracer.functions.iter() {
+            let node = Node::Function(*id);
+            if Rc::strong_count(v) - 1 > tracer.internal(node) {
+                roots.push(node)
+            }
+        }
+        for (id, v) in tracer.classes.iter() {
+            let node = Node::Class(*id);
+            if Rc::strong_count(v) - 1 > tracer.internal(node) {
+                roots.push(node)
+            }
+        }
+        for root in roots {
+            tracer.mark(root)
+        }
+        tracer.drain(self);
+        if tracer.busy {
+            return;
+        }
+
+        let unreachable: Vec<_> = self
+            .map
+            .keys()
+            .filter(|v| !tracer.marked.contains(&Node::Variable(*v)))
+            .collect();
+        let mut freed = Vec::with_capacity(unreachable.len());
+        for key in unreachable {
+            freed.push(self.map.remove(key));
+        }
+        // Dead scopes can still point at each other through their parents and classes
+        for (id, scope) in tracer.scopes.iter() {
+            if !tracer.marked.contains(&Node::Scope(*id)) {
+                scope.borrow_mut().release()
+            }
+        }
+
+        let freed_bytes: usize = freed.iter().flatten().map(|v| v.size()).sum();
+        self.used -= freed_bytes;
+        self.stats.collections += 1;
+        self.stats.freed_variables += freed.len() as u64;
+        self.stats.freed_bytes += freed_bytes as u64;
+        self.allocations_until_collection = self.map.len().max(MIN_ALLOCATIONS_BETWEEN_COLLECTIONS);
+        drop(tracer);
+        drop(freed)
+    }
+}
+
+/// The heap is allowed to grow by at least this many variables between collections
+pub(super) const MIN_ALLOCATIONS_BETWEEN_COLLECTIONS: usize = 10_000;
+
+/// Dead scopes are only pruned from the tracking list once it has grown past this
+pub(super) const MIN_TRACKED_SCOPES: usize = 1024;
@@ -0,0 +1,229 @@
+use crate::{
+    exceptions::{interpreter_exceptions::ReturnFromRoot, Exception},
+    interpreter::{
+        context::Context,
+        function::{Function, FunctionBody},
+        Class,
+    },
+    parser::data::Value,
+    Flow,
+};
+
+use self::chunk::{Chunk, Op};
+
+pub(crate) mod chunk;
+pub(crate) mod compiler;
+
+/// Where to continue when a catchable error is raised inside a `do` body
+struct Handler {
+    catch: usize,
+    stack: usize,
+    context: Context,
+}
+
+struct Vm<'a> {
+    chunk: &'a Chunk,
+    context: Context,
+    stack: Vec<Value>,
+    handlers: Vec<Handler>,
+    ip: usize,
+}
+
+/// Runs `chunk` in `context`, the same way [`Instruction::visit`](crate::interpreter::instructions::Instruction::visit)
+/// would run the instructions it was compiled from
+pub(crate) fn run(chunk: &Chunk, context: &Context) -> Result<Flow, Exception> {
+    let mut vm = Vm {
+        chunk,
+        context: context.clone(),
+        stack: vec![],
+        handlers: vec![],
+        ip: 0,
+    };
+    loop {
+        match vm.execute() {
+            Ok(v) => return Ok(v),
+            Err(e) => match vm.handlers.pop() {
+                Some(handler) if e.kind().is_catchable() => {
+                    vm.stack.truncate(handler.stack);
+                    vm.context = handler.context;
+                    vm.ip = handler.catch;
+                }
+                _ => return Err(e),
+            },
+        }
+    }
+}
+
+impl<'a> Vm<'a> {
+    fn pop(&mut self) -> Value {
+        self.stack.pop().unwrap()
+    }
+
+    fn execute(&mut self) -> Result<Flow, Exception> {
+        let chunk = self.chunk;
+        let file_data = &chunk.file_data;
+        while let Some(op) = chunk.ops.get(self.ip) {
+            let (start, end) = &chunk.spans[self.ip];
+            let position = (start, end, file_data);
+            self.context.runtime.borrow_mut().budget.step(position)?;
+            self.ip += 1;
+            match op {
+                Op::Null => self.stack.push(Value::Null),
+                Op::Constant(i) => self.stack.push(chunk.constants[*i as usize].clone()),
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Access(identifier, slot) => {
+                    let reference = self.context.access_variable_in_slot(
+                        &chunk.identifiers[*identifier as usize],
+                        *slot,
+                        position,
+                    )?;
+                    self.stack.push(Value::Reference(reference))
+                }
+                Op::Assign {
+                    identifier,
+                    type_hint,
+                    is_final,
+                    slot,
+                } => {
+                    let data = self.pop();
+                    self.context.assign_variable(
+                        chunk.identifiers[*identifier as usize].clone(),
+                        data,
+                        chunk.types[*type_hint as usize].clone(),
+                        *is_final,
+                        *slot,
+                        position,
+                    )?;
+                    self.stack.push(Value::Null)
+                }
+                Op::Update(identifier, slot) => {
+                    let data = self.pop();
+                    Context::update_variable_in_slot(
+                        &self.context,
+                        &chunk.identifiers[*identifier as usize],
+                        *slot,
+                        data,
+                        position,
+                    )?;
+                    self.stack.push(Value::Null)
+                }
+                Op::Binary(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(operator.evaluate(left, &right, position)?)
+                }
+                Op::Unary(operator) => {
+                    let value = self.pop();
+                    self.stack
+                        .push(operator.operate(&value.original(), position)?)
+                }
+                Op::As(convert_type) => {
+                    let value = self.pop();
+                    self.stack
+                        .push(value.convert_to(&chunk.types[*convert_type as usize], position)?)
+                }
+                Op::Call {
+                    identifier,
+                    arguments,
+                } => {
+                    let arguments = self.stack.split_off(self.stack.len() - *arguments as usize);
+                    self.stack.push(Context::call_function(
+                        &self.context,
+                        &chunk.identifiers[*identifier as usize],
+                        arguments,
+                        position,
+                    )?)
+                }
+                Op::New { class, arguments } => {
+                    let arguments = self.stack.split_off(self.stack.len() - *arguments as usize);
+                    self.stack.push(Context::new_class(
+                        &self.context,
+                        &chunk.identifiers[*class as usize],
+                        arguments,
+                        position,
+                    )?)
+                }
+                Op::Jump(target) => self.ip = *target,
+                Op::JumpIfFalse(target) => {
+                    if !self.pop().as_bool(position)? {
+                        self.ip = *target
+                    }
+                }
+                Op::EnterScope => self.context = Context::new(&self.context, file_data.clone()),
+                Op::ExitScope => self.context = self.context.parent().unwrap(),
+                Op::Use(file_path) => {
+                    match Context::use_file(
+                        &self.context,
+                        &chunk.identifiers[*file_path as usize],
+                        position,
+                    )? {
+                        v @ (Flow::Return(_) | Flow::TailCall(..)) => return Ok(v),
+                        v => self.stack.push(v.unwrap()),
+                    }
+                }
+                Op::DeclareFunction(i) => {
+                    let prototype = &chunk.functions[*i as usize];
+                    self.context.declare_function(Function::new(
+                        &prototype.arguments,
+                        FunctionBody::Bytecode(prototype.body.clone()),
+                        start.clone(),
+                        end.clone(),
+                        prototype.identifier.clone(),
+                        &self.context,
+                    )?)?
+                }
+                Op::DeclareClass(i) => {
+                    let prototype = &chunk.classes[*i as usize];
+                    let class = Class::new(
+                        prototype.identifier.clone(),
+                        (start.clone(), end.clone(), file_data.clone()),
+                        prototype.body.clone(),
+                        &self.context,
+                    )?;
+                    self.context.declare_class(class);
+                    self.stack.push(Value::Null)
+                }
+                Op::InContextOf(i) => {
+                    let target = self.pop();
+                    let run_chunk = &chunk.chunks[*i as usize];
+                    match Context::run_in_context_of(&self.context, &target, position, |v| {
+                        run(run_chunk, v)
+                    })? {
+                        v @ (Flow::Return(_) | Flow::TailCall(..)) => return Ok(v),
+                        v => self.stack.push(v.unwrap()),
+                    }
+                }
+                Op::CheckReturn => {
+                    if self.context.depth() == 0 {
+                        return Err(ReturnFromRoot::call(start, end, file_data));
+                    }
+                }
+                Op::Return => return Ok(Flow::Return(self.pop())),
+                Op::TailCall {
+                    identifier,
+                    arguments,
+                } => {
+                    let arguments = self.stack.split_off(self.stack.len() - *arguments as usize);
+                    return Context::tail_call(
+                        &self.context,
+                        &chunk.identifiers[*identifier as usize],
+                        arguments,
+                        position,
+                    );
+                }
+                Op::Try(catch) => self.handlers.push(Handler {
+                    catch: *catch,
+                    stack: self.stack.len(),
+                    context: self.context.clone(),
+                }),
+                Op::EndTry => {
+                    self.handlers.pop();
+                }
+                Op::Step => self.context.runtime.borrow_mut().budget.step(position)?,
+            }
+        }
+        Ok(Flow::Evaluate(self.pop()))
+    }
+}
@@ -0,0 +1,118 @@
+use std::rc::Rc;
+
+use crate::{
+    interpreter::{
+        instructions::{binary_op::BinaryOperator, unary_op::UnaryOperator, Instruction},
+        type_hint::Type,
+    },
+    parser::data::Value,
+    resolver::Slot,
+    FileData, Position,
+};
+
+/// Index into one of a [`Chunk`]'s tables
+pub(crate) type Index = u32;
+
+#[derive(Debug, Clone)]
+pub(crate) enum Op {
+    /// Pushes a null
+    Null,
+    /// Pushes a copy of a constant
+    Constant(Index),
+    Pop,
+    /// Pushes a reference to a variable
+    Access(Index, Option<Slot>),
+    /// Pops the value and declares a variable with it, pushes a null
+    Assign {
+        identifier: Index,
+        type_hint: Index,
+        is_final: bool,
+        slot: Option<usize>,
+    },
+    /// Pops the value and assigns it to an existing variable, pushes a null
+    Update(Index, Option<Slot>),
+    /// Pops the right then the left operand and pushes the result
+    Binary(BinaryOperator),
+    Unary(UnaryOperator),
+    As(Index),
+    /// Pops the arguments, last one on top, and pushes the return value
+    Call {
+        identifier: Index,
+        arguments: u32,
+    },
+    /// Pops the constructor arguments, last one on top, and pushes the instance
+    New {
+        class: Index,
+        arguments: u32,
+    },
+    Jump(usize),
+    /// Pops the condition and jumps if it's false
+    JumpIfFalse(usize),
+    EnterScope,
+    ExitScope,
+    /// Pushes the value of the imported file
+    Use(Index),
+    DeclareFunction(Index),
+    /// Pushes a null
+    DeclareClass(Index),
+    /// Pops the class instance and pushes the value of the chunk run in its scope
+    InContextOf(Index),
+    /// Errors if there is no scope to return from
+    CheckReturn,
+    /// Pops the value and returns it from the chunk
+    Return,
+    /// Pops the arguments like [`Op::Call`] and returns the call, see [`crate::Flow::TailCall`]
+    TailCall {
+        identifier: Index,
+        arguments: u32,
+    },
+    /// Catches catchable errors until the matching [`Op::EndTry`] by jumping to the catch body
+    Try(usize),
+    EndTry,
+    /// Charges a step to the budget, used on loop back-edges
+    Step,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FunctionPrototype {
+    pub(crate) identifier: String,
+    pub(crate) arguments: Vec<(String, Type, Option<Instruction>)>,
+    pub(crate) body: Rc<Chunk>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ClassPrototype {
+    pub(crate) identifier: String,
+    pub(crate) body: Vec<Instruction>,
+}
+
+/// Compiled bytecode of a file, function body or expression
+#[derive(Debug, Clone)]
+pub(crate) struct Chunk {
+    pub(crate) ops: Vec<Op>,
+    /// Source span of every op, indexed like `ops`
+    pub(crate) spans: Vec<(Position, Position)>,
+    pub(crate) file_data: Rc<FileData>,
+    pub(crate) identifiers: Vec<String>,
+    pub(crate) constants: Vec<Value>,
+    pub(crate) types: Vec<Type>,
+    pub(crate) functions: Vec<FunctionPrototype>,
+    pub(crate) classes: Vec<ClassPrototype>,
+    pub(crate) chunks: Vec<Rc<Chunk>>,
+}
+
+impl Chunk {
+    pub(crate) fn new(file_data: Rc<FileData>) -> Self {
+        Self {
+            ops: vec![],
+            spans: vec![],
+            file_data,
+            identifiers: vec![],
+            constants: vec![],
+            types: vec![],
+            functions: vec![],
+            classes: vec![],
+            chunks: vec![],
+        }
+    }
+}
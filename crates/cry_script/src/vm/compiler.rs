@@ -0,0 +1,336 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    interpreter::instructions::{Instruction, InstructionType},
+    FileData, Position,
+};
+
+use super::chunk::{Chunk, ClassPrototype, FunctionPrototype, Index, Op};
+
+/// Compiles the instruction tree to a [`Chunk`], blocks are laid out in the same
+/// order `run_with_instructions` would run them in
+pub(crate) struct Compiler {
+    chunk: Chunk,
+    identifiers: HashMap<String, Index>,
+}
+
+impl Compiler {
+    fn new(file_data: Rc<FileData>) -> Self {
+        Self {
+            chunk: Chunk::new(file_data),
+            identifiers: HashMap::new(),
+        }
+    }
+
+    /// Compiles the top level of a file, which runs in the scope it's given
+    pub(crate) fn compile_file(instructions: &[Instruction], file_data: Rc<FileData>) -> Chunk {
+        let mut compiler = Compiler::new(file_data);
+        compiler.block(instructions);
+        compiler.chunk
+    }
+
+    /// Compiles a single instruction, such as a function body
+    pub(crate) fn compile_expression(instruction: &Instruction) -> Chunk {
+        let mut compiler = Compiler::new(instruction.file_data.clone());
+        compiler.instruction(instruction);
+        compiler.chunk
+    }
+
+    fn emit(&mut self, op: Op, instruction: &Instruction) -> usize {
+        self.emit_at(op, &instruction.start, &instruction.end)
+    }
+
+    fn emit_at(&mut self, op: Op, start: &Position, end: &Position) -> usize {
+        self.chunk.ops.push(op);
+        self.chunk.spans.push((start.clone(), end.clone()));
+        self.chunk.ops.len() - 1
+    }
+
+    /// Points the jump at `index` to the next op
+    fn patch(&mut self, index: usize) {
+        let target = self.chunk.ops.len();
+        match &mut self.chunk.ops[index] {
+            Op::Jump(v) | Op::JumpIfFalse(v) | Op::Try(v) => *v = target,
+            _ => unreachable!(),
+        }
+    }
+
+    fn identifier(&mut self, identifier: &str) -> Index {
+        if let Some(v) = self.identifiers.get(identifier) {
+            return *v;
+        }
+        self.chunk.identifiers.push(identifier.to_string());
+        let index = (self.chunk.identifiers.len() - 1) as Index;
+        self.identifiers.insert(identifier.to_string(), index);
+        index
+    }
+
+    fn push<T>(table: &mut Vec<T>, value: T) -> Index {
+        table.push(value);
+        (table.len() - 1) as Index
+    }
+
+    fn block(&mut self, instructions: &[Instruction]) {
+        for instruction in instructions {
+            if let InstructionType::UseStatement { .. } = instruction.instruction_type {
+                self.instruction(instruction);
+                self.emit(Op::Pop, instruction);
+            }
+        }
+        for instruction in instructions {
+            if let InstructionType::ClassDeclaration { .. } = instruction.instruction_type {
+                self.instruction(instruction);
+                self.emit(Op::Pop, instruction);
+            }
+        }
+        for instruction in instructions {
+            if let InstructionType::FunctionDeclaration { .. } = instruction.instruction_type {
+                self.function_declaration(instruction);
+            }
+        }
+
+        let mut has_value = false;
+        for instruction in instructions {
+            if matches!(
+                instruction.instruction_type,
+                InstructionType::UseStatement { .. } | InstructionType::FunctionDeclaration { .. }
+            ) {
+                continue;
+            }
+            if has_value {
+                self.emit(Op::Pop, instruction);
+            }
+            self.instruction(instruction);
+            has_value = true;
+        }
+        if !has_value {
+            match instructions.last() {
+                Some(v) => self.emit(Op::Null, v),
+                None => self.emit_at(Op::Null, &Position::new(0), &Position::new(0)),
+            };
+        }
+    }
+
+    fn function_declaration(&mut self, instruction: &Instruction) {
+        if let InstructionType::FunctionDeclaration {
+            identifier,
+            body,
+            arguments,
+        } = &instruction.instruction_type
+        {
+            let prototype = FunctionPrototype {
+                identifier: identifier.clone(),
+                arguments: arguments.clone(),
+                body: Rc::new(Compiler::compile_expression(body)),
+            };
+            let index = Compiler::push(&mut self.chunk.functions, prototype);
+            self.emit(Op::DeclareFunction(index), instruction);
+        }
+    }
+
+    /// Compiles `instruction` so that it leaves exactly one value on the stack
+    fn instruction(&mut self, instruction: &Instruction) {
+        match &instruction.instruction_type {
+            InstructionType::Data(v) => {
+                let index = Compiler::push(&mut self.chunk.constants, v.clone());
+                self.emit(Op::Constant(index), instruction);
+            }
+            InstructionType::Pass => {
+                self.emit(Op::Null, instruction);
+            }
+            InstructionType::BinaryOperation {
+                left,
+                right,
+                operator,
+            } => {
+                self.instruction(left);
+                self.instruction(right);
+                self.emit(Op::Binary(operator.clone()), instruction);
+            }
+            InstructionType::UnaryOperation { value, operator } => {
+                self.instruction(value);
+                self.emit(Op::Unary(operator.clone()), instruction);
+            }
+            InstructionType::As {
+                convert_type,
+                value,
+            } => {
+                self.instruction(value);
+                let index = Compiler::push(&mut self.chunk.types, convert_type.clone());
+                self.emit(Op::As(index), instruction);
+            }
+            InstructionType::UseStatement { file_path } => {
+                let index = self.identifier(file_path);
+                self.emit(Op::Use(index), instruction);
+            }
+            InstructionType::VarAccess { identifier, slot } => {
+                let index = self.identifier(identifier);
+                self.emit(Op::Access(index, *slot), instruction);
+            }
+            InstructionType::VarAssign {
+                identifier,
+                data,
+                type_hint,
+                is_final,
+                slot,
+            } => {
+                self.instruction(data);
+                let identifier = self.identifier(identifier);
+                let type_hint = Compiler::push(&mut self.chunk.types, type_hint.clone());
+                self.emit(
+                    Op::Assign {
+                        identifier,
+                        type_hint,
+                        is_final: *is_final,
+                        slot: *slot,
+                    },
+                    instruction,
+                );
+            }
+            InstructionType::VarUpdate {
+                identifier,
+                data,
+                slot,
+            } => {
+                self.instruction(data);
+                let index = self.identifier(identifier);
+                self.emit(Op::Update(index, *slot), instruction);
+            }
+            InstructionType::IfStatement {
+                condition,
+                body,
+                else_value,
+            } => match condition {
+                None => self.instruction(body),
+                Some(condition) => {
+                    self.instruction(condition);
+                    let to_else = self.emit(Op::JumpIfFalse(0), instruction);
+                    self.instruction(body);
+                    let to_end = self.emit(Op::Jump(0), instruction);
+                    self.patch(to_else);
+                    match else_value {
+                        Some(v) => self.instruction(v),
+                        None => {
+                            self.emit(Op::Null, instruction);
+                        }
+                    }
+                    self.patch(to_end);
+                }
+            },
+            InstructionType::WhileStatement { condition, body } => {
+                // The loop evaluates to the value of its last iteration
+                self.emit(Op::Null, instruction);
+                let start = self.chunk.ops.len();
+                self.instruction(condition);
+                let to_end = self.emit(Op::JumpIfFalse(0), instruction);
+                self.emit(Op::Pop, instruction);
+                self.instruction(body);
+                self.emit(Op::Step, instruction);
+                self.emit(Op::Jump(start), instruction);
+                self.patch(to_end);
+            }
+            InstructionType::FunctionDeclaration { .. } => {
+                self.function_declaration(instruction);
+                self.emit(Op::Null, instruction);
+            }
+            InstructionType::FunctionCall {
+                identifier,
+                arguments,
+            } => {
+                for argument in arguments {
+                    self.instruction(argument);
+                }
+                let identifier = self.identifier(identifier);
+                self.emit(
+                    Op::Call {
+                        identifier,
+                        arguments: arguments.len() as u32,
+                    },
+                    instruction,
+                );
+            }
+            InstructionType::Section { body } => {
+                self.emit(Op::EnterScope, instruction);
+                self.block(body);
+                self.emit(Op::ExitScope, instruction);
+            }
+            InstructionType::ReturnStatement { value, tail_call } => {
+                self.emit(Op::CheckReturn, instruction);
+                match &value.instruction_type {
+                    InstructionType::FunctionCall {
+                        identifier,
+                        arguments,
+                    } if *tail_call => {
+                        for argument in arguments {
+                            self.instruction(argument);
+                        }
+                        let identifier = self.identifier(identifier);
+                        self.emit(
+                            Op::TailCall {
+                                identifier,
+                                arguments: arguments.len() as u32,
+                            },
+                            instruction,
+                        );
+                    }
+                    _ => {
+                        self.instruction(value);
+                        self.emit(Op::Return, instruction);
+                    }
+                }
+            }
+            // The block a break is in evaluates to its value, same as in the tree walker
+            InstructionType::BreakStatement { value } => {
+                self.emit(Op::CheckReturn, instruction);
+                self.instruction(value);
+            }
+            InstructionType::DoCatch {
+                do_body,
+                catch_body,
+            } => {
+                let to_catch = self.emit(Op::Try(0), instruction);
+                self.instruction(do_body);
+                self.emit(Op::EndTry, instruction);
+                let to_end = self.emit(Op::Jump(0), instruction);
+                self.patch(to_catch);
+                self.instruction(catch_body);
+                self.patch(to_end);
+            }
+            InstructionType::ClassDeclaration { identifier, body } => {
+                let body = match &body.instruction_type {
+                    InstructionType::Section { body } => body.clone(),
+                    _ => panic!(),
+                };
+                let prototype = ClassPrototype {
+                    identifier: identifier.clone(),
+                    body,
+                };
+                let index = Compiler::push(&mut self.chunk.classes, prototype);
+                self.emit(Op::DeclareClass(index), instruction);
+            }
+            InstructionType::ClassInstantiation {
+                identifier,
+                constructor_arguments,
+            } => {
+                for argument in constructor_arguments {
+                    self.instruction(argument);
+                }
+                let class = self.identifier(identifier);
+                self.emit(
+                    Op::New {
+                        class,
+                        arguments: constructor_arguments.len() as u32,
+                    },
+                    instruction,
+                );
+            }
+            InstructionType::InContextOf { context_of, run } => {
+                self.instruction(context_of);
+                let chunk = Rc::new(Compiler::compile_expression(run));
+                let index = Compiler::push(&mut self.chunk.chunks, chunk);
+                self.emit(Op::InContextOf(index), instruction);
+            }
+            InstructionType::DocComment { value, .. } => self.instruction(value),
+        }
+    }
+}
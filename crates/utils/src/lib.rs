@@ -3,6 +3,10 @@ use std::{rc::Rc, cell::RefCell};
 pub trait CharUtils {
     fn is_alphabetic(&self) -> bool;
     fn is_number(&self) -> bool;
+    /// Whether an identifier can start with the character, letters of any script can
+    fn is_identifier_start(&self) -> bool;
+    /// Whether the character can be part of an identifier after its first character
+    fn is_identifier_continue(&self) -> bool;
 }
 
 impl CharUtils for char {
@@ -15,55 +19,176 @@ impl CharUtils for char {
     fn is_alphabetic(&self) -> bool {
         matches!(self, 'a'..='z' | 'A'..='Z')
     }
+
+    #[inline(always)]
+    fn is_identifier_start(&self) -> bool {
+        unicode_ident::is_xid_start(*self)
+    }
+
+    #[inline(always)]
+    fn is_identifier_continue(&self) -> bool {
+        unicode_ident::is_xid_continue(*self)
+    }
 }
 
-pub trait StringUtils {
-    fn line_at(&self, idx: usize) -> Option<usize>;
-    fn start_of_line(&self, index: usize) -> Option<usize>;
-    fn start_of_line_or(&self, index: usize) -> usize;
+/// Where every line of a source starts and ends, built once so positions can be turned
+/// into lines and columns without rescanning the source
+///
+/// Indices count characters, not bytes, like the positions of the lexer do.
+/// `\n` and `\r\n` both end a line.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct SourceMap {
+    line_starts: Vec<usize>,
+    /// Index of the line break ending every line, or the length of the source for the last one
+    line_ends: Vec<usize>,
+    /// Byte range of every line without its line break, to slice the source
+    line_bytes: Vec<(usize, usize)>,
+    length: usize,
 }
 
-impl StringUtils for String {
-    fn line_at(&self, idx: usize) -> Option<usize> {
-        let mut current_index = 0;
-        for (line_number, line) in self.lines().enumerate() {
-            current_index += line.len() + 1;
-            // println!("{} {}", current_index, idx);
-            if current_index > idx {
-                return Some(line_number);
+impl SourceMap {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut line_ends = vec![];
+        let mut line_bytes = vec![];
+        let mut line_byte_start = 0;
+        let mut previous = None;
+        let mut length = 0;
+        for (index, (byte, character)) in source.char_indices().enumerate() {
+            if character == '\n' {
+                let carriage_return = usize::from(previous == Some('\r'));
+                line_ends.push(index - carriage_return);
+                line_bytes.push((line_byte_start, byte - carriage_return));
+                line_starts.push(index + 1);
+                line_byte_start = byte + 1;
             }
+            previous = Some(character);
+            length = index + 1;
         }
-        if current_index == idx {
-            return Some(0)
+        line_ends.push(length);
+        line_bytes.push((line_byte_start, source.len()));
+        Self {
+            line_starts,
+            line_ends,
+            line_bytes,
+            length,
         }
-        None
     }
 
-    fn start_of_line(&self, index: usize) -> Option<usize> {
-        let mut current_index = 0;
-        for (current_line, line) in self.lines().enumerate() {
-            if current_line == index {
-                return Some(current_index);
-            }
+    /// The line, starting at 0, of the character at `index`, indices past the end are on the last line
+    pub fn line_at(&self, index: usize) -> usize {
+        self.line_starts.partition_point(|v| *v <= index) - 1
+    }
 
-            current_index += line.len() as i32 as usize;
-            current_index += 1; // new line
-        }
-        None
+    /// The index of the first character of `line`
+    pub fn start_of_line(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
     }
 
-    fn start_of_line_or(&self, index: usize) -> usize {
-        let mut current_index = 0;
-        for (current_line, line) in self.lines().enumerate() {
-            if current_line == index {
-                return current_index;
-            }
+    /// The index after the last character of `line`, its line break isn't part of it
+    pub fn end_of_line(&self, line: usize) -> Option<usize> {
+        self.line_ends.get(line).copied()
+    }
 
-            current_index += line.len() as i32 as usize;
-            current_index += if cfg!(target = "windows") { 2 } else { 1 }; // new line
+    /// The index of the first character of `line`, or the length of the source when there's no such line
+    pub fn start_of_line_or(&self, line: usize) -> usize {
+        self.start_of_line(line).unwrap_or(self.length)
+    }
+
+    /// The line and column of `index`, both start at 0
+    pub fn location(&self, index: usize) -> (usize, usize) {
+        let line = self.line_at(index);
+        (line, index - self.line_starts[line])
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The text of `line` in `source`, which has to be the source the map was built from
+    pub fn line<'a>(&self, source: &'a str, line: usize) -> &'a str {
+        let (start, end) = self.line_bytes[line];
+        &source[start..end]
+    }
+}
+
+/// How many characters have to be inserted, removed, replaced or swapped with
+/// their neighbour to turn `a` into `b`
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // distances[i][j] is the distance between the first i characters of a and the first j of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let replace = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = replace
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
         }
-        current_index
     }
+    distances[a.len()][b.len()]
+}
+
+#[test]
+fn string_edit_distance() {
+    assert_eq!(edit_distance("", ""), 0);
+    assert_eq!(edit_distance("print", "print"), 0);
+    assert_eq!(edit_distance("pritn", "print"), 1);
+    assert_eq!(edit_distance("Piont", "Point"), 1);
+    assert_eq!(edit_distance("prin", "print"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn source_map_locations() {
+    let map = SourceMap::new("aé\r\n\nbç€d\n");
+    assert_eq!(map.line_count(), 4);
+    assert_eq!(map.location(0), (0, 0));
+    assert_eq!(map.location(1), (0, 1));
+    assert_eq!(map.location(3), (0, 3));
+    assert_eq!(map.location(4), (1, 0));
+    assert_eq!(map.location(7), (2, 2));
+    assert_eq!(map.location(100), (3, 90));
+    assert_eq!(map.end_of_line(0), Some(2));
+    assert_eq!(map.start_of_line_or(5), 10);
+}
+
+#[test]
+fn source_map_lines() {
+    let source = "aé\r\n\nbç€d\nend";
+    let map = SourceMap::new(source);
+    assert_eq!(map.line(source, 0), "aé");
+    assert_eq!(map.line(source, 1), "");
+    assert_eq!(map.line(source, 2), "bç€d");
+    assert_eq!(map.line(source, 3), "end");
+}
+
+#[test]
+fn char_utils_is_identifier() {
+    assert!('a'.is_identifier_start());
+    assert!('æ'.is_identifier_start());
+    assert!('名'.is_identifier_start());
+    assert!('λ'.is_identifier_start());
+    assert!(!'_'.is_identifier_start());
+    assert!(!'1'.is_identifier_start());
+    assert!(!'€'.is_identifier_start());
+    assert!('_'.is_identifier_continue());
+    assert!('1'.is_identifier_continue());
+    assert!('é'.is_identifier_continue());
+    assert!(!'-'.is_identifier_continue());
+    assert!(!' '.is_identifier_continue());
 }
 
 #[test]
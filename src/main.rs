@@ -1,23 +1,227 @@
-use std::{
-    env::set_current_dir,
-    fs::{self},
+use std::{env, process, time::Duration, time::Instant};
+
+use colored::Colorize;
+use cry_script::{
+    engine::{Backend, Capabilities, Engine, FileSystemAccess, Limits},
+    exceptions::{Exception, ExceptionKind},
+    lints::{Lint, LintLevel},
+    parser::data::Value,
+    Returnable,
 };
 
-use cry_script::run;
+mod repl;
+
+const USAGE: &str = "\
+Usage: cry <command> [options]
+
+Commands:
+    run <file> [-- args...]   Runs a script, arguments after -- are passed to it
+    check <file>              Parses a script without running it
+    eval <source>             Runs source code and prints the value it evaluates to
+    repl                      Starts an interactive session, :help lists its commands
+
+Options:
+    --time                    Prints how long the script took
+    --color, --no-color       Forces colored output on or off
+    --max-steps <n>           Stops the script after n instructions
+    --time-limit <ms>         Stops the script after ms milliseconds
+    --max-memory <bytes>      Limits how much memory the script's variables may take up
+    --sandbox                 Denies file system access, stdin and exiting the process
+    --root <dir>              Only allows file system access inside dir
+    --bytecode                Runs the script on the bytecode backend
+    --no-optimize             Skips constant folding and dead code elimination
+    --seed <n>                Makes std_rand return the same numbers on every run
+    --gc-stats                Prints garbage collector statistics
+    --json                    Prints errors and warnings as JSON, one object per line
+    -A, --allow <lint>        Doesn't report the lint
+    -W, --warn <lint>         Reports the lint as a warning, the default
+    -D, --deny <lint>         Reports the lint as an error and doesn't run the file
+    -h, --help                Prints this message
+
+Lints:
+    unused_variables, unused_imports, shadowing, unreachable_code, duplicate_methods
+    warnings                  every lint
+
+Exit codes:
+    0    the script ran successfully
+    1    the script failed
+    2    the command line was invalid
+    n    the script called exit(n)";
+
+enum Command {
+    Run,
+    Check,
+    Eval,
+    Repl,
+}
 
 fn main() {
-    // let iterations = 1;
-    // let mut number = 0.0;
-    // for _ in 0..iterations {
-    //     number += run("script.cry") as f64 / 1_000_000.
-    // }
-    // println!(
-    //     "Executed in {} milliseconds",
-    //     number / iterations as f64
-    // )
-
-    println!(
-        "Executed in {} milliseconds",
-        run("script.cry") as f64 / 1_000_000.
-    )
+    let mut args = env::args().skip(1);
+    let command = match args.next().as_deref() {
+        Some("run") => Command::Run,
+        Some("check") => Command::Check,
+        Some("eval") => Command::Eval,
+        Some("repl") => Command::Repl,
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return;
+        }
+        Some(v) => usage_error(&format!("unknown command {}", v)),
+        None => usage_error("expected a command"),
+    };
+
+    let mut target = None;
+    let mut limits = Limits::default();
+    let mut capabilities = Capabilities::default();
+    let mut print_time = false;
+    let mut print_gc_stats = false;
+    let mut json = false;
+    let mut backend = Backend::default();
+    let mut optimize = true;
+    let mut script_args = vec![];
+    let mut lint_levels = vec![];
+    let mut seed = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--" => {
+                script_args = args.by_ref().collect();
+                break;
+            }
+            "--max-steps" => limits.max_steps = Some(parse_flag(&arg, args.next())),
+            "--max-memory" => limits.max_memory = Some(parse_flag(&arg, args.next()) as usize),
+            "--time-limit" => {
+                limits.time_limit = Some(Duration::from_millis(parse_flag(&arg, args.next())))
+            }
+            "--sandbox" => capabilities = Capabilities::sandboxed(),
+            "--time" => print_time = true,
+            "--color" => colored::control::set_override(true),
+            "--no-color" => colored::control::set_override(false),
+            "--gc-stats" => print_gc_stats = true,
+            "--json" => json = true,
+            "--bytecode" => backend = Backend::Bytecode,
+            "--no-optimize" => optimize = false,
+            "--seed" => seed = Some(parse_flag(&arg, args.next())),
+            "-A" | "--allow" => lint_levels.push((lint_flag(&arg, args.next()), LintLevel::Allow)),
+            "-W" | "--warn" => lint_levels.push((lint_flag(&arg, args.next()), LintLevel::Warn)),
+            "-D" | "--deny" => lint_levels.push((lint_flag(&arg, args.next()), LintLevel::Deny)),
+            "--root" => match args.next() {
+                Some(v) => capabilities.file_system = FileSystemAccess::Restricted(v.into()),
+                None => usage_error("--root expects a directory"),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            v if v.starts_with("--") => usage_error(&format!("unknown option {}", v)),
+            _ if target.is_none() => target = Some(arg),
+            v => usage_error(&format!("unexpected argument {}", v)),
+        }
+    }
+    let target = match (target, &command) {
+        (Some(v), Command::Repl) => usage_error(&format!("unexpected argument {}", v)),
+        (Some(v), _) => v,
+        (None, Command::Repl) => String::new(),
+        (None, Command::Eval) => usage_error("eval expects source code"),
+        (None, _) => usage_error("expected a file"),
+    };
+
+    let mut engine = Engine::new();
+    engine.set_limits(limits);
+    engine.set_capabilities(capabilities);
+    engine.set_backend(backend);
+    engine.set_optimize(optimize);
+    engine.set_args(script_args);
+    if let Some(seed) = seed {
+        engine.set_seed(seed)
+    }
+    for (lints, level) in lint_levels {
+        for lint in lints {
+            engine.set_lint_level(lint, level)
+        }
+    }
+
+    let time = Instant::now();
+    let result = match command {
+        Command::Repl => {
+            repl::run(&engine);
+            Ok(())
+        }
+        Command::Run => engine.run_file(&target).map(|_| ()),
+        Command::Check => engine.check_file(&target),
+        Command::Eval => engine.eval(&target).map(|v| match v {
+            Returnable::Evaluate(Value::Null) => {}
+            Returnable::Evaluate(v) | Returnable::Return(v) | Returnable::Break(v) => {
+                println!("{}", v)
+            }
+        }),
+    };
+    for warning in engine.warnings() {
+        report(&warning, json)
+    }
+    if print_time {
+        eprintln!(
+            "Executed in {} milliseconds",
+            time.elapsed().as_nanos() as f64 / 1_000_000.
+        );
+    }
+    if print_gc_stats {
+        let stats = engine.gc_stats();
+        eprintln!(
+            "GC: {} collections, freed {} variables ({} bytes), {} variables ({} bytes) left",
+            stats.collections,
+            stats.freed_variables,
+            stats.freed_bytes,
+            stats.live_variables,
+            stats.live_bytes
+        )
+    }
+    if let Err(e) = result {
+        process::exit(exit_code(&e, json))
+    }
+}
+
+/// Prints the exception and returns the code the process should exit with
+fn exit_code(exception: &Exception, json: bool) -> i32 {
+    match exception.kind() {
+        ExceptionKind::Exit(code) => *code,
+        _ => {
+            report(exception, json);
+            1
+        }
+    }
+}
+
+fn report(exception: &Exception, json: bool) {
+    if json {
+        for v in exception.diagnostics() {
+            eprintln!("{}", v.diagnostic().to_json())
+        }
+    } else {
+        eprintln!("{}", exception.message())
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{} {}\n\n{}", "Err:".red().bold(), message, USAGE);
+    process::exit(2)
+}
+
+/// The lints a lint level flag applies to
+fn lint_flag(flag: &str, value: Option<String>) -> Vec<Lint> {
+    match value.as_deref() {
+        Some("warnings") => Lint::ALL.to_vec(),
+        Some(v) => match Lint::from_name(v) {
+            Some(v) => vec![v],
+            None => usage_error(&format!("unknown lint {}", v)),
+        },
+        None => usage_error(&format!("{} expects a lint", flag)),
+    }
+}
+
+fn parse_flag(flag: &str, value: Option<String>) -> u64 {
+    match value.as_deref().map(str::parse) {
+        Some(Ok(v)) => v,
+        _ => usage_error(&format!("{} expects a number", flag)),
+    }
 }
@@ -0,0 +1,159 @@
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    process,
+};
+
+use cry_script::{
+    engine::{Engine, Session},
+    exceptions::{Exception, ExceptionKind},
+    parser::data::Value,
+    Returnable,
+};
+
+const HELP: &str = "\
+:type <expr>    Prints the type of an expression
+:vars           Lists the variables declared in this session
+:load <file>    Runs a file in this session
+:history        Prints the lines entered so far
+:help           Prints this message
+:quit           Leaves the REPL";
+
+/// Reads lines from stdin and runs them in one session until stdin closes or `:quit` is entered
+pub(crate) fn run(engine: &Engine) {
+    let mut session = match engine.session() {
+        Ok(v) => v,
+        Err(e) => process::exit(report(&e)),
+    };
+    let mut history = History::open();
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut source = String::new();
+
+    loop {
+        prompt(if source.is_empty() { "> " } else { "... " });
+        let line = match lines.next() {
+            Some(Ok(v)) => v,
+            _ => break,
+        };
+        if source.is_empty() {
+            match line.trim() {
+                "" => continue,
+                ":quit" => break,
+                ":help" => {
+                    println!("{}", HELP);
+                    continue;
+                }
+                ":history" => {
+                    for (i, v) in history.lines.iter().enumerate() {
+                        println!("{:>4}  {}", i + 1, v)
+                    }
+                    continue;
+                }
+                ":vars" => {
+                    for (name, value) in session.variables() {
+                        println!("{}: {} = {}", name, value.data_type(), value)
+                    }
+                    continue;
+                }
+                v if v.starts_with(':') => {
+                    history.push(v);
+                    meta_command(&mut session, v);
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        source.push_str(&line);
+        source.push('\n');
+        if session.is_incomplete(&source) {
+            continue;
+        }
+        history.push(source.trim_end());
+        match session.eval(&source) {
+            Ok(v) => {
+                if let Some(v) = value(v) {
+                    println!("{}", v)
+                }
+            }
+            Err(e) => {
+                report(&e);
+            }
+        }
+        source.clear();
+    }
+}
+
+fn meta_command(session: &mut Session, line: &str) {
+    let (command, argument) = match line.split_once(' ') {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (line, ""),
+    };
+    match (command, argument) {
+        (":type", "") => eprintln!(":type expects an expression"),
+        (":type", v) => match session.eval(v) {
+            Ok(v) => println!("{}", value(v).unwrap_or(Value::Null).data_type()),
+            Err(e) => {
+                report(&e);
+            }
+        },
+        (":load", "") => eprintln!(":load expects a file"),
+        (":load", v) => {
+            if let Err(e) = session.load(v) {
+                report(&e);
+            }
+        }
+        (v, _) => eprintln!("unknown command {}, :help lists the commands", v),
+    }
+}
+
+/// The value worth printing, `null` is left out so statements don't print anything
+fn value(returnable: Returnable) -> Option<Value> {
+    match returnable {
+        Returnable::Evaluate(Value::Null) => None,
+        Returnable::Evaluate(v) | Returnable::Return(v) | Returnable::Break(v) => Some(v),
+    }
+}
+
+/// Prints the exception, exiting when the script asked for it
+fn report(exception: &Exception) -> i32 {
+    match exception.kind() {
+        ExceptionKind::Exit(code) => process::exit(*code),
+        _ => {
+            eprintln!("{}", exception.message());
+            1
+        }
+    }
+}
+
+fn prompt(prompt: &str) {
+    print!("{}", prompt);
+    io::stdout().flush().ok();
+}
+
+/// Lines entered in this session, also appended to `~/.cry_history`
+struct History {
+    lines: Vec<String>,
+    file: Option<PathBuf>,
+}
+
+impl History {
+    fn open() -> Self {
+        History {
+            lines: vec![],
+            file: env::var_os("HOME").map(|v| PathBuf::from(v).join(".cry_history")),
+        }
+    }
+
+    fn push(&mut self, line: &str) {
+        if let Some(path) = &self.file {
+            // History is a convenience, failing to save it shouldn't stop the REPL
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                writeln!(file, "{}", line).ok();
+            }
+        }
+        self.lines.push(line.to_string())
+    }
+}
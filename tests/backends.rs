@@ -0,0 +1,151 @@
+mod common;
+
+use std::fs;
+
+use common::{error_on_backends as error, eval_on_backends as eval};
+use cry_script::{
+    engine::Limits,
+    exceptions::{interpreter_exceptions::InvalidAmountOfArguments, ExceptionKind},
+};
+
+#[test]
+fn do_catch_recovers() {
+    let source = "var log = \"\"
+fn fail(n) {
+    log += n
+    missing_function()
+}
+do {
+    fail(1)
+    log += \"not reached\"
+} catch {
+    log += \"caught\"
+}
+do {
+    log += 2
+} catch {
+    log += \"not caught\"
+}
+log";
+    assert_eq!(eval(source), "1caught2");
+}
+
+#[test]
+fn nested_scopes() {
+    let source = "var x = 1
+var total = 0
+fn outer() {
+    var x = 10
+    if x > 5 {
+        var x = 100
+        total += x
+    } else {
+        total = 0
+    }
+    total += x
+}
+outer()
+while total < 1000 {
+    var step = total
+    total += step
+}
+var result = total + x
+result";
+    assert_eq!(eval(source), "1761");
+}
+
+#[test]
+fn classes_and_methods() {
+    let source = "class Point {
+    var x = 0
+    var y = 0
+    fn constructor(l_x, l_y) {
+        x = l_x
+        y = l_y
+    }
+    fn add(other) {
+        new Point(x + other.x, y + other.y)
+    }
+    fn show() {
+        \"(\" + x + \", \" + y + \")\"
+    }
+}
+var a = new Point(1, 2)
+var b = a.add(new Point(3, 4))
+var result = b.show() + a.show()
+result";
+    assert_eq!(eval(source), "(4, 6)(1, 2)");
+}
+
+#[test]
+fn use_statements() {
+    let dir = common::temp_dir("backends_use");
+    let module = dir.join("module.cry");
+    fs::write(&module, "fn triple(n) {\n    return n * 3\n}\n").unwrap();
+    let source = format!("use \"{}\"\ntriple(5)", module.display());
+    assert_eq!(eval(&source), "15");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn while_loops() {
+    let source = "var i = 0
+var found = null
+while found == null {
+    i += 1
+    if i * i > 50 {
+        found = i
+    }
+}
+found";
+    assert_eq!(eval(source), "8");
+}
+
+#[test]
+fn errors_are_the_same() {
+    let exception = error("var count = 1\nfn f() {\n    return count + missing\n}\nf()");
+    let span = exception.diagnostic().span.as_ref().unwrap();
+    assert_eq!((span.start.line, span.start.column), (3, 20));
+    error("class A {\n    fn f() {\n        return 1 / 0\n    }\n}\nvar a = new A()\na.f()");
+    error("var s = \"abc\" as int");
+}
+
+#[test]
+fn natives_check_the_argument_count() {
+    for source in [
+        "std_length()",
+        "std_gcd(1)",
+        "std_path_join(\"a\")",
+        "std_clone()",
+        "std_cwd(1)",
+    ] {
+        let exception = error(source);
+        assert_eq!(
+            exception.diagnostic().code,
+            Some(InvalidAmountOfArguments::CODE),
+            "{}",
+            source
+        );
+    }
+    let source = "var result = null\ndo {\n    result = std_gcd(1)\n} catch {\n    result = \"caught\"\n}\nresult";
+    assert_eq!(eval(source), "caught");
+}
+
+#[test]
+fn budgets_fire_on_loop_back_edges() {
+    for mut engine in common::backends() {
+        engine.set_limits(Limits {
+            max_steps: Some(10_000),
+            ..Limits::default()
+        });
+        // The body is empty so only the back-edge can count the iterations
+        let exception = common::error_with(&engine, "while 1 {\n}");
+        assert!(matches!(exception.kind(), ExceptionKind::StepLimitExceeded));
+        engine.set_limits(Limits {
+            time_limit: Some(std::time::Duration::from_millis(20)),
+            ..Limits::default()
+        });
+        let exception = common::error_with(&engine, "var i = 0\nwhile 1 {\n    i += 1\n}");
+        assert!(matches!(exception.kind(), ExceptionKind::TimeLimitExceeded));
+    }
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Runs the cry binary with colors turned off so the output can be compared,
+/// the first argument is the command
+fn cry(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cry"))
+        .arg(args[0])
+        .arg("--no-color")
+        .args(&args[1..])
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn exit_codes() {
+    let output = cry(&["eval", "1 + 1"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "2\n");
+
+    let output = cry(&["eval", "1 / 0"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains("division by zero"),
+        "{}",
+        stderr(&output)
+    );
+
+    for args in [
+        &["frobnicate"][..],
+        &["eval"],
+        &["run", "a.cry", "--max-steps", "x"],
+    ] {
+        let output = cry(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(stderr(&output).contains("Usage: cry"), "{:?}", args);
+    }
+
+    assert_eq!(cry(&["eval", "exit(7)"]).status.code(), Some(7));
+    assert_eq!(
+        cry(&["eval", "--sandbox", "exit(7)"]).status.code(),
+        Some(7)
+    );
+}
+
+#[test]
+fn run_and_check_files() {
+    let dir = common::temp_dir("cli_run");
+    let script = dir.join("script.cry");
+    fs::write(&script, "println(arg_count())\nprintln(arg(0) + arg(1))\n").unwrap();
+    let script = script.to_str().unwrap();
+
+    let output = cry(&["run", script, "--", "a", "--b"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "2\na--b\n");
+
+    assert_eq!(cry(&["check", script]).status.code(), Some(0));
+    let missing = dir.join("missing.cry");
+    assert_eq!(
+        cry(&["run", missing.to_str().unwrap()]).status.code(),
+        Some(1)
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn sandbox_and_root() {
+    let root = common::temp_dir("cli_root");
+    fs::write(root.join("inside.txt"), "inside").unwrap();
+    let root_arg = root.to_str().unwrap();
+
+    let output = cry(&["eval", "--sandbox", "std_contents_of_file(\"inside.txt\")"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains("permission denied"),
+        "{}",
+        stderr(&output)
+    );
+
+    let output = cry(&[
+        "eval",
+        "--root",
+        root_arg,
+        "std_contents_of_file(\"inside.txt\")",
+    ]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "inside\n");
+
+    let output = cry(&[
+        "eval",
+        "--root",
+        root_arg,
+        "std_contents_of_file(\"../x.txt\")",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stderr(&output).contains("permission denied"),
+        "{}",
+        stderr(&output)
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn repl_keeps_going_after_errors() {
+    // The REPL saves its history in the home directory
+    let home = common::temp_dir("cli_repl");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cry"))
+        .args(["repl", "--no-color"])
+        .env("HOME", &home)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"var x = 2\nmissing\nelse\nfn f(n) {\n    n * x\n}\nf(5)\n:vars\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stderr(&output).contains("missing"), "{}", stderr(&output));
+    let stdout = stdout(&output);
+    assert!(stdout.contains("10\n"), "{}", stdout);
+    assert!(stdout.contains("x: integer = 2"), "{}", stdout);
+    assert!(stdout.contains("... "), "{}", stdout);
+    fs::remove_dir_all(home).unwrap();
+}
+
+#[test]
+fn lint_levels() {
+    let output = cry(&["eval", "var unused = 1"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stderr(&output).contains("unused"), "{}", stderr(&output));
+
+    let output = cry(&["eval", "-A", "warnings", "var unused = 1"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stderr(&output), "");
+
+    let output = cry(&["eval", "-D", "unused_variables", "var unused = 1"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+
+    assert_eq!(cry(&["eval", "-D", "typos", "1"]).status.code(), Some(2));
+}
+
+#[test]
+fn json_diagnostics() {
+    let output = cry(&["eval", "--json", "var unused = 1\nprintln(missing)"]);
+    assert_eq!(output.status.code(), Some(1));
+    let lines: Vec<_> = stderr(&output).lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    assert!(
+        lines[0].starts_with("{\"severity\":\"warning\""),
+        "{}",
+        lines[0]
+    );
+    assert!(
+        lines[1].starts_with("{\"severity\":\"error\",\"code\":\"E0205\""),
+        "{}",
+        lines[1]
+    );
+}
@@ -0,0 +1,89 @@
+//! Helpers shared by the integration tests, not every test file uses all of them
+#![allow(dead_code)]
+
+use std::{fs, path::PathBuf};
+
+use cry_script::{
+    engine::{Backend, Engine},
+    exceptions::Exception,
+    parser::data::Value,
+    Returnable,
+};
+
+/// The value `source` evaluates to on `engine`, panics if it fails
+pub fn eval_with(engine: &Engine, source: &str) -> Value {
+    match engine.eval(source) {
+        Ok(Returnable::Evaluate(v)) => v,
+        Ok(v) => panic!("unexpected {}", v),
+        Err(e) => panic!("{}", e.message()),
+    }
+}
+
+/// The value `source` evaluates to on a default engine, panics if it fails
+pub fn eval(source: &str) -> Value {
+    eval_with(&Engine::new(), source)
+}
+
+/// The exception `source` fails with on `engine`, panics if it doesn't fail
+pub fn error_with(engine: &Engine, source: &str) -> Exception {
+    match engine.eval(source) {
+        Ok(v) => panic!("{} evaluated to {}", source, v),
+        Err(e) => e,
+    }
+}
+
+/// The exception `source` fails with on a default engine, panics if it doesn't fail
+pub fn error(source: &str) -> Exception {
+    error_with(&Engine::new(), source)
+}
+
+/// A default engine for every backend
+pub fn backends() -> Vec<Engine> {
+    [Backend::TreeWalker, Backend::Bytecode]
+        .into_iter()
+        .map(|backend| {
+            let mut engine = Engine::new();
+            engine.set_backend(backend);
+            engine
+        })
+        .collect()
+}
+
+/// What `source` evaluates to, checked to be the same on all of `engines`
+pub fn eval_on(engines: &[Engine], source: &str) -> String {
+    let values: Vec<_> = engines
+        .iter()
+        .map(|v| eval_with(v, source).to_string())
+        .collect();
+    for value in &values[1..] {
+        assert_eq!(value, &values[0], "{}", source);
+    }
+    values[0].clone()
+}
+
+/// The exception `source` fails with, checked to be the same on all of `engines`
+pub fn error_on(engines: &[Engine], source: &str) -> Exception {
+    let mut exceptions: Vec<_> = engines.iter().map(|v| error_with(v, source)).collect();
+    for exception in &exceptions[1..] {
+        assert_eq!(exception.message(), exceptions[0].message(), "{}", source);
+    }
+    exceptions.swap_remove(0)
+}
+
+/// What `source` evaluates to, checked to be the same on every backend
+pub fn eval_on_backends(source: &str) -> String {
+    eval_on(&backends(), source)
+}
+
+/// The exception `source` fails with, checked to be the same on every backend
+pub fn error_on_backends(source: &str) -> Exception {
+    error_on(&backends(), source)
+}
+
+/// An empty directory only used by the test called `name`
+pub fn temp_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("cry_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::error;
+use cry_script::{
+    engine::Engine,
+    exceptions::{diagnostic::Severity, interpreter_exceptions::AccessUndeclaredVariable},
+    lints::{Lint, LintLevel},
+};
+
+#[test]
+fn parser_reports_every_error_in_order() {
+    let source = "var x = )\nprintln(\"ok\")\nfn f() {\n    bad )\n}\nvar = 3\n";
+    let exception = error(source);
+    let lines: Vec<_> = exception
+        .diagnostics()
+        .iter()
+        .map(|v| v.message().lines().nth(2).unwrap().to_string())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("eval:1:9"), "{}", lines[0]);
+    assert!(lines[1].ends_with("eval:4:9"), "{}", lines[1]);
+    assert!(lines[2].ends_with("eval:6:5"), "{}", lines[2]);
+}
+
+#[test]
+fn lints_warn_by_default_and_can_be_denied() {
+    let source = "fn f() {\n    return 1\n    println(2)\n}\nvar unused = f()\n";
+    let mut engine = Engine::new();
+    assert!(engine.eval(source).is_ok());
+    assert_eq!(engine.warnings().len(), 2);
+
+    engine.set_lint_level(Lint::UnusedVariables, LintLevel::Allow);
+    engine.set_lint_level(Lint::UnreachableCode, LintLevel::Deny);
+    assert!(engine.eval(source).is_err());
+    assert!(engine.warnings().is_empty());
+}
+
+#[test]
+fn diagnostics_have_stable_codes_and_json() {
+    let exception = error("var count = 1\nprintln(cont)");
+    let diagnostic = exception.diagnostic();
+    assert_eq!(diagnostic.code, Some(AccessUndeclaredVariable::CODE));
+    assert_eq!(diagnostic.severity, Severity::Error);
+    let span = diagnostic.span.as_ref().unwrap();
+    assert_eq!((span.start.line, span.start.column), (2, 9));
+    assert_eq!((span.end.line, span.end.column), (2, 12));
+    assert!(diagnostic.to_json().starts_with(
+        "{\"severity\":\"error\",\"code\":\"E0205\",\"message\":\"access undeclared variable\""
+    ));
+}
+
+#[test]
+fn undeclared_names_suggest_similar_ones() {
+    for (source, suggestion) in [
+        ("var count = 1\nprintln(cont)", "count"),
+        ("fn double(n) {\n    n * 2\n}\ndoubel(2)", "double"),
+        ("use \"std_math\"\nnew Vectr2(1, 2)", "Vector2"),
+        ("use \"std_math\"\nMth.max(1, 2)", "Math"),
+        ("use \"std_string\"\nString.len(\"a\")", "Strings"),
+    ] {
+        let message = error(source).message().to_string();
+        assert!(
+            message.contains(&format!("did you mean » {} «?", suggestion)),
+            "{}",
+            message
+        );
+    }
+    let message = error("var count = 1\nprintln(xyz)").message().to_string();
+    assert!(!message.contains("did you mean"), "{}", message);
+}
+
+#[test]
+fn positions_count_characters_and_ignore_carriage_returns() {
+    let source = "var s = \"€uro\"\r\nprintln(s + \"ünïcode\" + missing)\r\n";
+    let exception = error(source);
+    let span = exception.diagnostic().span.as_ref().unwrap();
+    assert_eq!((span.start.line, span.start.column), (2, 25));
+    let lines: Vec<_> = exception.message().lines().collect();
+    assert!(lines[2].ends_with("eval:2:25"), "{}", lines[2]);
+    assert!(!lines[4].contains('\r'), "{}", lines[4]);
+    assert_eq!(
+        lines[5].find('^').unwrap() - lines[5].find('|').unwrap(),
+        lines[4].chars().position(|v| v == 'm').unwrap() - lines[4].find('|').unwrap()
+    );
+}
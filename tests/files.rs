@@ -0,0 +1,112 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use cry_script::{
+    engine::{Capabilities, Engine, FileSystemAccess},
+    exceptions::interpreter_exceptions::{FileSystemError, InvalidFilePath, PermissionDenied},
+};
+
+/// An engine that can only access a fresh directory of its own
+fn engine(name: &str) -> (Engine, PathBuf) {
+    let root = common::temp_dir(&format!("files_{}", name));
+    let mut engine = Engine::new();
+    engine.set_capabilities(Capabilities {
+        file_system: FileSystemAccess::Restricted(root.clone()),
+        ..Capabilities::default()
+    });
+    (engine, root)
+}
+
+fn eval(engine: &Engine, source: &str) -> String {
+    common::eval_with(engine, &format!("use \"std_file\"\n{}", source)).to_string()
+}
+
+fn error_code(engine: &Engine, source: &str) -> Option<&'static str> {
+    common::error_with(engine, &format!("use \"std_file\"\n{}", source))
+        .diagnostic()
+        .code
+}
+
+#[test]
+fn files() {
+    let (engine, root) = engine("files");
+    fs::write(root.join("kept.txt"), "kept").unwrap();
+    assert_eq!(
+        eval(&engine, "var f = new File(\"kept.txt\")\nf.contents()"),
+        "kept"
+    );
+    assert_eq!(
+        eval(&engine, "var f = new File(\"new.txt\")\nf.exists()"),
+        "0"
+    );
+
+    let source = "var f = new File(\"new.txt\")\nf.write_all(\"one\\r\\ntwo\\n\")\nf.append(\"three\")\nvar lines = f.lines()\nf\"{lines.length()} {lines.get(0)} {lines.get(2)} {f.size()}\"";
+    assert_eq!(eval(&engine, source), "3 one three 14");
+
+    let source = "var f = new File(\"new.txt\")\nvar copy = f.copy(\"copy.txt\")\nf.rename(\"moved.txt\")\nf\"{Fs.exists(\"new.txt\")} {f.contents() == copy.contents()} {f.modified() > 0}\"";
+    assert_eq!(eval(&engine, source), "0 1 1");
+    assert_eq!(
+        fs::read_to_string(root.join("moved.txt")).unwrap(),
+        "one\r\ntwo\nthree"
+    );
+
+    assert_eq!(
+        eval(
+            &engine,
+            "var f = new File(\"copy.txt\")\nf.remove()\nf.exists()"
+        ),
+        "0"
+    );
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn directories() {
+    let (engine, root) = engine("directories");
+    let source = "Fs.create_dir_all(\"a/b/c\")\nvar f = new File(\"a/z.txt\")\nf.create()\nvar entries = Fs.list_dir(\"a\")\nf\"{entries.length()} {entries.get(0)} {entries.get(1)} {Fs.is_dir(\"a/b\")} {Fs.is_dir(\"a/z.txt\")} {Fs.list_dir(\"a/b/c\").length()}\"";
+    assert_eq!(eval(&engine, source), "2 b z.txt 1 0 0");
+    assert!(root.join("a/b/c").is_dir());
+
+    assert_eq!(
+        error_code(&engine, "Fs.remove(\"a\")"),
+        Some(FileSystemError::CODE)
+    );
+    assert_eq!(
+        eval(&engine, "Fs.remove(\"a/b/c\")\nFs.exists(\"a/b/c\")"),
+        "0"
+    );
+    assert_eq!(
+        error_code(&engine, "Fs.create_dir_all(\"a/../../outside\")"),
+        Some(PermissionDenied::CODE)
+    );
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn errors_can_be_caught() {
+    let (engine, root) = engine("errors");
+    assert_eq!(
+        error_code(&engine, "var f = new File(\"missing.txt\")\nf.contents()"),
+        Some(InvalidFilePath::CODE)
+    );
+    assert_eq!(
+        error_code(&engine, "var f = new File(\".\")\nf.write_all(\"text\")"),
+        Some(FileSystemError::CODE)
+    );
+    let source = "var result = \"ok\"\ndo {\n    Fs.size(\"missing.txt\")\n} catch {\n    result = \"caught\"\n}\nresult";
+    assert_eq!(eval(&engine, source), "caught");
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn paths() {
+    let engine = Engine::new();
+    assert_eq!(eval(&engine, "Path.join(\"a/b\", \"c.txt\")"), "a/b/c.txt");
+    assert_eq!(eval(&engine, "Path.join(\"a\", \"/c\")"), "/c");
+    assert_eq!(eval(&engine, "Path.extension(\"a/b.tar.gz\")"), "gz");
+    assert_eq!(eval(&engine, "Path.extension(\"a/b\")"), "null");
+    assert_eq!(eval(&engine, "Path.parent(\"a/b.txt\")"), "a");
+    assert_eq!(eval(&engine, "Path.parent(\"/\")"), "null");
+    assert_eq!(eval(&engine, "Path.file_name(\"a/b.txt\")"), "b.txt");
+}
@@ -0,0 +1,42 @@
+mod common;
+
+/// Creates `count` pairs of instances that reference each other and drops them again
+fn cycles(count: usize) -> String {
+    format!(
+        "class Node {{
+    var other = 0
+    fn link(o) {{
+        other = o
+    }}
+}}
+fn pair() {{
+    var a = new Node()
+    var b = new Node()
+    a.link(b)
+    b.link(a)
+}}
+var i = 0
+while i < {} {{
+    pair()
+    i += 1
+}}
+i",
+        count
+    )
+}
+
+#[test]
+fn cycles_are_collected() {
+    for engine in common::backends() {
+        assert_eq!(
+            common::eval_with(&engine, &cycles(20_000)).to_string(),
+            "20000"
+        );
+        let stats = engine.gc_stats();
+        assert!(stats.collections > 1, "{:?}", stats);
+        assert!(stats.freed_variables > 100_000, "{:?}", stats);
+        assert!(stats.freed_bytes > 0, "{:?}", stats);
+        // The collector runs at least every 10 000 allocations, so the pairs can't pile up
+        assert!(stats.live_variables < 10_000, "{:?}", stats);
+    }
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::error_with;
+use cry_script::{
+    engine::{Engine, Limits},
+    exceptions::ExceptionKind,
+};
+
+fn limited(limits: Limits) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_limits(limits);
+    engine
+}
+
+#[test]
+fn step_limit_stops_endless_loops() {
+    let engine = limited(Limits {
+        max_steps: Some(10_000),
+        ..Limits::default()
+    });
+    for source in [
+        "var i = 0\nwhile 1 {\n    i += 1\n}",
+        "var i = 0\ndo {\n    while 1 {\n        i += 1\n    }\n} catch {\n    i = 0\n}",
+    ] {
+        let exception = error_with(&engine, source);
+        assert!(matches!(exception.kind(), ExceptionKind::StepLimitExceeded));
+        assert!(!exception.kind().is_catchable());
+    }
+}
+
+#[test]
+fn time_limit_stops_endless_loops() {
+    let engine = limited(Limits {
+        time_limit: Some(Duration::from_millis(50)),
+        ..Limits::default()
+    });
+    for source in [
+        "var i = 0\nwhile 1 {\n    i += 1\n}",
+        "var i = 0\ndo {\n    while 1 {\n        i += 1\n    }\n} catch {\n    i = 0\n}",
+    ] {
+        let time = Instant::now();
+        let exception = error_with(&engine, source);
+        assert!(matches!(exception.kind(), ExceptionKind::TimeLimitExceeded));
+        assert!(time.elapsed() < Duration::from_secs(5));
+    }
+}
+
+/// An engine for every backend whose variables may take up at most `max_memory` bytes
+fn memory_limited(max_memory: usize) -> Vec<Engine> {
+    let mut engines = common::backends();
+    for engine in &mut engines {
+        engine.set_limits(Limits {
+            max_memory: Some(max_memory),
+            ..Limits::default()
+        });
+    }
+    engines
+}
+
+#[test]
+fn memory_limit_stops_growing_scripts() {
+    for engine in memory_limited(1_000_000) {
+        let exception = error_with(&engine, "var s = \"x\"\nwhile 1 {\n    s += s\n}");
+        assert!(matches!(exception.kind(), ExceptionKind::OutOfMemory));
+    }
+}
+
+#[test]
+fn out_of_memory_can_be_caught() {
+    let source = "var s = \"x\"
+var result = 0
+do {
+    while 1 {
+        s += s
+    }
+} catch {
+    result = \"caught\"
+}
+result";
+    assert_eq!(
+        common::eval_on(&memory_limited(1_000_000), source),
+        "caught"
+    );
+}
+
+#[test]
+fn collected_memory_can_be_used_again() {
+    // Every pair holds on to 2 KB and can only be freed by the collector since it's a cycle
+    let source = "class Node {
+    var other = 0
+    var payload = 0
+    fn link(o) {
+        other = o
+        payload = \"x\" * 1000
+    }
+}
+fn pair() {
+    var a = new Node()
+    var b = new Node()
+    a.link(b)
+    b.link(a)
+}
+var i = 0
+while i < 500 {
+    pair()
+    i += 1
+}
+i";
+    for engine in memory_limited(100_000) {
+        assert_eq!(common::eval_with(&engine, source).to_string(), "500");
+        assert!(engine.gc_stats().collections > 0);
+    }
+}
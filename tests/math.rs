@@ -0,0 +1,75 @@
+mod common;
+
+use cry_script::{
+    exceptions::interpreter_exceptions::{
+        IntegerOverflow, InvalidArgumentType, InvalidNumericArgument,
+    },
+    parser::data::Value,
+};
+
+fn eval(source: &str) -> Value {
+    common::eval(&format!("use \"std_math\"\n{}", source))
+}
+
+fn float(source: &str) -> f32 {
+    match eval(source) {
+        Value::Float(v) => v,
+        v => panic!("{} evaluated to {}", source, v),
+    }
+}
+
+#[test]
+fn min_max_and_clamp() {
+    assert_eq!(eval("Math.min(3, 5)").to_string(), "3");
+    assert_eq!(eval("Math.max(3, 5)").to_string(), "5");
+    assert_eq!(eval("Math.clamp(9, 0, 3)").to_string(), "3");
+    assert_eq!(eval("Math.clamp(-2, 0, 3)").to_string(), "0");
+    assert_eq!(eval("Math.clamp(2, 0, 3)").to_string(), "2");
+}
+
+#[test]
+fn native_math_functions() {
+    assert_eq!(float("Math.sqrt(16)"), 4.0);
+    assert_eq!(float("Math.hypot(3, 4)"), 5.0);
+    assert!((float("Math.sin(Math.PI / 2)") - 1.0).abs() < 1e-6);
+    assert!((float("Math.atan2(1, 1) * 4") - std::f32::consts::PI).abs() < 1e-6);
+    assert!((float("Math.log(8, 2)") - 3.0).abs() < 1e-6);
+    assert_eq!(float("Math.exp(0)"), 1.0);
+    assert_eq!(float("Math.floor(2.7)"), 2.0);
+    assert_eq!(float("Math.ceil(2.1)"), 3.0);
+    assert_eq!(float("Math.round(2.5)"), 3.0);
+    assert_eq!(eval("Math.floor(3)").to_string(), "3");
+    assert_eq!(eval("Math.pow(2, 10)").to_string(), "1024");
+    assert_eq!(float("Math.pow(2, -1)"), 0.5);
+    assert_eq!(eval("Math.gcd(12, -18)").to_string(), "6");
+    assert_eq!(eval("Math.lcm(4, 6)").to_string(), "12");
+    assert_eq!(eval("Math.lcm(46340, 46341)").to_string(), "2147441940");
+    assert_eq!(float("Math.INFINITY"), f32::INFINITY);
+    assert_eq!(eval("Math.is_nan(Math.NaN)").to_string(), "1");
+}
+
+#[test]
+fn vectors() {
+    assert_eq!(
+        float("var a = new Vector2(1.0, 1.0)\na.distance(new Vector2(4.0, 5.0))"),
+        5.0
+    );
+    assert_eq!(
+        eval("var a = new Vector2i(1, 2)\na.equals(new Vector2i(1, 3))").to_string(),
+        "0"
+    );
+}
+
+#[test]
+fn wrong_argument_types_are_errors() {
+    for (source, code) in [
+        ("Math.sqrt(\"4\")", InvalidNumericArgument::CODE),
+        ("Math.floor(null)", InvalidNumericArgument::CODE),
+        ("Math.gcd(1.5, 2)", InvalidArgumentType::CODE),
+        ("Math.lcm(65536, 65537)", IntegerOverflow::CODE),
+        ("Math.gcd(-2147483647 - 1, 0)", IntegerOverflow::CODE),
+    ] {
+        let exception = common::error(&format!("use \"std_math\"\n{}", source));
+        assert_eq!(exception.diagnostic().code, Some(code), "{}", source);
+    }
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use cry_script::{
+    engine::Engine,
+    exceptions::{interpreter_exceptions::IntegerOverflow, Exception},
+};
+
+/// An engine with and one without the optimizer
+fn engines() -> [Engine; 2] {
+    [true, false].map(|optimize| {
+        let mut engine = Engine::new();
+        engine.set_optimize(optimize);
+        engine
+    })
+}
+
+fn eval(source: &str) -> String {
+    common::eval_on(&engines(), source)
+}
+
+fn error(source: &str) -> Exception {
+    common::error_on(&engines(), source)
+}
+
+#[test]
+fn optimizing_does_not_change_results() {
+    assert_eq!(eval("60 * 60 * 24"), "86400");
+    assert_eq!(eval("var x = 2\n-(2 * 3) + x * 0.5"), "-5");
+    assert_eq!(eval("\"ab\" * 2 + 1"), "abab1");
+    assert_eq!(
+        eval("var x = 1\nif 0 {\n    x = 2\n} else {\n    x = 3\n}\nwhile 0 {\n    x = 4\n}\nx"),
+        "3"
+    );
+    assert_eq!(
+        eval("fn f() {\n    return g() + 1\n    println(\"unreachable\")\n    fn g() {\n        2\n    }\n}\nf()"),
+        "3"
+    );
+}
+
+#[test]
+fn failing_constants_stay_runtime_errors() {
+    for source in [
+        "\"abc\" as int",
+        "1 / 0",
+        "2147483647 + 1",
+        "60 * 60 * 24 * 365 * 100",
+    ] {
+        let span = error(source).diagnostic().span.clone().unwrap();
+        assert_eq!((span.start.line, span.start.column), (1, 1));
+        assert_eq!(
+            eval(&format!(
+                "var result = 0\ndo {{\n    result = {}\n}} catch {{\n    result = \"caught\"\n}}\nresult",
+                source
+            )),
+            "caught"
+        );
+    }
+}
+
+#[test]
+fn overflowing_constants() {
+    for source in [
+        "2147483647 + 1",
+        "-2147483647 - 2",
+        "2 ^ 31",
+        "-(-2147483647 - 1)",
+    ] {
+        assert_eq!(
+            error(source).diagnostic().code,
+            Some(IntegerOverflow::CODE),
+            "{}",
+            source
+        );
+    }
+    assert_eq!(eval("2 ^ -1"), "0.5");
+    // Dead branches are removed without evaluating what's inside of them
+    assert_eq!(
+        eval("if 0 {\n    var x = 2147483647 + 1\n}\nwhile 0 {\n    2 ^ 40\n}\n5"),
+        "5"
+    );
+}
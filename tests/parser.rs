@@ -0,0 +1,58 @@
+mod common;
+
+use common::eval;
+
+#[test]
+fn if_without_else_ends_at_its_block() {
+    let source = "var log = \"\"
+if 1 {
+    log += \"a\"
+}
+log += \"b\"
+if 0 {
+    log += \"c\"
+}
+log += \"d\"
+log";
+    assert_eq!(eval(source).to_string(), "abd");
+}
+
+#[test]
+fn statements_after_else_if_chains_run() {
+    let source = "var log = \"\"
+var i = 0
+while i < 3 {
+    if i == 0 {
+        log += \"zero\"
+    } else if i == 1 {
+        log += \"one\"
+    } else {
+        log += \"two\"
+    }
+    log += \",\"
+    i += 1
+}
+log";
+    assert_eq!(eval(source).to_string(), "zero,one,two,");
+}
+
+#[test]
+fn functions_keep_going_after_else_if_chains() {
+    let source = "fn sign(n) {
+    var name = \"\"
+    if n < 0 {
+        name = \"negative\"
+    } else if n == 0 {
+        name = \"zero\"
+    } else if n < 10 {
+        name = \"small\"
+    } else {
+        name = \"large\"
+    }
+    name += \"!\"
+    return name
+}
+var signs = sign(-3) + sign(0) + sign(4) + sign(40)
+signs";
+    assert_eq!(eval(source).to_string(), "negative!zero!small!large!");
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+mod common;
+
+use common::eval_with as eval;
+use cry_script::{engine::Engine, parser::data::Value};
+
+#[test]
+fn host_supplied_args_and_env() {
+    let mut engine = Engine::new();
+    engine.set_args(vec!["first".to_string(), "second".to_string()]);
+    engine.set_env(HashMap::from([("NAME".to_string(), "cry".to_string())]));
+    let value = eval(&engine, "args().get(1) + env(\"NAME\")");
+    assert_eq!(value.to_string(), "secondcry");
+    assert!(matches!(eval(&engine, "env(\"HOME\")"), Value::Null));
+}
+
+#[test]
+fn set_env_stays_inside_the_script() {
+    let engine = Engine::new();
+    let value = eval(
+        &engine,
+        "set_env(\"CRY_SCRIPT_TEST\", \"set\")\nenv(\"CRY_SCRIPT_TEST\")",
+    );
+    assert_eq!(value.to_string(), "set");
+    assert!(std::env::var("CRY_SCRIPT_TEST").is_err());
+}
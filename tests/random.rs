@@ -0,0 +1,70 @@
+mod common;
+
+use cry_script::{engine::Engine, parser::data::Value};
+
+fn eval(engine: &Engine, source: &str) -> String {
+    common::eval_with(
+        engine,
+        &format!("use \"std_rand\"\nuse \"std_string\"\n{}", source),
+    )
+    .to_string()
+}
+
+fn seeded(seed: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_seed(seed);
+    engine
+}
+
+#[test]
+fn engine_seed_makes_runs_repeatable() {
+    let source =
+        "f\"{Random.rand_int()} {Random.rand_float()} {Random.normal()} {Random.exponential()}\"";
+    assert_eq!(eval(&seeded(7), source), eval(&seeded(7), source));
+    assert_ne!(eval(&seeded(7), source), eval(&seeded(8), source));
+}
+
+#[test]
+fn instances_have_their_own_seed() {
+    let source = "var a = new Random()\nvar b = new Random()\na.seed(3)\nb.seed(4)\nvar first = a.rand_int()\nb.seed(3)\nfirst == b.rand_int()";
+    assert_eq!(eval(&Engine::new(), source), "1");
+}
+
+#[test]
+fn shuffle_and_choice() {
+    let engine = Engine::new();
+    let shuffled = eval(
+        &engine,
+        "Strings.join(Random.shuffle(Strings.split(\"a,b,c,d,e\", \",\")), \"\")",
+    );
+    let mut letters: Vec<char> = shuffled.chars().collect();
+    letters.sort();
+    assert_eq!(letters.into_iter().collect::<String>(), "abcde");
+
+    assert!("xyz".contains(&eval(&engine, "Random.choice(Strings.chars(\"xyz\"))")));
+    assert_eq!(eval(&engine, "Random.choice(Strings.chars(\"\"))"), "null");
+    assert_eq!(
+        eval(
+            &engine,
+            "Random.weighted_choice(Strings.chars(\"abc\"), Strings.split(\"0,0,2\", \",\"))"
+        ),
+        "c"
+    );
+}
+
+#[test]
+fn distributions() {
+    let engine = seeded(1);
+    let mean = |source: &str| {
+        let source = format!(
+            "var total = 0.0\nvar i = 0\nwhile i < 2000 {{\n    total += {}\n    i += 1\n}}\ntotal / 2000",
+            source
+        );
+        match common::eval_with(&engine, &format!("use \"std_rand\"\n{}", source)) {
+            Value::Float(v) => v,
+            v => panic!("{} evaluated to {}", source, v),
+        }
+    };
+    assert!((mean("Random.normal(5.0, 2.0)") - 5.0).abs() < 0.3);
+    assert!((mean("Random.exponential(4.0)") - 0.25).abs() < 0.05);
+}
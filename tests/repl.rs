@@ -0,0 +1,98 @@
+mod common;
+
+use std::fs;
+
+use cry_script::{
+    engine::{Capabilities, Engine, FileSystemAccess, Session},
+    exceptions::interpreter_exceptions::PermissionDenied,
+    Returnable,
+};
+
+fn session() -> Session {
+    match Engine::new().session() {
+        Ok(v) => v,
+        Err(e) => panic!("{}", e.message()),
+    }
+}
+
+fn eval(session: &mut Session, source: &str) -> String {
+    match session.eval(source) {
+        Ok(Returnable::Evaluate(v)) => v.to_string(),
+        Ok(v) => panic!("unexpected {}", v),
+        Err(e) => panic!("{}", e.message()),
+    }
+}
+
+#[test]
+fn declarations_are_kept_between_lines() {
+    let mut session = session();
+    eval(&mut session, "var x = 2");
+    eval(&mut session, "fn double(n) {\n    n * 2\n}");
+    eval(&mut session, "class Box {\n    var value = 3\n}");
+    eval(&mut session, "var b = new Box()");
+    assert_eq!(eval(&mut session, "double(x) + b.value"), "7");
+    let names: Vec<_> = session.variables().into_iter().map(|v| v.0).collect();
+    assert_eq!(names, ["b", "double", "x"]);
+}
+
+#[test]
+fn errors_do_not_end_the_session() {
+    let mut session = session();
+    eval(&mut session, "var x = 2");
+    for source in [
+        "missing",
+        "x = )",
+        "var = 3",
+        "1 / 0",
+        "\"a\" as int",
+        "else",
+        "as",
+        "catch",
+        "x + else",
+    ] {
+        assert!(session.eval(source).is_err(), "{}", source);
+    }
+    eval(&mut session, "x += 1");
+    assert_eq!(eval(&mut session, "x"), "3");
+}
+
+#[test]
+fn open_blocks_continue_on_the_next_line() {
+    let session = session();
+    assert!(session.is_incomplete("fn f() {\n"));
+    assert!(session.is_incomplete("fn f() {\n    if 1 {\n        2\n    }\n"));
+    assert!(!session.is_incomplete("fn f() {\n    2\n}\n"));
+    assert!(!session.is_incomplete("var x = 1\n"));
+}
+
+#[test]
+fn loading_files_follows_the_file_system_capability() {
+    let root = common::temp_dir("repl_root");
+    let outside = common::temp_dir("repl_outside");
+    fs::write(root.join("inside.cry"), "var inside = 1").unwrap();
+    fs::write(outside.join("outside.cry"), "var outside = 2").unwrap();
+    let mut engine = Engine::new();
+    engine.set_capabilities(Capabilities {
+        file_system: FileSystemAccess::Restricted(root.clone()),
+        ..Capabilities::default()
+    });
+    let mut session = engine.session().unwrap();
+    session.load("inside.cry").unwrap();
+    assert_eq!(eval(&mut session, "inside"), "1");
+    for path in [
+        outside.join("outside.cry").display().to_string(),
+        format!(
+            "../{}/outside.cry",
+            outside.file_name().unwrap().to_string_lossy()
+        ),
+    ] {
+        let exception = session.load(&path).unwrap_err();
+        assert_eq!(
+            exception.diagnostic().code,
+            Some(PermissionDenied::CODE),
+            "{}",
+            path
+        );
+    }
+    assert!(session.eval("outside").is_err());
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::{error_with, eval_with};
+use cry_script::{
+    engine::{Capabilities, Engine, FileSystemAccess},
+    exceptions::{
+        interpreter_exceptions::{InvalidFilePath, PermissionDenied},
+        ExceptionKind,
+    },
+};
+
+fn engine(capabilities: Capabilities) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_capabilities(capabilities);
+    engine
+}
+
+fn restricted(root: &Path) -> Engine {
+    engine(Capabilities {
+        file_system: FileSystemAccess::Restricted(root.to_path_buf()),
+        ..Capabilities::default()
+    })
+}
+
+/// Reads the file through the native std_file's `File` is built on
+fn read(path: &Path) -> String {
+    format!("std_contents_of_file(\"{}\")", path.display())
+}
+
+fn assert_denied(engine: &Engine, source: &str) {
+    let exception = error_with(engine, source);
+    assert_eq!(
+        exception.diagnostic().code,
+        Some(PermissionDenied::CODE),
+        "{}",
+        source
+    );
+}
+
+#[test]
+fn denied_file_system() {
+    let engine = engine(Capabilities {
+        file_system: FileSystemAccess::Denied,
+        ..Capabilities::default()
+    });
+    assert_denied(&engine, &read(Path::new("Cargo.toml")));
+    assert_denied(&engine, "use \"Cargo.toml\"");
+    assert_denied(&engine, "use \"std_process\"\nProcess.cwd()");
+}
+
+#[test]
+fn restricted_root_can_not_be_escaped() {
+    let root = common::temp_dir("sandbox_root");
+    let outside = common::temp_dir("sandbox_outside");
+    fs::write(root.join("inside.txt"), "inside").unwrap();
+    fs::write(outside.join("secret.txt"), "secret").unwrap();
+    let engine = restricted(&root);
+
+    assert_eq!(
+        eval_with(&engine, &read(Path::new("inside.txt"))).to_string(),
+        "inside"
+    );
+    assert_denied(
+        &engine,
+        &read(
+            &Path::new("..")
+                .join(outside.file_name().unwrap())
+                .join("secret.txt"),
+        ),
+    );
+    assert_denied(&engine, &read(Path::new("missing/../../secret.txt")));
+    assert_denied(&engine, &read(&outside.join("secret.txt")));
+    assert_denied(
+        &engine,
+        &format!("use \"{}\"", outside.join("secret.txt").display()),
+    );
+
+    fs::remove_dir_all(root).unwrap();
+    fs::remove_dir_all(outside).unwrap();
+}
+
+#[test]
+fn only_embedded_modules_skip_the_file_system() {
+    let engine = engine(Capabilities {
+        file_system: FileSystemAccess::Denied,
+        ..Capabilities::default()
+    });
+    assert_eq!(
+        eval_with(&engine, "use \"std_math\"\nMath.max(1, 2)").to_string(),
+        "2"
+    );
+    assert_denied(&engine, "use \"std_whatever\"");
+
+    let root = common::temp_dir("sandbox_std_prefix");
+    fs::write(root.join("std_local"), "var local = 5\n").unwrap();
+    let engine = restricted(&root);
+    assert_eq!(
+        eval_with(&engine, "use \"std_local\"\nlocal").to_string(),
+        "5"
+    );
+    assert_denied(&engine, "use \"../std_local\"");
+    fs::remove_dir_all(root).unwrap();
+
+    let exception = error_with(&Engine::new(), "use \"std_missing\"");
+    assert_eq!(exception.diagnostic().code, Some(InvalidFilePath::CODE));
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_out_of_the_root_are_denied() {
+    use std::os::unix::fs::symlink;
+
+    let root = common::temp_dir("sandbox_links");
+    let outside = common::temp_dir("sandbox_link_targets");
+    fs::write(outside.join("secret.txt"), "secret").unwrap();
+    symlink(outside.join("secret.txt"), root.join("file_link")).unwrap();
+    symlink(&outside, root.join("dir_link")).unwrap();
+    let engine = restricted(&root);
+
+    assert_denied(&engine, &read(Path::new("file_link")));
+    assert_denied(&engine, &read(Path::new("dir_link/secret.txt")));
+    // Files that don't exist yet are checked through the directory they would be created in
+    assert_denied(&engine, "std_write_to_file(\"dir_link/new.txt\", \"x\")");
+    assert!(!outside.join("new.txt").exists());
+
+    fs::remove_dir_all(root).unwrap();
+    fs::remove_dir_all(outside).unwrap();
+}
+
+#[test]
+fn stdin_and_exit_are_sandboxed() {
+    let engine = engine(Capabilities::sandboxed());
+    assert_denied(&engine, "read()");
+    let exception = error_with(
+        &engine,
+        "do {\n    exit(3)\n} catch {\n    println(\"caught\")\n}",
+    );
+    assert!(matches!(exception.kind(), ExceptionKind::Exit(3)));
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::eval_on_backends as eval;
+
+#[test]
+fn instances_outlive_the_scope_that_created_them() {
+    let source = "class Node {
+    var value = 0
+    var next = null
+    fn constructor(v) {
+        value = v
+    }
+    fn link(n) {
+        next = n
+    }
+    fn total() {
+        var sum = value
+        sum += next.value
+        sum
+    }
+}
+fn chain(first) {
+    var a = new Node(first)
+    var b = new Node(first * 10)
+    a.link(b)
+    a
+}
+var head = chain(1)
+var other = chain(2)
+head.link(other)
+var result = head.total() + other.total()
+result";
+    assert_eq!(eval(source), "25");
+}
+
+#[test]
+fn values_outlive_the_run() {
+    let source = "class Node {
+    var value = 0
+}
+fn make() {
+    var local = new Node()
+    local
+}
+make()";
+    // The run's scopes are gone by the time the host looks at the value
+    assert_eq!(eval(source), "Node");
+    assert_eq!(eval(source), "Node");
+}
+
+#[test]
+fn nested_blocks_shadow_outer_variables() {
+    let source = "var x = 1
+var seen = \"\"
+var i = 0
+while i < 1 {
+    var x = 2
+    seen += x
+    while i < 1 {
+        var x = 3
+        seen += x
+        i += 1
+    }
+    seen += x
+}
+seen += x
+seen";
+    assert_eq!(eval(source), "2321");
+}
+
+#[test]
+fn loop_bodies_start_with_fresh_variables() {
+    let source = "var total = 0
+var i = 0
+while i < 3 {
+    var fresh
+    if fresh == null {
+        total += 1
+    } else {
+        total += 100
+    }
+    fresh = i
+    var doubled = i * 2
+    total += doubled
+    i += 1
+}
+total";
+    assert_eq!(eval(source), "9");
+}
+
+#[test]
+fn functions_read_the_scope_they_were_declared_in() {
+    let source = "var base = 10
+fn add(n) {
+    return base + n
+}
+fn call() {
+    var base = 1
+    add(5)
+}
+var result = add(5) + call()
+var i = 0
+while i < 1 {
+    var inner = 4
+    fn get() {
+        inner
+    }
+    result += get()
+    i += 1
+}
+result";
+    assert_eq!(eval(source), "34");
+}
+
+#[test]
+fn methods_read_fields_and_outer_variables() {
+    let source = "var factor = 3
+class Counter {
+    var count = 0
+    fn bump() {
+        var step = factor
+        count += step
+        count
+    }
+}
+var first = new Counter()
+var second = new Counter()
+first.bump()
+first.bump()
+second.bump()
+var result = first.count * 10 + second.count
+result";
+    assert_eq!(eval(source), "63");
+}
+
+#[test]
+fn recursion_keeps_locals_apart() {
+    let source = "fn fib(n) {
+    if n < 2 {
+        return n
+    } else {
+        var a = fib(n - 1)
+        var b = fib(n - 2)
+        return a + b
+    }
+}
+fib(10)";
+    assert_eq!(eval(source), "55");
+}
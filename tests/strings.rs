@@ -0,0 +1,156 @@
+mod common;
+
+use common::{error, eval};
+use cry_script::{
+    exceptions::lexer_exceptions::{InvalidInterpolation, InvalidUnicodeEscape},
+    parser::data::Value,
+};
+
+#[test]
+fn identifiers_can_use_any_script() {
+    let source = "var größe = 2\nfn verdoppeln(wert: int) {\n    wert * 2\n}\nvar 名前 = verdoppeln(größe)\n名前";
+    assert_eq!(eval(source).to_string(), "4");
+}
+
+#[test]
+fn unicode_escapes() {
+    assert_eq!(eval("\"\\u{48}\\u{E9}\\u{1F600}\"").to_string(), "Hé😀");
+    for source in ["\"\\u{110000}\"", "\"\\u{41\"", "\"\\u41\"", "\"\\u{}\""] {
+        let exception = error(source);
+        assert_eq!(
+            exception.diagnostic().code,
+            Some(InvalidUnicodeEscape::CODE)
+        );
+    }
+}
+
+#[test]
+fn strings_are_indexed_by_character() {
+    assert_eq!(eval("len(\"naïve €\")").to_string(), "7");
+    assert_eq!(eval("char_at(\"naïve €\", 2)").to_string(), "ï");
+    assert_eq!(eval("char_at(\"naïve €\", 6)").to_string(), "€");
+    assert!(matches!(eval("char_at(\"naïve €\", 7)"), Value::Null));
+    assert!(matches!(eval("char_at(\"naïve €\", -1)"), Value::Null));
+}
+
+#[test]
+fn interpolated_strings() {
+    let source = "var name = \"Ada\"\nvar count = 2\nf\"Hello {name}, you have {count + 1} items\"";
+    assert_eq!(eval(source).to_string(), "Hello Ada, you have 3 items");
+    assert_eq!(
+        eval("f\"{{{1}}} {f\"{null}\"} {if 1 > 2 { \"a\" } else { \"b\" }}\"").to_string(),
+        "{1} null b"
+    );
+    assert_eq!(eval("f\"plain\"").to_string(), "plain");
+}
+
+#[test]
+fn interpolation_errors_point_into_the_string() {
+    let exception = error("var count = 1\nf\"{count} {cuont * 2}\"");
+    let span = exception.diagnostic().span.as_ref().unwrap();
+    assert_eq!((span.start.line, span.start.column), (2, 12));
+    assert_eq!((span.end.line, span.end.column), (2, 16));
+
+    for source in ["f\"{}\"", "f\"a } b\""] {
+        let exception = error(source);
+        assert_eq!(
+            exception.diagnostic().code,
+            Some(InvalidInterpolation::CODE)
+        );
+    }
+}
+
+#[test]
+fn multi_line_strings() {
+    let source =
+        "var name = \"Ada\"\nf\"\"\"\n    Dear {name},\n      \"indented\"\n\n    bye\n    \"\"\"";
+    assert_eq!(eval(source).to_string(), "Dear Ada,\n  \"indented\"\n\nbye");
+    assert_eq!(
+        eval("\"\"\"first\n  second\"\"\"").to_string(),
+        "first\n  second"
+    );
+    assert_eq!(
+        eval("\"\"\"\n  kept\n  line break\n\"\"\"").to_string(),
+        "  kept\n  line break"
+    );
+    assert_eq!(eval("\"\"\"\n  a\n  b\"\"\"").to_string(), "a\nb");
+}
+
+#[test]
+fn raw_strings() {
+    assert_eq!(eval("r\"\\d+\\.\\u{41}\"").to_string(), "\\d+\\.\\u{41}");
+    assert_eq!(
+        eval("r\"\"\"\n    \\n is \"literal\"\n    \"\"\"").to_string(),
+        "\\n is \"literal\""
+    );
+    // Escapes and stray line breaks in regular strings still work the way they did
+    assert_eq!(eval("\"a\\tb\nc\"").to_string(), "a\tbc");
+}
+
+#[test]
+fn std_string_module() {
+    let eval = |source: &str| eval(&format!("use \"std_string\"\n{}", source)).to_string();
+    assert_eq!(eval("Strings.len(\"héllo\")"), "5");
+    assert_eq!(eval("Strings.trim(\"  a b \")"), "a b");
+    assert_eq!(eval("Strings.replace(\"a-b-c\", \"-\", \"+\")"), "a+b+c");
+    assert_eq!(eval("Strings.contains(\"héllo\", \"ll\")"), "1");
+    assert_eq!(eval("Strings.starts_with(\"héllo\", \"hé\")"), "1");
+    assert_eq!(eval("Strings.ends_with(\"héllo\", \"x\")"), "0");
+    assert_eq!(eval("Strings.find(\"héllo\", \"l\")"), "2");
+    assert_eq!(eval("Strings.find(\"héllo\", \"x\")"), "null");
+    assert_eq!(eval("Strings.to_upper(\"héllo\")"), "HÉLLO");
+    assert_eq!(eval("Strings.to_lower(\"HÉLLO\")"), "héllo");
+    assert_eq!(eval("Strings.substring(\"héllo\", 1, 3)"), "él");
+    assert_eq!(eval("Strings.substring(\"héllo\", 3)"), "lo");
+    assert_eq!(eval("Strings.substring(\"héllo\", 4, 99)"), "o");
+    assert_eq!(eval("Strings.repeat(\"ab\", 3)"), "ababab");
+    assert_eq!(eval("Strings.pad_left(\"7\", 3, \"0\")"), "007");
+    assert_eq!(eval("Strings.pad_right(\"ab\", 4) + \"|\""), "ab  |");
+    assert_eq!(eval("Strings.split(\"a,b,,c\", \",\").length()"), "4");
+    assert_eq!(eval("Strings.split(\"a,b,,c\", \",\").get(3)"), "c");
+    assert_eq!(
+        eval("Strings.join(Strings.split(\"a,b,,c\", \",\"), \"|\")"),
+        "a|b||c"
+    );
+    assert_eq!(
+        eval("Strings.join(Strings.chars(\"héllo\"), \" \")"),
+        "h é l l o"
+    );
+}
+
+#[test]
+fn splits_keep_their_own_parts() {
+    let eval = |source: &str| eval(&format!("use \"std_string\"\n{}", source)).to_string();
+    let source = "var words = Strings.split(\"a b c\", \" \")\nvar pair = Strings.split(\"x-y\", \"-\")\nwords.get(2) + pair.get(1)";
+    assert_eq!(eval(source), "cy");
+    assert_eq!(eval("Strings.split(\"x-y\", \"-\").get(2)"), "null");
+    assert_eq!(eval("Strings.split(\"x-y\", \"-\").get(-1)"), "null");
+    let source = "len(Strings.join(Strings.split(Strings.repeat(\"ab,\", 5000), \",\"), \"\"))";
+    assert_eq!(eval(source), "10000");
+}
+
+#[test]
+fn parse_errors_can_be_caught() {
+    let source = |v: &str| {
+        format!(
+            "use \"std_string\"\nvar parsed = null\ndo {{\n    parsed = {}\n}} catch {{\n    parsed = \"failed\"\n}}\nparsed",
+            v
+        )
+    };
+    assert_eq!(
+        eval(&source("Strings.parse_int(\" 42 \") + 1")).to_string(),
+        "43"
+    );
+    assert_eq!(
+        eval(&source("Strings.parse_float(\"1.5\") * 2")).to_string(),
+        "3"
+    );
+    assert_eq!(
+        eval(&source("Strings.parse_int(\"4x2\")")).to_string(),
+        "failed"
+    );
+    assert_eq!(
+        eval(&source("Strings.parse_float(\"nope\")")).to_string(),
+        "failed"
+    );
+}
@@ -0,0 +1,70 @@
+use cry_script::{
+    engine::{Backend, Engine},
+    parser::data::Value,
+    Returnable,
+};
+
+const COUNT: &str = "fn count(n, total) {
+    if n == 0 { return total }
+    return count(n - 1, total + 1)
+}
+";
+
+fn engine(backend: Backend) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_backend(backend);
+    engine
+}
+
+fn count(backend: Backend, n: i32) -> Returnable {
+    match engine(backend).eval(&format!("{}count({}, 0)", COUNT, n)) {
+        Ok(v) => v,
+        Err(e) => panic!("{}", e.message()),
+    }
+}
+
+fn run(backend: Backend) -> Returnable {
+    let path = format!(
+        "{}/tests/scripts/tail_calls.cry",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    match engine(backend).run_file(&path) {
+        Ok(v) => v,
+        Err(e) => panic!("{}", e.message()),
+    }
+}
+
+#[test]
+fn tail_recursion_tree_walker() {
+    assert!(matches!(
+        count(Backend::TreeWalker, 10_000),
+        Returnable::Evaluate(Value::Integer(10_000))
+    ));
+}
+
+#[test]
+fn tail_recursion_bytecode() {
+    assert!(matches!(
+        count(Backend::Bytecode, 10_000),
+        Returnable::Evaluate(Value::Integer(10_000))
+    ));
+}
+
+// A million calls take a while without optimizations, run with `cargo test --release`
+#[test]
+#[cfg_attr(debug_assertions, ignore)]
+fn deep_tail_recursion_tree_walker() {
+    assert!(matches!(
+        run(Backend::TreeWalker),
+        Returnable::Evaluate(Value::Integer(1_000_000))
+    ));
+}
+
+#[test]
+#[cfg_attr(debug_assertions, ignore)]
+fn deep_tail_recursion_bytecode() {
+    assert!(matches!(
+        run(Backend::Bytecode),
+        Returnable::Evaluate(Value::Integer(1_000_000))
+    ));
+}